@@ -0,0 +1,81 @@
+//! BP+OSD decoding of quantum LDPC codes
+use quantum_error_correction::correction_codes::{CorrectionCode, CssCode};
+use quantum_error_correction::decoders::{BpMethod, BpOsdDecoder, DecodedCode, OsdMethod};
+use quantum_error_correction::error_models::{ErrorModel, PauliChannel};
+use quantum_error_correction::gf2::BinaryMatrix;
+use quantum_error_correction::pauli::Pauli;
+use quantum_error_correction::qubit::Qubit;
+
+fn main() {
+    println!("\n=== BP+OSD Decoder ===");
+    // Quantum LDPC codes under depolarizing noise: the built-in single-qubit lookup, belief
+    // propagation alone, and with OSD-0 and combination-sweep post-processing. Every decoder
+    // sees the same sampled errors, so the differences between them are not sampling noise;
+    // the combination sweep keeps the OSD-0 solution among its candidates, so it never settles
+    // on a less likely correction
+    let repetition = BinaryMatrix::from_bits(&[
+        &[1, 1, 0, 0, 0],
+        &[0, 1, 1, 0, 0],
+        &[0, 0, 1, 1, 0],
+        &[0, 0, 0, 1, 1],
+    ]);
+    let hamming = BinaryMatrix::from_bits(&[
+        &[0, 0, 0, 1, 1, 1, 1],
+        &[0, 1, 1, 0, 0, 1, 1],
+        &[1, 0, 1, 0, 1, 0, 1],
+    ]);
+    let bp_osd_codes = [
+        ("Repetition × repetition product", CssCode::hypergraph_product(&repetition, &repetition), 0.05, 50_000),
+        ("Hamming × Hamming product", CssCode::hypergraph_product(&hamming, &hamming), 0.03, 50_000),
+        ("Gross code", Ok(CssCode::gross()), 0.05, 20_000),
+    ];
+    let min_sum = BpMethod::MinSum { scaling_factor: 0.625 };
+    let bp_osd_variants = [
+        ("BP", min_sum, OsdMethod::None),
+        ("BP+OSD-0", min_sum, OsdMethod::Osd0),
+        ("BP+OSD-CS", min_sum, OsdMethod::CombinationSweep { order: 10 }),
+        ("product-sum BP+OSD-CS", BpMethod::ProductSum, OsdMethod::CombinationSweep { order: 10 }),
+    ];
+    for (name, code, error_rate, shots) in bp_osd_codes {
+        let code = match code {
+            Ok(code) => code,
+            Err(e) => {
+                println!("{}: {}", name, e);
+                continue;
+            }
+        };
+        let third = error_rate / 3.0;
+        let depolarizing = PauliChannel::new(third, third, third);
+        let stabilizers = code.stabilizer_generators();
+        let copy = || CssCode::from_parity_checks(code.hx().clone(), code.hz().clone()).unwrap();
+        let mut decoders: Vec<(&str, Box<dyn CorrectionCode>)> = vec![("built-in", Box::new(copy()))];
+        for (label, method, osd) in bp_osd_variants {
+            let decoder = BpOsdDecoder::new(&stabilizers, &depolarizing).with_method(method).with_osd(osd);
+            decoders.push((label, Box::new(DecodedCode::new(copy(), decoder))));
+        }
+
+        let mut failures = vec![0; decoders.len()];
+        for _ in 0..shots {
+            let mut noisy = code.encode(&Qubit::new());
+            for qubit in noisy.iter_mut() {
+                depolarizing.apply_error(qubit);
+            }
+            for ((_, decoder), count) in decoders.iter_mut().zip(failures.iter_mut()) {
+                let mut block = noisy.clone();
+                let syndromes = decoder.syndrome_measurement(&mut block);
+                decoder.correct(&mut block, syndromes);
+                if decoder.logical_errors(&block).iter().any(|&error| error != Pauli::I) {
+                    *count += 1;
+                }
+            }
+        }
+
+        print!("{} {} at p = {}, {} shots:", name, code.parameters(), error_rate, shots);
+        for ((label, _), count) in decoders.iter().zip(&failures) {
+            let rate = *count as f64 / shots as f64;
+            let standard_error = (rate * (1.0 - rate) / shots as f64).sqrt();
+            print!(" {} {:.4} ± {:.4}", label, rate, standard_error);
+        }
+        println!();
+    }
+}
@@ -0,0 +1,117 @@
+//! Circuit-level detector error models and sliding-window decoding
+use quantum_error_correction::circuits::{CircuitNoise, DetectorErrorModel, NoisyCircuit};
+use quantum_error_correction::correction_codes::{CorrectionCode, XzzxCode};
+use quantum_error_correction::decoders::{
+    DemMatchingDecoder, DemUnionFindDecoder, DetectorDecoder, SlidingWindowDecoder, WindowDecoderFactory,
+};
+
+fn main() {
+    println!("\n=== Detector Error Models ===");
+    // Circuit-level memory experiments on the XZZX code: every fault of the syndrome-extraction
+    // circuit becomes an error mechanism, and the model's matching graph decodes samples of it
+    let circuit_noise = CircuitNoise::uniform(0.002);
+    for distance in [3, 5] {
+        let code = XzzxCode::new(distance);
+        let stabilizers = code.stabilizer_generators();
+        let circuit =
+            NoisyCircuit::memory_experiment(&stabilizers, code.logical_z(), distance, &circuit_noise);
+        let model = circuit.detector_error_model();
+        let text = model.to_string();
+        if distance == 3 {
+            for line in text.lines().take(3) {
+                println!("{}", line);
+            }
+            println!("...");
+        }
+        let round_trip = text.parse::<DetectorErrorModel>().is_ok_and(|parsed| parsed == model);
+        let graph = match model.decoding_graph() {
+            Ok(graph) => graph,
+            Err(e) => {
+                println!("d = {}: {}", distance, e);
+                continue;
+            }
+        };
+        let shots = 2000;
+        let failures = (0..shots)
+            .filter(|_| {
+                let (detectors, flipped) = model.sample();
+                graph.predict_observables(&detectors) != flipped
+            })
+            .count();
+        println!(
+            "d = {}, {} rounds at p = 0.002: {} detectors, {} error mechanisms, text round trip {}, \
+             matching failure rate {:.4}",
+            distance,
+            distance,
+            model.num_detectors(),
+            model.mechanisms().len(),
+            if round_trip { "exact" } else { "lossy" },
+            failures as f64 / shots as f64
+        );
+    }
+
+    println!("\n=== Sliding-Window Decoding ===");
+    // A 50-round circuit-level memory experiment on the d = 5 XZZX code, decoded all at once
+    // and in windows that commit 5 rounds with 5 rounds of buffer
+    let code = XzzxCode::new(5);
+    let stabilizers = code.stabilizer_generators();
+    let circuit =
+        NoisyCircuit::memory_experiment(&stabilizers, code.logical_z(), 50, &CircuitNoise::uniform(0.002));
+    let model = circuit.detector_error_model();
+    let windowed = |make_decoder: &WindowDecoderFactory| {
+        SlidingWindowDecoder::new(&model, stabilizers.len(), 5, 5, make_decoder)
+    };
+    let decoders = (
+        DemMatchingDecoder::new(&model),
+        windowed(&|window| Ok(Box::new(DemMatchingDecoder::new(window)?))),
+        windowed(&|window| Ok(Box::new(DemUnionFindDecoder::new(window)?))),
+    );
+    if let (Ok(mut global), Ok(mut matching), Ok(mut union_find)) = decoders {
+        let shots = 100;
+        let (mut global_failures, mut matching_failures, mut union_find_failures) = (0, 0, 0);
+        let mut global_time = 0.0;
+        let (mut matching_run, mut union_find_run) = (None, None);
+        for _ in 0..shots {
+            let (detectors, flipped) = model.sample();
+            let start = std::time::Instant::now();
+            let predicted = global
+                .decode_detectors(&detectors)
+                .iter()
+                .flat_map(|&mechanism| &model.mechanisms()[mechanism].observables)
+                .fold(0, |mask, &observable| mask ^ 1 << observable);
+            global_time += start.elapsed().as_secs_f64();
+            global_failures += usize::from(predicted != flipped);
+            let run = matching.decode(&detectors);
+            matching_failures += usize::from(run.observables != flipped);
+            matching_run = Some(run);
+            let run = union_find.decode(&detectors);
+            union_find_failures += usize::from(run.observables != flipped);
+            union_find_run = Some(run);
+        }
+        println!(
+            "{} windows with {} distinct decoders; failure rates: global matching {:.3} in {:.1} ms per \
+             shot, windowed matching {:.3}, windowed union-find {:.3}",
+            matching.num_windows(),
+            matching.num_decoders(),
+            global_failures as f64 / shots as f64,
+            global_time * 1e3 / shots as f64,
+            matching_failures as f64 / shots as f64,
+            union_find_failures as f64 / shots as f64
+        );
+        for (name, run) in [("Matching", matching_run), ("Union-find", union_find_run)] {
+            let Some(run) = run else { continue };
+            let (mean, max) = (run.mean_latency(), run.max_latency());
+            println!("{}: {:?} mean and {:?} max latency per window", name, mean, max);
+            for cycle_time in [1, 10] {
+                let backlog = run.backlog(std::time::Duration::from_micros(cycle_time));
+                println!(
+                    "  {} µs cycle: {:?} budget per window, {}, final reaction time {:?}",
+                    cycle_time,
+                    backlog.budget_per_window,
+                    if backlog.keeps_up { "keeps up" } else { "falls behind" },
+                    backlog.final_reaction_time()
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,67 @@
+//! Distances, parameters and weight enumerators of small codes
+use quantum_error_correction::correction_codes::{
+    minimum_distance, BaconShorCode, BitFlipCode, Concatenated, CorrectionCode, CssCode, HeavyHexCode,
+    LogicalErrorPolynomial, PhaseFlipCode, ReedMullerCode, StabilizerCode, ToricCode, WeightEnumerators,
+};
+use quantum_error_correction::error_models::PauliChannel;
+
+fn main() {
+    println!("\n=== Code Distances ===");
+    let distances = [
+        ("Five-qubit code", StabilizerCode::five_qubit().distance()),
+        ("Steane code", CssCode::steane().distance()),
+        ("Reed–Muller code", ReedMullerCode::new().as_css().distance()),
+        ("Heavy-hex code (d = 5)", HeavyHexCode::new(5).as_subsystem().distance()),
+        ("Gross code", CssCode::gross().distance()),
+    ];
+    for (name, distance) in distances {
+        let search = if distance.exact { "exhaustive search" } else { "randomized upper bound" };
+        println!("{}: d = {} ({})", name, distance.distance, search);
+        if distance.witness.len() <= 20 {
+            println!("Minimum-weight logical: {}", distance.witness);
+        }
+    }
+
+    println!("\n=== Code Introspection ===");
+    let codes: Vec<(&str, Box<dyn CorrectionCode>)> = vec![
+        ("Bit flip code", Box::new(BitFlipCode::new())),
+        ("Shor code", Box::new(Concatenated::new(PhaseFlipCode::new(), BitFlipCode::new()))),
+        ("Steane code", Box::new(CssCode::steane())),
+        ("Toric code (L = 3)", Box::new(ToricCode::new(3))),
+        ("Bacon–Shor code (3×3)", Box::new(BaconShorCode::new(3, 3))),
+        ("Heavy-hex code (d = 3)", Box::new(HeavyHexCode::new(3))),
+    ];
+    for (name, code) in &codes {
+        // The distance is searched from the operators alone when the code does not know it
+        let logicals: Vec<_> = code.logical_operators().into_iter().flat_map(|(x, z)| [x, z]).collect();
+        let distance = minimum_distance(&code.stabilizer_generators(), &logicals).distance;
+        println!(
+            "{}: {}, {} stabilizer generators, syndrome of {} bits, d = {}",
+            name,
+            code.parameters(),
+            code.stabilizer_generators().len(),
+            code.syndrome_length(),
+            distance
+        );
+    }
+
+    println!("\n=== Weight Enumerators ===");
+    let depolarizing = PauliChannel::new(0.01 / 3.0, 0.01 / 3.0, 0.01 / 3.0);
+    let small_codes: Vec<(&str, Box<dyn CorrectionCode>)> = vec![
+        ("Five-qubit code", Box::new(StabilizerCode::five_qubit())),
+        ("Steane code", Box::new(CssCode::steane())),
+        ("Shor code", Box::new(Concatenated::new(PhaseFlipCode::new(), BitFlipCode::new()))),
+    ];
+    for (name, mut code) in small_codes {
+        let enumerators = WeightEnumerators::new(&code.stabilizer_generators());
+        let polynomial = LogicalErrorPolynomial::new(&mut code);
+        println!("{}:", name);
+        println!("Stabilizer enumerator: {:?}", enumerators.stabilizer);
+        println!("Normalizer enumerator: {:?}", enumerators.normalizer);
+        println!("Uncorrected errors by weight: {:?}", polynomial.failures_by_weight());
+        println!(
+            "Exact logical error rate at 1% depolarizing noise: {:.3e}",
+            polynomial.failure_probability(&depolarizing)
+        );
+    }
+}
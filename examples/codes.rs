@@ -0,0 +1,222 @@
+//! Code families under simple noise models: toric, CSS, stabilizer, color, subsystem,
+//! quantum LDPC, concatenated, Reed–Muller, heavy-hex, Floquet and bosonic codes
+use quantum_error_correction::bosonic::{BosonicCode, CatCode, GaussianDisplacement, GkpCode, PhotonLoss};
+use quantum_error_correction::correction_codes::{
+    BaconShorCode, BitFlipCode, ColorCode, Concatenated, CorrectionCode, CssCode, FloquetCode,
+    FloquetMemory, HeavyHexCode, PhaseFlipCode, ReedMullerCode, StabilizerCode, ToricCode, XzzxCode,
+};
+use quantum_error_correction::error_models::{BiasedNoise, BitFlipNoise, PauliChannel, PhaseFlipNoise};
+use quantum_error_correction::gates::DiagonalGate;
+use quantum_error_correction::gf2::BinaryMatrix;
+use quantum_error_correction::simulation::Simulation;
+
+fn main() {
+    // Run toric code simulations, reporting each logical qubit separately
+    println!("\n=== Toric Code Simulation ===");
+    for size in [3, 5] {
+        let toric_error_model = Box::new(BitFlipNoise::new(0.05));
+        let toric_code = Box::new(ToricCode::new(size));
+        let mut toric_simulation = Simulation::new(toric_error_model, toric_code, 1000);
+        let toric_result = toric_simulation.run();
+
+        println!("{}x{} torus:", size, size);
+        println!("Success Rate: {:.2}%", toric_result.success_rate * 100.0);
+        for (i, rate) in toric_result.logical_error_rates.iter().enumerate() {
+            println!("Logical qubit {} Error Rate: {:.2}%", i, rate * 100.0);
+        }
+    }
+
+    // Run the Steane code, built from the Hamming parity-check matrix alone
+    println!("\n=== Steane Code Simulation ===");
+    let steane_error_model = Box::new(BitFlipNoise::new(0.05));
+    let steane_code = Box::new(CssCode::steane());
+    let mut steane_simulation = Simulation::new(steane_error_model, steane_code, 1000);
+    let steane_result = steane_simulation.run();
+
+    println!("Success Rate: {:.2}%", steane_result.success_rate * 100.0);
+    println!("Error Rate: {:.2}%", steane_result.error_rate * 100.0);
+
+    // Run the five-qubit code, defined only by its stabilizer generators
+    println!("\n=== Five-Qubit Code Simulation ===");
+    let five_qubit_error_model = Box::new(PhaseFlipNoise::new(0.05));
+    let five_qubit_code = Box::new(StabilizerCode::from_generators(&["XZZXI", "IXZZX", "XIXZZ", "ZXIXZ"])
+        .expect("the five-qubit generators are valid"));
+    let mut five_qubit_simulation = Simulation::new(five_qubit_error_model, five_qubit_code, 1000);
+    let five_qubit_result = five_qubit_simulation.run();
+
+    println!("Success Rate: {:.2}%", five_qubit_result.success_rate * 100.0);
+    println!("Error Rate: {:.2}%", five_qubit_result.error_rate * 100.0);
+
+    // Run triangular color codes on both tilings with the restriction decoder
+    println!("\n=== Color Code Simulation ===");
+    for distance in [3, 5] {
+        for (name, color_code) in [
+            ("6.6.6", ColorCode::hexagonal(distance)),
+            ("4.8.8", ColorCode::square_octagon(distance)),
+        ] {
+            let color_error_model = Box::new(BitFlipNoise::new(0.05));
+            let num_qubits = color_code.num_qubits();
+            let mut color_simulation = Simulation::new(color_error_model, Box::new(color_code), 1000);
+            let color_result = color_simulation.run();
+
+            println!("{} code, distance {} ({} qubits):", name, distance, num_qubits);
+            println!("Success Rate: {:.2}%", color_result.success_rate * 100.0);
+            println!("Error Rate: {:.2}%", color_result.error_rate * 100.0);
+        }
+    }
+
+    println!("\n=== Bacon–Shor Code Simulation ===");
+    for (rows, cols) in [(3, 3), (5, 5)] {
+        let bacon_shor_error_model = Box::new(BitFlipNoise::new(0.05));
+        let bacon_shor_code = Box::new(BaconShorCode::new(rows, cols));
+        let mut bacon_shor_simulation = Simulation::new(bacon_shor_error_model, bacon_shor_code, 1000);
+        let bacon_shor_result = bacon_shor_simulation.run();
+
+        println!("{}×{} code ({} qubits):", rows, cols, rows * cols);
+        println!("Success Rate: {:.2}%", bacon_shor_result.success_rate * 100.0);
+        println!("Error Rate: {:.2}%", bacon_shor_result.error_rate * 100.0);
+    }
+
+    println!("\n=== Quantum LDPC Code Simulation ===");
+    let repetition = BinaryMatrix::from_bits(&[&[1, 1, 0], &[0, 1, 1]]);
+    let hamming = BinaryMatrix::from_bits(&[
+        &[0, 0, 0, 1, 1, 1, 1],
+        &[0, 1, 1, 0, 0, 1, 1],
+        &[1, 0, 1, 0, 1, 0, 1],
+    ]);
+    let qldpc_codes = [
+        ("Repetition × repetition product", CssCode::hypergraph_product(&repetition, &repetition)),
+        ("Hamming × Hamming product", CssCode::hypergraph_product(&hamming, &hamming)),
+        ("Gross code", Ok(CssCode::gross())),
+    ];
+    for (name, qldpc_code) in qldpc_codes {
+        let qldpc_code = match qldpc_code {
+            Ok(code) => code,
+            Err(e) => {
+                println!("{}: {}", name, e);
+                continue;
+            }
+        };
+        let parameters = qldpc_code.parameters();
+        let qldpc_error_model = Box::new(BitFlipNoise::new(0.01));
+        let mut qldpc_simulation = Simulation::new(qldpc_error_model, Box::new(qldpc_code), 1000);
+        let qldpc_result = qldpc_simulation.run();
+
+        println!("{} {}:", name, parameters);
+        println!("Success Rate: {:.2}%", qldpc_result.success_rate * 100.0);
+        println!("Error Rate: {:.2}%", qldpc_result.error_rate * 100.0);
+    }
+
+
+    println!("\n=== Concatenated Code Simulation ===");
+    // The bit flip code inside the phase flip code is Shor's nine-qubit code
+    let shor_code = Concatenated::new(PhaseFlipCode::new(), BitFlipCode::new());
+    let shor_error_model = Box::new(BiasedNoise::new(0.05, 0.5));
+    let mut shor_simulation = Simulation::new(shor_error_model, Box::new(shor_code), 1000);
+    let shor_result = shor_simulation.run();
+    println!("Shor code under depolarizing noise:");
+    println!("Success Rate: {:.2}%", shor_result.success_rate * 100.0);
+    println!("Error Rate: {:.2}%", shor_result.error_rate * 100.0);
+
+    for level in 1..=3 {
+        let mut steane_levels: Box<dyn CorrectionCode> = Box::new(CssCode::steane());
+        for _ in 1..level {
+            steane_levels = Box::new(Concatenated::new(steane_levels, CssCode::steane()));
+        }
+        let concatenated_error_model = Box::new(BiasedNoise::new(0.05, 0.5));
+        let mut concatenated_simulation = Simulation::new(concatenated_error_model, steane_levels, 1000);
+        let concatenated_result = concatenated_simulation.run();
+        println!("Steane code, level {}:", level);
+        println!("Success Rate: {:.2}%", concatenated_result.success_rate * 100.0);
+        println!("Error Rate: {:.2}%", concatenated_result.error_rate * 100.0);
+    }
+
+    println!("\n=== Reed–Muller Code Simulation ===");
+    let reed_muller_code = ReedMullerCode::new();
+    let steane_code = CssCode::steane();
+    let steane_t: Vec<DiagonalGate> = (0..steane_code.num_qubits()).map(DiagonalGate::t).collect();
+    match reed_muller_code.as_css().logical_diagonal_phase(&reed_muller_code.transversal_t()) {
+        Some(eighths) => println!("Transversal T† on the Reed–Muller code: logical diag(1, ω^{})", eighths),
+        None => println!("Transversal T† on the Reed–Muller code leaves the code space"),
+    }
+    match steane_code.logical_diagonal_phase(&steane_t) {
+        Some(eighths) => println!("Transversal T on the Steane code: logical diag(1, ω^{})", eighths),
+        None => println!("Transversal T on the Steane code leaves the code space"),
+    }
+    let reed_muller_error_model = Box::new(BiasedNoise::new(0.02, 0.5));
+    let reed_muller_code = Box::new(reed_muller_code);
+    let mut reed_muller_simulation = Simulation::new(reed_muller_error_model, reed_muller_code, 1000);
+    let reed_muller_result = reed_muller_simulation.run();
+    println!("Reed–Muller code under depolarizing noise:");
+    println!("Success Rate: {:.2}%", reed_muller_result.success_rate * 100.0);
+    println!("Error Rate: {:.2}%", reed_muller_result.error_rate * 100.0);
+
+    println!("\n=== Heavy-Hex Code Simulation ===");
+    for distance in [3, 5] {
+        let heavy_hex_code = HeavyHexCode::new(distance);
+        let device = heavy_hex_code.coupling_graph();
+        println!(
+            "Distance {}: {} device qubits, {} couplers, maximum degree {}",
+            distance,
+            device.num_qubits(),
+            device.num_edges(),
+            device.max_degree()
+        );
+
+        // Same depolarizing noise on the heavy-hex code and on the surface code of equal size
+        let codes: [(&str, Box<dyn CorrectionCode>); 2] = [
+            ("Heavy-hex", Box::new(heavy_hex_code)),
+            ("Surface (XZZX)", Box::new(XzzxCode::new(distance))),
+        ];
+        for (name, code) in codes {
+            let error_model = Box::new(BiasedNoise::new(0.03, 0.5));
+            let mut simulation = Simulation::new(error_model, code, 1000);
+            let result = simulation.run();
+            println!("{} code:", name);
+            println!("Success Rate: {:.2}%", result.success_rate * 100.0);
+            println!("Error Rate: {:.2}%", result.error_rate * 100.0);
+        }
+    }
+
+    println!("\n=== Floquet Honeycomb Code Memory ===");
+    for (rows, cols) in [(4, 6), (4, 12)] {
+        let memory = FloquetMemory::new(FloquetCode::honeycomb(rows, cols), 6);
+        println!(
+            "{}x{} honeycomb: {} qubits, {} logical qubits, {} detectors",
+            rows,
+            cols,
+            memory.code().num_qubits(),
+            memory.code().num_logical_qubits(),
+            memory.detectors().len()
+        );
+
+        // Every qubit and every check measurement fails with the same probability
+        let error_rate = 0.001;
+        let result = memory.run(&BiasedNoise::new(error_rate, 0.5), error_rate, 1000);
+        println!("Success Rate: {:.2}%", result.success_rate * 100.0);
+        println!("Error Rate: {:.2}%", result.error_rate * 100.0);
+    }
+
+    println!("\n=== Bosonic Codes ===");
+    let cat_code = CatCode::new(2.0, 40);
+    let gkp_code = GkpCode::new(0.3, 60);
+    let cat_noise = cat_code.effective_noise(&PhotonLoss::new(0.03), 1000);
+    let gkp_noise = gkp_code.effective_noise(&GaussianDisplacement::new(0.2), 500);
+    for (name, noise) in [("Cat (α = 2, 3% photon loss)", cat_noise), ("GKP (Δ = 0.3, σ = 0.2)", gkp_noise)] {
+        let (p_x, p_y, p_z) = noise.pauli_probabilities();
+        println!("{} logical noise: X {:.4}, Y {:.4}, Z {:.4}", name, p_x, p_y, p_z);
+    }
+
+    // The logical noise of each oscillator becomes the physical noise of a qubit code on top
+    let concatenations: [(&str, PauliChannel, Box<dyn CorrectionCode>); 2] = [
+        ("Cat qubits in a bit-flip code", cat_noise, Box::new(BitFlipCode::new())),
+        ("GKP qubits in a surface code", gkp_noise, Box::new(XzzxCode::new(3))),
+    ];
+    for (name, noise, code) in concatenations {
+        let mut simulation = Simulation::new(Box::new(noise), code, 1000);
+        let result = simulation.run();
+        println!("{}:", name);
+        println!("Success Rate: {:.2}%", result.success_rate * 100.0);
+        println!("Error Rate: {:.2}%", result.error_rate * 100.0);
+    }
+}
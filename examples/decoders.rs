@@ -0,0 +1,156 @@
+//! Lookup-table, matching, maximum-likelihood and correlated decoders on the same codes
+use quantum_error_correction::correction_codes::{
+    BitFlipCode, Concatenated, CorrectionCode, CssCode, LogicalErrorPolynomial, PhaseFlipCode, ToricCode,
+    XzzxCode,
+};
+use quantum_error_correction::decoders::{
+    CorrelatedMatchingDecoder, DecodedCode, Decoder, LookupTableDecoder, MatchingDecoder,
+    MaximumLikelihoodDecoder,
+};
+use quantum_error_correction::error_models::{BiasedNoise, ErrorModel, PauliChannel};
+use quantum_error_correction::simulation::{Simulation, ThresholdSweep};
+
+/// Builds a fresh code, for comparisons that need several copies of it
+type MakeCode = fn() -> Box<dyn CorrectionCode>;
+
+fn main() {
+    println!("\n=== Lookup-Table Decoders ===");
+    // Exact logical error rates of each code's own decoder against generated tables, under
+    // Z-biased noise
+    let biased_noise = BiasedNoise::new(0.05, 5.0);
+    let channel = biased_noise.pauli_channel().expect("biased noise is a Pauli channel");
+    let exact_rate = |mut code: Box<dyn CorrectionCode>| {
+        LogicalErrorPolynomial::new(&mut code).failure_probability(&channel)
+    };
+    let table_codes: Vec<(&str, MakeCode)> = vec![
+        ("Steane code", || Box::new(CssCode::steane())),
+        ("Shor code", || Box::new(Concatenated::new(PhaseFlipCode::new(), BitFlipCode::new()))),
+        ("XZZX code (d = 3)", || Box::new(XzzxCode::new(3))),
+    ];
+    for (name, make_code) in table_codes {
+        let stabilizers = make_code().stabilizer_generators();
+        let minimum_weight = LookupTableDecoder::minimum_weight(&stabilizers);
+        let most_likely = LookupTableDecoder::most_likely(&stabilizers, &channel);
+        println!("{} ({} syndromes):", name, minimum_weight.len());
+        println!("Built-in decoder: {:.4}", exact_rate(make_code()));
+        println!(
+            "Minimum-weight table: {:.4}",
+            exact_rate(Box::new(DecodedCode::new(make_code(), minimum_weight)))
+        );
+        println!(
+            "Most-likely table: {:.4}",
+            exact_rate(Box::new(DecodedCode::new(make_code(), most_likely.clone())))
+        );
+
+        let noise = Box::new(BiasedNoise::new(0.05, 5.0));
+        let mut simulation = Simulation::with_decoder(noise, make_code(), Box::new(most_likely), 2000);
+        println!("Most-likely table, sampled: {:.4}", simulation.run().error_rate);
+    }
+
+    println!("\n=== Matching Decoder ===");
+    // Matching on a graph generated from the stabilizers, against each code's own decoder,
+    // under depolarizing noise
+    let depolarizing = PauliChannel::new(0.02, 0.02, 0.02);
+    let matching_decoder = |code: &dyn CorrectionCode| {
+        MatchingDecoder::new(&code.stabilizer_generators(), &code.logical_operators(), &depolarizing)
+    };
+    let xzzx = XzzxCode::new(3);
+    let decoder = matching_decoder(&xzzx);
+    let exact = LogicalErrorPolynomial::new(&mut DecodedCode::new(XzzxCode::new(3), decoder))
+        .failure_probability(&depolarizing);
+    let built_in = LogicalErrorPolynomial::new(&mut XzzxCode::new(3)).failure_probability(&depolarizing);
+    println!("XZZX code (d = 3), exact: built-in {:.4}, matching {:.4}", built_in, exact);
+
+    let matching_codes: Vec<(&str, MakeCode)> = vec![
+        ("XZZX code (d = 5)", || Box::new(XzzxCode::new(5))),
+        ("Toric code (L = 4)", || Box::new(ToricCode::new(4))),
+    ];
+    for (name, make_code) in matching_codes {
+        let decoder = matching_decoder(make_code().as_ref());
+        let mut built_in = Simulation::new(Box::new(depolarizing), make_code(), 2000);
+        let mut matched =
+            Simulation::with_decoder(Box::new(depolarizing), make_code(), Box::new(decoder), 2000);
+        println!(
+            "{}, sampled: built-in {:.4}, matching {:.4}",
+            name,
+            built_in.run().error_rate,
+            matched.run().error_rate
+        );
+    }
+
+    println!("\n=== Maximum-Likelihood Decoder ===");
+    // How far matching is from optimal under depolarizing noise: exactly on the d = 3 XZZX
+    // code, where brute force sums each coset, and sampled on larger codes, where the cosets
+    // are contracted as tensor networks
+    let xzzx = XzzxCode::new(3);
+    let (stabilizers, logicals) = (xzzx.stabilizer_generators(), xzzx.logical_operators());
+    for error_rate in [0.05, 0.1, 0.15] {
+        let third = error_rate / 3.0;
+        let channel = PauliChannel::new(third, third, third);
+        let exact_rate = |decoder: Box<dyn Decoder>| {
+            LogicalErrorPolynomial::new(&mut DecodedCode::new(XzzxCode::new(3), decoder))
+                .failure_probability(&channel)
+        };
+        let optimal = MaximumLikelihoodDecoder::brute_force(&stabilizers, &logicals, &channel);
+        let optimal = exact_rate(Box::new(optimal));
+        let matched = exact_rate(Box::new(MatchingDecoder::new(&stabilizers, &logicals, &channel)));
+        println!(
+            "XZZX code (d = 3) at p = {}, exact: maximum likelihood {:.4}, matching {:.4}",
+            error_rate, optimal, matched
+        );
+    }
+    let channel = PauliChannel::new(0.05, 0.05, 0.05);
+    for distance in [5, 7] {
+        let code = XzzxCode::new(distance);
+        let (stabilizers, logicals) = (code.stabilizer_generators(), code.logical_operators());
+        let optimal = MaximumLikelihoodDecoder::tensor_network(&stabilizers, &logicals, &channel);
+        let matched = MatchingDecoder::new(&stabilizers, &logicals, &channel);
+        let run = |decoder: Box<dyn Decoder>| {
+            Simulation::with_decoder(Box::new(channel), Box::new(XzzxCode::new(distance)), decoder, 1000)
+                .run()
+                .error_rate
+        };
+        println!(
+            "XZZX code (d = {}) at p = 0.15, sampled: maximum likelihood {:.4}, matching {:.4}",
+            distance,
+            run(Box::new(optimal)),
+            run(Box::new(matched))
+        );
+    }
+
+    println!("\n=== Correlated X/Z Decoding ===");
+    // Matching the X and Z parts of depolarizing noise independently against reweighting each
+    // by the other, on XZZX codes of distance 5, 7 and 9
+    let depolarizing_rates: Vec<f64> = (0..7).map(|i| 0.13 + 0.01 * i as f64).collect();
+    for correlated in [false, true] {
+        let sweep = ThresholdSweep::run(
+            &[5, 7, 9],
+            &depolarizing_rates,
+            3000,
+            |distance, rate| {
+                let code = XzzxCode::new(distance);
+                let channel = PauliChannel::new(rate / 3.0, rate / 3.0, rate / 3.0);
+                let (stabilizers, logicals) = (code.stabilizer_generators(), code.logical_operators());
+                let decoder: Box<dyn Decoder> = if correlated {
+                    Box::new(CorrelatedMatchingDecoder::new(&stabilizers, &logicals, &channel))
+                } else {
+                    Box::new(MatchingDecoder::new(&stabilizers, &logicals, &channel))
+                };
+                Box::new(DecodedCode::new(code, decoder))
+            },
+            |rate| Box::new(PauliChannel::new(rate / 3.0, rate / 3.0, rate / 3.0)),
+        );
+        let name = if correlated { "Correlated matching" } else { "Independent matching" };
+        let at_15 = &sweep.logical_error_rates[2][2];
+        match sweep.threshold() {
+            Some(estimate) => println!(
+                "{}: threshold {:.1}% ± {:.1}%, d = 9 failure rate {:.4} at p = 0.15",
+                name,
+                estimate.threshold * 100.0,
+                estimate.standard_error * 100.0,
+                at_15
+            ),
+            None => println!("{}: no crossing between 13% and 19%", name),
+        }
+    }
+}
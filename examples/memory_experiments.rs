@@ -0,0 +1,105 @@
+//! Repeated-round memory experiments decoded by matching and union-find, and erasures
+use quantum_error_correction::correction_codes::{CorrectionCode, XzzxCode};
+use quantum_error_correction::decoders::{MatchingDecoder, UnionFindDecoder};
+use quantum_error_correction::error_models::PauliChannel;
+use quantum_error_correction::pauli::{Pauli, PauliString};
+
+fn main() {
+    // Memory experiments: d rounds of noisy syndrome measurement on the XZZX code, with the
+    // same probability for a measurement error as for a data error. Matching takes most of a
+    // second per shot at d = 25, so that row only measures the decoding time: its ten shots
+    // cannot resolve a logical error rate this low
+    let round_error_rate = 0.02;
+    let third = round_error_rate / 3.0;
+    let per_round = PauliChannel::new(third, third, third);
+    for (distance, shots) in [(5, 400), (9, 400), (13, 200), (25, 10)] {
+        let code = XzzxCode::new(distance);
+        let decoder =
+            MatchingDecoder::new(&code.stabilizer_generators(), &code.logical_operators(), &per_round);
+        let graph = decoder.graph().repeated(distance, round_error_rate);
+        let start = std::time::Instant::now();
+        let mut failures = 0;
+        for _ in 0..shots {
+            let (defects, flipped) = graph.sample();
+            if graph.predict_observables(&defects) != flipped {
+                failures += 1;
+            }
+        }
+        println!(
+            "d = {}, {} rounds ({} nodes): logical error rate {:.4}, {:.1} ms per shot",
+            distance,
+            distance,
+            graph.num_nodes(),
+            failures as f64 / shots as f64,
+            start.elapsed().as_secs_f64() * 1000.0 / shots as f64
+        );
+    }
+
+    println!("\n=== Union-Find Decoder ===");
+    // Union-find against matching on the same sampled memory experiments, near threshold
+    let round_error_rate = 0.03;
+    let third = round_error_rate / 3.0;
+    let per_round = PauliChannel::new(third, third, third);
+    for (distance, shots) in [(5, 400), (9, 400), (13, 100), (25, 10)] {
+        let code = XzzxCode::new(distance);
+        let decoder =
+            MatchingDecoder::new(&code.stabilizer_generators(), &code.logical_operators(), &per_round);
+        let graph = decoder.graph().repeated(distance, round_error_rate);
+        let (mut matching_failures, mut union_find_failures) = (0, 0);
+        let (mut matching_time, mut union_find_time) = (0.0, 0.0);
+        for _ in 0..shots {
+            let (defects, flipped) = graph.sample();
+            let start = std::time::Instant::now();
+            if graph.predict_observables(&defects) != flipped {
+                matching_failures += 1;
+            }
+            matching_time += start.elapsed().as_secs_f64();
+            let start = std::time::Instant::now();
+            if graph.observables_of(&graph.union_find(&defects, &[])) != flipped {
+                union_find_failures += 1;
+            }
+            union_find_time += start.elapsed().as_secs_f64();
+        }
+        println!(
+            "d = {}: matching {:.4} in {:.0} µs per shot, union-find {:.4} in {:.0} µs per shot",
+            distance,
+            matching_failures as f64 / shots as f64,
+            matching_time * 1e6 / shots as f64,
+            union_find_failures as f64 / shots as f64,
+            union_find_time * 1e6 / shots as f64
+        );
+    }
+
+    // Erasures: each qubit of a d = 7 XZZX code is lost with some probability and replaced by
+    // a random Pauli, and the decoder is told which qubits were lost or not
+    let code = XzzxCode::new(7);
+    let stabilizers = code.stabilizer_generators();
+    let logicals = code.logical_operators();
+    let decoder = UnionFindDecoder::new(&stabilizers, &logicals, &PauliChannel::new(0.01, 0.01, 0.01));
+    for erasure_rate in [0.1, 0.2, 0.3] {
+        let shots = 1000;
+        let (mut informed_failures, mut blind_failures) = (0, 0);
+        for _ in 0..shots {
+            let erased: Vec<usize> =
+                (0..code.num_qubits()).filter(|_| rand::random::<f64>() < erasure_rate).collect();
+            let mut error = PauliString::identity(code.num_qubits());
+            for &qubit in &erased {
+                error.set(qubit, Pauli::from_bits(rand::random(), rand::random()));
+            }
+            let syndrome: Vec<bool> = stabilizers.iter().map(|s| !error.commutes_with(s)).collect();
+            let fails = |correction: PauliString| {
+                let residual = correction.product(&error);
+                logicals.iter().any(|(x, z)| !residual.commutes_with(x) || !residual.commutes_with(z))
+            };
+            let informed = decoder.predict_with_erasures(&syndrome, &erased);
+            informed_failures += usize::from(fails(informed.correction));
+            blind_failures += usize::from(fails(decoder.predict(&syndrome).correction));
+        }
+        println!(
+            "Erasure rate {:.1}: {:.4} with erasure positions, {:.4} without",
+            erasure_rate,
+            informed_failures as f64 / shots as f64,
+            blind_failures as f64 / shots as f64
+        );
+    }
+}
@@ -0,0 +1,316 @@
+use crate::error_models::PauliChannel;
+use crate::qubit::Qubit;
+use crate::sampling::{sample_index, standard_normal};
+use num_complex::Complex64;
+use std::f64::consts::FRAC_1_SQRT_2;
+
+mod cat;
+mod gkp;
+
+pub use cat::CatCode;
+pub use gkp::{GkpCode, GkpSyndrome};
+
+/// The state of one harmonic oscillator mode, truncated to the Fock states |0⟩ … |cutoff − 1⟩.
+///
+/// Quadratures follow q = (a + a†)/√2 and p = (a − a†)/(i√2), so a displacement by α shifts
+/// q by √2·Re α and p by √2·Im α. Operators act on the truncated space as they are, so states
+/// should keep well away from the cutoff.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Mode {
+    amplitudes: Vec<Complex64>,
+}
+
+impl Mode {
+    /// The vacuum |0⟩
+    pub fn new(cutoff: usize) -> Self {
+        Self::fock(cutoff, 0)
+    }
+
+    /// The Fock state |n⟩
+    pub fn fock(cutoff: usize, n: usize) -> Self {
+        assert!(n < cutoff, "|{}⟩ lies above the cutoff {}", n, cutoff);
+        let mut amplitudes = vec![Complex64::new(0.0, 0.0); cutoff];
+        amplitudes[n] = Complex64::new(1.0, 0.0);
+        Self { amplitudes }
+    }
+
+    /// The coherent state |α⟩, renormalised after truncation
+    pub fn coherent(cutoff: usize, alpha: Complex64) -> Self {
+        let mut amplitudes = Vec::with_capacity(cutoff);
+        let mut amplitude = Complex64::new((-alpha.norm_sqr() / 2.0).exp(), 0.0);
+        for n in 0..cutoff {
+            if n > 0 {
+                amplitude *= alpha / (n as f64).sqrt();
+            }
+            amplitudes.push(amplitude);
+        }
+        Self::from_amplitudes(amplitudes)
+    }
+
+    /// The state with the given Fock amplitudes, normalised
+    pub fn from_amplitudes(amplitudes: Vec<Complex64>) -> Self {
+        let mut mode = Self { amplitudes };
+        mode.normalize();
+        mode
+    }
+
+    pub fn cutoff(&self) -> usize {
+        self.amplitudes.len()
+    }
+
+    pub fn amplitudes(&self) -> &[Complex64] {
+        &self.amplitudes
+    }
+
+    /// ⟨self|other⟩
+    pub fn inner(&self, other: &Mode) -> Complex64 {
+        self.amplitudes.iter().zip(&other.amplitudes).map(|(a, b)| a.conj() * b).sum()
+    }
+
+    pub fn norm_sqr(&self) -> f64 {
+        self.amplitudes.iter().map(|a| a.norm_sqr()).sum()
+    }
+
+    pub fn normalize(&mut self) {
+        let norm = self.norm_sqr().sqrt();
+        assert!(norm > 0.0, "cannot normalise the zero vector");
+        for amplitude in self.amplitudes.iter_mut() {
+            *amplitude /= norm;
+        }
+    }
+
+    /// Probability of each photon number
+    pub fn photon_number_distribution(&self) -> Vec<f64> {
+        self.amplitudes.iter().map(|a| a.norm_sqr()).collect()
+    }
+
+    pub fn mean_photon_number(&self) -> f64 {
+        self.photon_number_distribution().iter().enumerate().map(|(n, p)| n as f64 * p).sum()
+    }
+
+    /// ⟨(−1)^n⟩
+    pub fn parity(&self) -> f64 {
+        self.photon_number_distribution()
+            .iter()
+            .enumerate()
+            .map(|(n, p)| if n % 2 == 0 { *p } else { -p })
+            .sum()
+    }
+
+    /// Multiplies |n⟩ by `factor(n)`, leaving the state unnormalised
+    pub fn scale_by_photon_number(&mut self, factor: impl Fn(usize) -> f64) {
+        for (n, amplitude) in self.amplitudes.iter_mut().enumerate() {
+            *amplitude *= factor(n);
+        }
+    }
+
+    /// The annihilation operator a, leaving the state unnormalised
+    pub fn annihilate(&mut self) {
+        self.amplitudes.remove(0);
+        for (n, amplitude) in self.amplitudes.iter_mut().enumerate() {
+            *amplitude *= ((n + 1) as f64).sqrt();
+        }
+        self.amplitudes.push(Complex64::new(0.0, 0.0));
+    }
+
+    /// Phase-space rotation e^{−iθn}, which turns q into q cos θ + p sin θ
+    pub fn rotate(&mut self, theta: f64) {
+        for (n, amplitude) in self.amplitudes.iter_mut().enumerate() {
+            *amplitude *= Complex64::from_polar(1.0, -theta * n as f64);
+        }
+    }
+
+    /// The displacement D(α) = exp(αa† − α*a).
+    ///
+    /// The exponential is applied to the state as a Taylor series, in steps short enough for
+    /// the generator to have norm at most one in each.
+    pub fn displace(&mut self, alpha: Complex64) {
+        let generator_norm = 2.0 * alpha.norm() * (self.cutoff() as f64).sqrt();
+        let steps = generator_norm.ceil().max(1.0) as usize;
+        let step = alpha / steps as f64;
+        for _ in 0..steps {
+            let mut term = self.amplitudes.clone();
+            for order in 1..=40 {
+                term = displacement_generator(step, &term);
+                let mut largest = 0.0_f64;
+                for (amplitude, t) in self.amplitudes.iter_mut().zip(term.iter_mut()) {
+                    *t /= order as f64;
+                    *amplitude += *t;
+                    largest = largest.max(t.norm_sqr());
+                }
+                if largest < 1e-32 {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// (αa† − α*a)v
+fn displacement_generator(alpha: Complex64, v: &[Complex64]) -> Vec<Complex64> {
+    (0..v.len())
+        .map(|n| {
+            let raised = if n > 0 { alpha * (n as f64).sqrt() * v[n - 1] } else { Complex64::new(0.0, 0.0) };
+            let lowered = v.get(n + 1).map_or(Complex64::new(0.0, 0.0), |&next| {
+                alpha.conj() * ((n + 1) as f64).sqrt() * next
+            });
+            raised - lowered
+        })
+        .collect()
+}
+
+/// A noise channel on a mode, sampled one trajectory at a time
+pub trait ModeChannel {
+    fn apply(&self, mode: &mut Mode);
+}
+
+/// Pure loss: each photon leaks out independently with probability `loss_probability`.
+///
+/// Losing k photons has Kraus operator √(γ^k/k!) (1 − γ)^{n/2} a^k, so a run also shrinks
+/// the amplitudes of the larger photon numbers when nothing is lost.
+pub struct PhotonLoss {
+    loss_probability: f64,
+}
+
+impl PhotonLoss {
+    pub fn new(loss_probability: f64) -> Self {
+        assert!((0.0..=1.0).contains(&loss_probability), "the loss probability must lie in [0, 1]");
+        Self { loss_probability }
+    }
+
+    /// The loss after time `t` at energy decay rate `kappa`, 1 − e^{−κt}
+    pub fn from_decay(kappa: f64, t: f64) -> Self {
+        Self::new(1.0 - (-kappa * t).exp())
+    }
+
+    pub fn loss_probability(&self) -> f64 {
+        self.loss_probability
+    }
+}
+
+impl ModeChannel for PhotonLoss {
+    fn apply(&self, mode: &mut Mode) {
+        let gamma = self.loss_probability;
+        let populations = mode.photon_number_distribution();
+        let mut loss_weights = vec![0.0; mode.cutoff()];
+        for (n, population) in populations.iter().enumerate() {
+            for (k, weight) in loss_weights.iter_mut().enumerate().take(n + 1) {
+                *weight += population * binomial_probability(n, k, gamma);
+            }
+        }
+        let lost = sample_index(&loss_weights);
+
+        let mut amplitudes = vec![Complex64::new(0.0, 0.0); mode.cutoff()];
+        for (n, &amplitude) in mode.amplitudes.iter().enumerate().skip(lost) {
+            amplitudes[n - lost] = amplitude * binomial_probability(n, lost, gamma).sqrt();
+        }
+        *mode = Mode::from_amplitudes(amplitudes);
+    }
+}
+
+/// Random displacements with independent Gaussian shifts of standard deviation `sigma` in q
+/// and in p, the usual noise model for GKP codes
+pub struct GaussianDisplacement {
+    sigma: f64,
+}
+
+impl GaussianDisplacement {
+    pub fn new(sigma: f64) -> Self {
+        assert!(sigma >= 0.0, "the standard deviation must be non-negative");
+        Self { sigma }
+    }
+
+    pub fn sigma(&self) -> f64 {
+        self.sigma
+    }
+}
+
+impl ModeChannel for GaussianDisplacement {
+    fn apply(&self, mode: &mut Mode) {
+        let q_shift = self.sigma * standard_normal();
+        let p_shift = self.sigma * standard_normal();
+        mode.displace(Complex64::new(q_shift, p_shift) * FRAC_1_SQRT_2);
+    }
+}
+
+/// A qubit encoded in an oscillator mode.
+///
+/// One round of error correction measures the syndrome and corrects from it, like
+/// `CorrectionCode` does for qubit codes. `logical_bloch_vector` reads the logical qubit
+/// back out, and `effective_noise` condenses a channel followed by a round into the Pauli
+/// noise it leaves on that qubit.
+pub trait BosonicCode {
+    type Syndrome;
+
+    fn encode(&self, data: &Qubit) -> Mode;
+    fn syndrome_measurement(&self, mode: &mut Mode) -> Self::Syndrome;
+    fn correct(&self, mode: &mut Mode, syndrome: Self::Syndrome);
+
+    /// (⟨X̄⟩, ⟨Ȳ⟩, ⟨Z̄⟩) of the logical qubit carried by `mode`
+    fn logical_bloch_vector(&self, mode: &Mode) -> [f64; 3];
+
+    /// The Pauli channel with the same logical fidelities as `channel` followed by one round
+    /// of error correction, estimated from `num_shots` runs on each of |+⟩, |+i⟩ and |0⟩.
+    ///
+    /// The +1 eigenstate of P survives with probability p_I + p_P, so the three fidelities
+    /// fix the Pauli-twirled channel. The result is a qubit-level `ErrorModel`, ready to be
+    /// fed to any of the qubit codes.
+    fn effective_noise(&self, channel: &dyn ModeChannel, num_shots: usize) -> PauliChannel {
+        let h = Complex64::new(FRAC_1_SQRT_2, 0.0);
+        let eigenstates = [
+            [h, h],
+            [h, Complex64::new(0.0, FRAC_1_SQRT_2)],
+            [Complex64::new(1.0, 0.0), Complex64::new(0.0, 0.0)],
+        ];
+        let mut fidelities = [0.0; 3];
+        for (axis, state) in eigenstates.iter().enumerate() {
+            let mut data = Qubit::new();
+            data.set_state(0, state[0]);
+            data.set_state(1, state[1]);
+            let encoded = self.encode(&data);
+            for _ in 0..num_shots {
+                let mut mode = encoded.clone();
+                channel.apply(&mut mode);
+                let syndrome = self.syndrome_measurement(&mut mode);
+                self.correct(&mut mode, syndrome);
+                fidelities[axis] += (1.0 + self.logical_bloch_vector(&mode)[axis]) / 2.0;
+            }
+            fidelities[axis] /= num_shots as f64;
+        }
+
+        let [f_x, f_y, f_z] = fidelities;
+        let p_x = (1.0 + f_x - f_y - f_z) / 2.0;
+        let p_y = (1.0 - f_x + f_y - f_z) / 2.0;
+        let p_z = (1.0 - f_x - f_y + f_z) / 2.0;
+        PauliChannel::new(p_x.max(0.0), p_y.max(0.0), p_z.max(0.0))
+    }
+}
+
+/// a|0̄⟩ + b|1̄⟩ for the data qubit a|0⟩ + b|1⟩
+fn encode_with(codewords: &[Mode; 2], data: &Qubit) -> Mode {
+    let [a, b] = data.get_state();
+    let amplitudes = codewords[0]
+        .amplitudes
+        .iter()
+        .zip(&codewords[1].amplitudes)
+        .map(|(&zero, &one)| a * zero + b * one)
+        .collect();
+    Mode::from_amplitudes(amplitudes)
+}
+
+/// Bloch vector of a c₀|0̄⟩ + c₁|1̄⟩, where c is the overlap of a state with orthonormal
+/// codewords; weight outside the code space counts as maximally mixed
+fn bloch_vector(c: [Complex64; 2]) -> [f64; 3] {
+    let coherence = c[0].conj() * c[1];
+    [2.0 * coherence.re, 2.0 * coherence.im, c[0].norm_sqr() - c[1].norm_sqr()]
+}
+
+/// Probability of k successes in n trials of probability p
+fn binomial_probability(n: usize, k: usize, p: f64) -> f64 {
+    let mut probability = p.powi(k as i32) * (1.0 - p).powi((n - k) as i32);
+    for i in 0..k {
+        probability *= (n - i) as f64 / (i + 1) as f64;
+    }
+    probability
+}
+
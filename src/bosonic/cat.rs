@@ -0,0 +1,130 @@
+use crate::bosonic::{bloch_vector, encode_with, BosonicCode, Mode};
+use crate::qubit::Qubit;
+use num_complex::Complex64;
+
+/// The four-component cat code, which corrects the loss of a single photon.
+///
+/// |0̄⟩ and |1̄⟩ are the superpositions of |α⟩, |iα⟩, |−α⟩ and |−iα⟩ whose photon numbers
+/// are 0 and 2 mod 4. Losing a photon moves them to 3 and 1 mod 4, so the photon-number
+/// parity is the syndrome and the logical information survives the jump.
+///
+/// Correction maps the states a codeword can be left in back onto that codeword: the
+/// codewords themselves for even parity and their images under a for odd parity. A state
+/// outside those is beyond the code's reach and is reset to a random codeword. Two losses
+/// bring 0 mod 4 to 2 mod 4 with even parity, which is a logical bit flip.
+pub struct CatCode {
+    alpha: f64,
+    codewords: [Mode; 2],
+    /// The codewords after one photon loss, normalised
+    lost_codewords: [Mode; 2],
+}
+
+impl CatCode {
+    /// Cat states of amplitude `alpha` in a space of `cutoff` Fock states, which should
+    /// comfortably exceed the mean photon number α²
+    pub fn new(alpha: f64, cutoff: usize) -> Self {
+        assert!(alpha > 0.0, "the cat amplitude must be positive");
+        let codeword = |residue: usize| {
+            let coherent = Mode::coherent(cutoff, Complex64::new(alpha, 0.0));
+            let amplitudes = coherent
+                .amplitudes()
+                .iter()
+                .enumerate()
+                .map(|(n, &a)| if n % 4 == residue { a } else { Complex64::new(0.0, 0.0) })
+                .collect();
+            Mode::from_amplitudes(amplitudes)
+        };
+        let codewords = [codeword(0), codeword(2)];
+        let lost_codewords = codewords.clone().map(|mut lost| {
+            lost.annihilate();
+            lost.normalize();
+            lost
+        });
+        Self {
+            alpha,
+            codewords,
+            lost_codewords,
+        }
+    }
+
+    pub fn alpha(&self) -> f64 {
+        self.alpha
+    }
+
+    pub fn cutoff(&self) -> usize {
+        self.codewords[0].cutoff()
+    }
+
+    /// |0̄⟩ and |1̄⟩
+    pub fn codewords(&self) -> &[Mode; 2] {
+        &self.codewords
+    }
+}
+
+impl BosonicCode for CatCode {
+    /// Whether the photon-number parity is odd
+    type Syndrome = bool;
+
+    fn encode(&self, data: &Qubit) -> Mode {
+        encode_with(&self.codewords, data)
+    }
+
+    /// Measures the photon-number parity, projecting the mode onto the outcome
+    fn syndrome_measurement(&self, mode: &mut Mode) -> bool {
+        let distribution = mode.photon_number_distribution();
+        let odd_probability: f64 = distribution.iter().skip(1).step_by(2).sum();
+        let odd = rand::random::<f64>() < odd_probability;
+        mode.scale_by_photon_number(|n| if (n % 2 == 1) == odd { 1.0 } else { 0.0 });
+        mode.normalize();
+        odd
+    }
+
+    fn correct(&self, mode: &mut Mode, odd: bool) {
+        let error_words = if odd { &self.lost_codewords } else { &self.codewords };
+        let overlaps = error_words.each_ref().map(|word| word.inner(mode));
+        let kept: f64 = overlaps.iter().map(|c| c.norm_sqr()).sum();
+        *mode = if rand::random::<f64>() < kept {
+            let [a, b] = overlaps;
+            let mut data = Qubit::new();
+            data.set_state(0, a);
+            data.set_state(1, b);
+            encode_with(&self.codewords, &data)
+        } else {
+            self.codewords[usize::from(rand::random::<bool>())].clone()
+        };
+    }
+
+    fn logical_bloch_vector(&self, mode: &Mode) -> [f64; 3] {
+        bloch_vector(self.codewords.each_ref().map(|word| word.inner(mode)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bosonic::PhotonLoss;
+    use std::f64::consts::FRAC_1_SQRT_2;
+
+    #[test]
+    fn no_loss_leaves_no_logical_noise() {
+        let code = CatCode::new(2.0, 40);
+        let noise = code.effective_noise(&PhotonLoss::new(0.0), 20);
+        assert!(noise.probability() < 1e-9, "{:?}", noise.pauli_probabilities());
+    }
+
+    #[test]
+    fn encoded_eigenstates_lie_on_their_axes() {
+        let code = CatCode::new(2.0, 40);
+        let zero = Qubit::new();
+        let mut plus_i = Qubit::new();
+        plus_i.set_state(0, Complex64::new(FRAC_1_SQRT_2, 0.0));
+        plus_i.set_state(1, Complex64::new(0.0, FRAC_1_SQRT_2));
+
+        for (data, expected) in [(zero, [0.0, 0.0, 1.0]), (plus_i, [0.0, 1.0, 0.0])] {
+            let bloch = code.logical_bloch_vector(&code.encode(&data));
+            for (component, target) in bloch.iter().zip(expected) {
+                assert!((component - target).abs() < 1e-9, "{:?} should be {:?}", bloch, expected);
+            }
+        }
+    }
+}
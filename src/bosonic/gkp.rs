@@ -0,0 +1,239 @@
+use crate::bosonic::{encode_with, BosonicCode, Mode};
+use crate::sampling::{sample_index, standard_normal};
+use crate::qubit::Qubit;
+use num_complex::Complex64;
+use std::f64::consts::{FRAC_1_SQRT_2, FRAC_PI_2, FRAC_PI_4, PI};
+
+/// The square GKP code with finite energy.
+///
+/// The ideal codeword |j̄⟩ is a comb of position eigenstates at q = √π(2s + j), and the
+/// finite-energy codeword is e^{−Δ²n}|j̄⟩: peaks of width about Δ under a Gaussian envelope.
+/// Shifts smaller than √π/2 in either quadrature are corrected; larger ones round to the
+/// wrong peak and cause a logical error, X̄ for shifts in q and Z̄ for shifts in p.
+///
+/// A syndrome round is Steane-style. It measures q mod √π and then p mod √π through an
+/// ancilla whose own peaks have width Δ, so a measurement narrows the mode's peaks around
+/// the outcome instead of projecting them onto the ideal comb. Correction displaces the
+/// outcomes back to the lattice. The logical qubit is read out the usual way, by rounding
+/// q, p or (q − p)/√2 to the nearest peak.
+pub struct GkpCode {
+    delta: f64,
+    codewords: [Mode; 2],
+    grid: QuadratureGrid,
+}
+
+/// The shifts found in q and in p, both within (−√π/2, √π/2)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GkpSyndrome {
+    pub q_shift: f64,
+    pub p_shift: f64,
+}
+
+impl GkpCode {
+    /// Codewords with peak width `delta` in a space of `cutoff` Fock states. The envelope
+    /// e^{−Δ²n} should be small at the cutoff, which takes a cutoff of several times 1/Δ².
+    pub fn new(delta: f64, cutoff: usize) -> Self {
+        assert!(delta > 0.0, "the peak width must be positive");
+        let grid = QuadratureGrid::new(cutoff);
+        let codeword = |j: usize| {
+            let mut amplitudes = vec![Complex64::new(0.0, 0.0); cutoff];
+            let spacing = 2.0 * PI.sqrt();
+            let mut peak = j as f64 * PI.sqrt() - (grid.extent / spacing).ceil() * spacing;
+            while peak <= grid.extent {
+                for (amplitude, value) in amplitudes.iter_mut().zip(hermite_functions(cutoff, peak)) {
+                    *amplitude += value;
+                }
+                peak += spacing;
+            }
+            let mut mode = Mode::from_amplitudes(amplitudes);
+            mode.scale_by_photon_number(|n| (-delta * delta * n as f64).exp());
+            mode.normalize();
+            mode
+        };
+        let codewords = [codeword(0), codeword(1)];
+        Self { delta, codewords, grid }
+    }
+
+    pub fn delta(&self) -> f64 {
+        self.delta
+    }
+
+    pub fn cutoff(&self) -> usize {
+        self.codewords[0].cutoff()
+    }
+
+    /// |0̄⟩ and |1̄⟩, which are only approximately orthogonal
+    pub fn codewords(&self) -> &[Mode; 2] {
+        &self.codewords
+    }
+
+    /// Measures the quadrature q cos θ + p sin θ modulo √π, returning the outcome
+    fn measure_shift(&self, mode: &mut Mode, theta: f64) -> f64 {
+        let period = PI.sqrt();
+        let filter = |outcome: f64, x: f64| {
+            let offset = x - outcome;
+            let nearest = (offset / period).round();
+            (-1..=1)
+                .map(|k| {
+                    let distance = offset - (nearest + k as f64) * period;
+                    (-distance * distance / (2.0 * self.delta * self.delta)).exp()
+                })
+                .sum::<f64>()
+        };
+
+        // |filter|² is a comb of Gaussians of variance Δ²/2, so the outcome is the position
+        // plus the ancilla's Gaussian noise, folded into one period
+        mode.rotate(theta);
+        let mut wavefunction = self.grid.wavefunction(mode);
+        let density: Vec<f64> = wavefunction.iter().map(|psi| psi.norm_sqr()).collect();
+        let position = self.grid.points[sample_index(&density)];
+        let noisy = position + self.delta * FRAC_1_SQRT_2 * standard_normal();
+        let outcome = noisy - (noisy / period).round() * period;
+
+        for (&x, psi) in self.grid.points.iter().zip(wavefunction.iter_mut()) {
+            *psi *= filter(outcome, x);
+        }
+        *mode = self.grid.mode(&wavefunction);
+        mode.rotate(-theta);
+        outcome
+    }
+}
+
+impl BosonicCode for GkpCode {
+    type Syndrome = GkpSyndrome;
+
+    fn encode(&self, data: &Qubit) -> Mode {
+        encode_with(&self.codewords, data)
+    }
+
+    fn syndrome_measurement(&self, mode: &mut Mode) -> GkpSyndrome {
+        let q_shift = self.measure_shift(mode, 0.0);
+        let p_shift = self.measure_shift(mode, FRAC_PI_2);
+        GkpSyndrome { q_shift, p_shift }
+    }
+
+    fn correct(&self, mode: &mut Mode, syndrome: GkpSyndrome) {
+        mode.displace(-Complex64::new(syndrome.q_shift, syndrome.p_shift) * FRAC_1_SQRT_2);
+    }
+
+    /// Each logical Pauli is the sign of its quadrature rounded to the nearest peak: Z̄ from
+    /// q with spacing √π, X̄ from p and Ȳ = e^{i√π(q − p)} from (q − p)/√2 with spacing √(π/2)
+    fn logical_bloch_vector(&self, mode: &Mode) -> [f64; 3] {
+        let binned = |theta: f64, period: f64| {
+            let mut rotated = mode.clone();
+            rotated.rotate(theta);
+            let wavefunction = self.grid.wavefunction(&rotated);
+            let points = self.grid.points.iter().zip(&wavefunction);
+            let sum: f64 = points.map(|(&x, psi)| (PI * x / period).cos().signum() * psi.norm_sqr()).sum();
+            sum * self.grid.spacing
+        };
+        [
+            binned(FRAC_PI_2, PI.sqrt()),
+            binned(-FRAC_PI_4, FRAC_PI_2.sqrt()),
+            binned(0.0, PI.sqrt()),
+        ]
+    }
+}
+
+/// Points along a quadrature, with the Fock-state wavefunctions on them, for moving between
+/// the Fock basis and the quadrature basis
+struct QuadratureGrid {
+    /// The points cover [−extent, extent]
+    extent: f64,
+    spacing: f64,
+    points: Vec<f64>,
+    /// ψ_n(x) for every point x, point by point
+    hermite: Vec<Vec<f64>>,
+}
+
+impl QuadratureGrid {
+    fn new(cutoff: usize) -> Self {
+        // ψ_n dies off quickly beyond its turning point √(2n + 1)
+        let extent = (2.0 * cutoff as f64 + 1.0).sqrt() + 6.0;
+        let spacing = 0.02;
+        let num_points = (2.0 * extent / spacing) as usize + 1;
+        let points: Vec<f64> = (0..num_points).map(|i| -extent + i as f64 * spacing).collect();
+        let hermite = points.iter().map(|&x| hermite_functions(cutoff, x)).collect();
+        Self {
+            extent,
+            spacing,
+            points,
+            hermite,
+        }
+    }
+
+    /// ⟨x|ψ⟩ at every point
+    fn wavefunction(&self, mode: &Mode) -> Vec<Complex64> {
+        self.hermite
+            .iter()
+            .map(|values| values.iter().zip(mode.amplitudes()).map(|(&h, &a)| a * h).sum())
+            .collect()
+    }
+
+    /// The normalised mode with the given wavefunction, projected onto the truncated space
+    fn mode(&self, wavefunction: &[Complex64]) -> Mode {
+        let mut amplitudes = vec![Complex64::new(0.0, 0.0); self.hermite[0].len()];
+        for (values, &psi) in self.hermite.iter().zip(wavefunction) {
+            for (amplitude, &h) in amplitudes.iter_mut().zip(values) {
+                *amplitude += psi * h;
+            }
+        }
+        Mode::from_amplitudes(amplitudes)
+    }
+}
+
+/// ψ_0(x), …, ψ_{cutoff−1}(x), the position wavefunctions of the Fock states, by the
+/// recurrence ψ_{n+1} = √(2/(n+1)) x ψ_n − √(n/(n+1)) ψ_{n−1}
+fn hermite_functions(cutoff: usize, x: f64) -> Vec<f64> {
+    let mut values = Vec::with_capacity(cutoff);
+    values.push(PI.powf(-0.25) * (-x * x / 2.0).exp());
+    if cutoff > 1 {
+        values.push(2.0_f64.sqrt() * x * values[0]);
+    }
+    for n in 1..cutoff.saturating_sub(1) {
+        let next = (2.0 / (n + 1) as f64).sqrt() * x * values[n]
+            - (n as f64 / (n + 1) as f64).sqrt() * values[n - 1];
+        values.push(next);
+    }
+    values.truncate(cutoff);
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bosonic::GaussianDisplacement;
+
+    #[test]
+    fn noiseless_rounds_only_fail_through_the_finite_peak_width() {
+        let delta = 0.3;
+        let code = GkpCode::new(delta, 60);
+        let noise = code.effective_noise(&GaussianDisplacement::new(0.0), 100);
+        let (p_x, p_y, p_z) = noise.pauli_probabilities();
+
+        // The mode's peaks, the ancilla's and the back-action of measuring the other
+        // quadrature each add a variance of Δ²/2, so a round rounds to the wrong peak no more
+        // often than a Gaussian of width √2·Δ reaches past √π/2, which at z standard
+        // deviations is below √(2/π) e^{−z²/2}/z
+        let z = PI.sqrt() / 2.0 / (2.0_f64.sqrt() * delta);
+        let tail = (2.0 / PI).sqrt() * (-z * z / 2.0).exp() / z;
+        assert!(p_x + p_y < tail && p_z + p_y < tail, "{:?} against {}", (p_x, p_y, p_z), tail);
+    }
+
+    #[test]
+    fn encoded_eigenstates_lie_on_their_axes() {
+        let code = GkpCode::new(0.3, 60);
+        let zero = Qubit::new();
+        let mut plus_i = Qubit::new();
+        plus_i.set_state(0, Complex64::new(FRAC_1_SQRT_2, 0.0));
+        plus_i.set_state(1, Complex64::new(0.0, FRAC_1_SQRT_2));
+
+        // The codewords overlap slightly, so the vectors fall a little short of the poles
+        for (data, expected) in [(zero, [0.0, 0.0, 1.0]), (plus_i, [0.0, 1.0, 0.0])] {
+            let bloch = code.logical_bloch_vector(&code.encode(&data));
+            for (component, target) in bloch.iter().zip(expected) {
+                assert!((component - target).abs() < 1e-2, "{:?} should be {:?}", bloch, expected);
+            }
+        }
+    }
+}
@@ -0,0 +1,385 @@
+use crate::error_models::PauliChannel;
+use crate::gates::CliffordGate;
+use crate::pauli::{Pauli, PauliString};
+use std::collections::HashMap;
+
+mod detector_error_model;
+
+pub use detector_error_model::{DetectorErrorModel, DetectorErrorModelError, ErrorMechanism};
+
+/// One step of a noisy stabilizer circuit
+#[derive(Clone, Debug, PartialEq)]
+pub enum Operation {
+    Gate(CliffordGate),
+    /// Resets a qubit to |0⟩, discarding any error on it
+    Reset(usize),
+    /// Measures a qubit in the Z basis, appending a result to the measurement record that is
+    /// flipped with `flip_probability`
+    Measure { qubit: usize, flip_probability: f64 },
+    /// Measures a Pauli product directly, appending a result to the measurement record
+    MeasurePauli { pauli: PauliString, flip_probability: f64 },
+    /// A single-qubit Pauli channel
+    PauliNoise { qubit: usize, channel: PauliChannel },
+    /// Two-qubit depolarizing noise: each of the 15 non-identity two-qubit Paulis with
+    /// probability `probability` / 15
+    Depolarize2 { qubits: (usize, usize), probability: f64 },
+    /// A parity of measurement results, by index into the record, that is deterministic
+    /// without noise
+    Detector(Vec<usize>),
+    /// Adds the parity of these measurement results to a logical observable
+    Observable { index: usize, measurements: Vec<usize> },
+}
+
+/// Noise strengths of a syndrome-extraction circuit
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CircuitNoise {
+    /// Depolarizing noise on every data qubit at the start of each round
+    pub idle: f64,
+    /// Two-qubit depolarizing noise after every two-qubit gate
+    pub gate: f64,
+    /// Probability that a reset prepares |1⟩ instead of |0⟩
+    pub reset: f64,
+    /// Probability that a measurement result is flipped
+    pub measurement: f64,
+}
+
+impl CircuitNoise {
+    /// The same probability p for every kind of noise
+    pub fn uniform(probability: f64) -> Self {
+        Self {
+            idle: probability,
+            gate: probability,
+            reset: probability,
+            measurement: probability,
+        }
+    }
+}
+
+/// A stabilizer circuit with Pauli noise, measurements, and the detectors and logical
+/// observables defined on its measurement record.
+///
+/// As with `CliffordGate`, circuits are not run on `Qubit` states. Only the propagation of
+/// Pauli errors matters, which is what `detector_error_model` follows to find the detectors
+/// and observables each error flips.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NoisyCircuit {
+    num_qubits: usize,
+    operations: Vec<Operation>,
+    num_measurements: usize,
+    num_detectors: usize,
+    num_observables: usize,
+}
+
+/// A single fault of one of the circuit's noise channels, taking effect right after its
+/// operation
+struct Fault {
+    operation: usize,
+    probability: f64,
+    effect: FaultEffect,
+}
+
+enum FaultEffect {
+    Pauli(Vec<(usize, Pauli)>),
+    MeasurementFlip(usize),
+}
+
+impl NoisyCircuit {
+    pub fn new(num_qubits: usize) -> Self {
+        Self {
+            num_qubits,
+            operations: Vec::new(),
+            num_measurements: 0,
+            num_detectors: 0,
+            num_observables: 0,
+        }
+    }
+
+    /// A memory experiment on the code with these stabilizer generators, followed by a
+    /// perfect readout of the stabilizers and of `logical`.
+    ///
+    /// The data qubits are assumed to start in a code state. Each of the `rounds` rounds
+    /// measures every stabilizer with its own ancilla, placed after the n data qubits: the
+    /// ancilla is reset, put in |+⟩, controls the stabilizer's Pauli on each qubit of its
+    /// support, and is measured in the X basis. Round 0 has one detector per stabilizer on
+    /// its result alone, and later rounds compare each result with the round before, as does
+    /// the final readout. The logical readout is observable 0.
+    pub fn memory_experiment(
+        stabilizers: &[PauliString],
+        logical: &PauliString,
+        rounds: usize,
+        noise: &CircuitNoise,
+    ) -> Self {
+        let n = stabilizers[0].len();
+        let mut circuit = Self::new(n + stabilizers.len());
+        let idle = PauliChannel::new(noise.idle / 3.0, noise.idle / 3.0, noise.idle / 3.0);
+        let reset = PauliChannel::new(noise.reset, 0.0, 0.0);
+        let mut previous: Option<Vec<usize>> = None;
+
+        for _ in 0..rounds {
+            if noise.idle > 0.0 {
+                for qubit in 0..n {
+                    circuit.push(Operation::PauliNoise { qubit, channel: idle });
+                }
+            }
+            let mut results = Vec::new();
+            for (j, stabilizer) in stabilizers.iter().enumerate() {
+                let ancilla = n + j;
+                circuit.push(Operation::Reset(ancilla));
+                if noise.reset > 0.0 {
+                    circuit.push(Operation::PauliNoise { qubit: ancilla, channel: reset });
+                }
+                circuit.push(Operation::Gate(CliffordGate::H(ancilla)));
+                for qubit in stabilizer.support() {
+                    circuit.push_controlled_pauli(ancilla, qubit, stabilizer.get(qubit));
+                    if noise.gate > 0.0 {
+                        let qubits = (ancilla, qubit);
+                        circuit.push(Operation::Depolarize2 { qubits, probability: noise.gate });
+                    }
+                }
+                circuit.push(Operation::Gate(CliffordGate::H(ancilla)));
+                results.push(circuit.measure(ancilla, noise.measurement));
+            }
+            circuit.push_round_detectors(&results, previous.as_deref());
+            previous = Some(results);
+        }
+
+        // The final readout acts on the data qubits only
+        let on_data = |pauli: &PauliString| {
+            let padding = vec![false; stabilizers.len()];
+            PauliString::from_bits([pauli.x_bits(), &padding].concat(), [pauli.z_bits(), &padding].concat())
+        };
+        let results: Vec<usize> =
+            stabilizers.iter().map(|stabilizer| circuit.measure_pauli(on_data(stabilizer), 0.0)).collect();
+        circuit.push_round_detectors(&results, previous.as_deref());
+        let readout = circuit.measure_pauli(on_data(logical), 0.0);
+        circuit.push(Operation::Observable { index: 0, measurements: vec![readout] });
+        circuit
+    }
+
+    /// The controlled Pauli from `control` onto `target`: CNOT, CZ, or CNOT conjugated by S
+    fn push_controlled_pauli(&mut self, control: usize, target: usize, pauli: Pauli) {
+        match pauli {
+            Pauli::I => {}
+            Pauli::X => self.push(Operation::Gate(CliffordGate::Cnot { control, target })),
+            Pauli::Z => self.push(Operation::Gate(CliffordGate::Cz(control, target))),
+            Pauli::Y => {
+                self.push(Operation::Gate(CliffordGate::SDag(target)));
+                self.push(Operation::Gate(CliffordGate::Cnot { control, target }));
+                self.push(Operation::Gate(CliffordGate::S(target)));
+            }
+        }
+    }
+
+    fn push_round_detectors(&mut self, results: &[usize], previous: Option<&[usize]>) {
+        for (j, &result) in results.iter().enumerate() {
+            let mut measurements = vec![result];
+            measurements.extend(previous.map(|previous| previous[j]));
+            self.push(Operation::Detector(measurements));
+        }
+    }
+
+    /// Appends an operation, checking the qubits and measurements it refers to
+    pub fn push(&mut self, operation: Operation) {
+        let check_qubit = |qubit: usize| {
+            assert!(qubit < self.num_qubits, "qubit {} is outside the circuit", qubit);
+        };
+        let check_measurements = |measurements: &[usize]| {
+            for &measurement in measurements {
+                assert!(
+                    measurement < self.num_measurements,
+                    "measurement {} has not happened yet",
+                    measurement
+                );
+            }
+        };
+        match &operation {
+            Operation::Gate(CliffordGate::H(q) | CliffordGate::S(q) | CliffordGate::SDag(q))
+            | Operation::Reset(q)
+            | Operation::PauliNoise { qubit: q, .. } => check_qubit(*q),
+            Operation::Gate(CliffordGate::Cnot { control: a, target: b } | CliffordGate::Cz(a, b))
+            | Operation::Depolarize2 { qubits: (a, b), .. } => {
+                check_qubit(*a);
+                check_qubit(*b);
+                assert_ne!(a, b, "a two-qubit operation needs two different qubits");
+            }
+            Operation::Measure { qubit, .. } => {
+                check_qubit(*qubit);
+                self.num_measurements += 1;
+            }
+            Operation::MeasurePauli { pauli, .. } => {
+                assert_eq!(pauli.len(), self.num_qubits, "the Pauli product has the wrong length");
+                self.num_measurements += 1;
+            }
+            Operation::Detector(measurements) => {
+                check_measurements(measurements);
+                self.num_detectors += 1;
+            }
+            Operation::Observable { index, measurements } => {
+                check_measurements(measurements);
+                self.num_observables = self.num_observables.max(index + 1);
+            }
+        }
+        self.operations.push(operation);
+    }
+
+    /// Measures a qubit in the Z basis and returns the index of its result
+    pub fn measure(&mut self, qubit: usize, flip_probability: f64) -> usize {
+        self.push(Operation::Measure { qubit, flip_probability });
+        self.num_measurements - 1
+    }
+
+    /// Measures a Pauli product and returns the index of its result
+    pub fn measure_pauli(&mut self, pauli: PauliString, flip_probability: f64) -> usize {
+        self.push(Operation::MeasurePauli { pauli, flip_probability });
+        self.num_measurements - 1
+    }
+
+    pub fn num_qubits(&self) -> usize {
+        self.num_qubits
+    }
+
+    pub fn operations(&self) -> &[Operation] {
+        &self.operations
+    }
+
+    pub fn num_measurements(&self) -> usize {
+        self.num_measurements
+    }
+
+    pub fn num_detectors(&self) -> usize {
+        self.num_detectors
+    }
+
+    pub fn num_observables(&self) -> usize {
+        self.num_observables
+    }
+
+    /// Every fault the noise can cause, in circuit order. Each Pauli of a channel becomes
+    /// its own fault with that Pauli's probability, which treats the channel's exclusive
+    /// outcomes as independent, correct to first order in the noise strength.
+    fn faults(&self) -> Vec<Fault> {
+        let mut faults = Vec::new();
+        let mut measurement = 0;
+        for (operation, step) in self.operations.iter().enumerate() {
+            let mut add = |probability: f64, effect: FaultEffect| {
+                if probability > 0.0 {
+                    faults.push(Fault { operation, probability, effect });
+                }
+            };
+            match step {
+                Operation::Measure { flip_probability, .. }
+                | Operation::MeasurePauli { flip_probability, .. } => {
+                    add(*flip_probability, FaultEffect::MeasurementFlip(measurement));
+                    measurement += 1;
+                }
+                Operation::PauliNoise { qubit, channel } => {
+                    let (p_x, p_y, p_z) = channel.pauli_probabilities();
+                    for (pauli, probability) in [(Pauli::X, p_x), (Pauli::Y, p_y), (Pauli::Z, p_z)] {
+                        add(probability, FaultEffect::Pauli(vec![(*qubit, pauli)]));
+                    }
+                }
+                Operation::Depolarize2 { qubits: (a, b), probability } => {
+                    for pair in 1..16 {
+                        let first = Pauli::from_bits(pair & 1 == 1, pair & 2 == 2);
+                        let second = Pauli::from_bits(pair & 4 == 4, pair & 8 == 8);
+                        add(probability / 15.0, FaultEffect::Pauli(vec![(*a, first), (*b, second)]));
+                    }
+                }
+                _ => {}
+            }
+        }
+        faults
+    }
+
+    /// The detector error model of the circuit: every fault, with the detectors and
+    /// observables it flips. Faults are pushed through the rest of the circuit as Pauli
+    /// frames, 64 at a time with one bit per fault, and faults with the same effect are
+    /// merged into one mechanism of probability p₁(1 − p₂) + p₂(1 − p₁). Faults that flip
+    /// nothing are left out.
+    pub fn detector_error_model(&self) -> DetectorErrorModel {
+        let mut model = DetectorErrorModel::new(self.num_detectors, self.num_observables);
+        let mut index_of: HashMap<(Vec<usize>, Vec<usize>), usize> = HashMap::new();
+        for chunk in self.faults().chunks(64) {
+            let (detectors, observables) = self.propagate(chunk);
+            for (bit, fault) in chunk.iter().enumerate() {
+                let flipped = |words: &[u64]| -> Vec<usize> {
+                    (0..words.len()).filter(|&i| words[i] >> bit & 1 == 1).collect()
+                };
+                let symptom = (flipped(&detectors), flipped(&observables));
+                if symptom.0.is_empty() && symptom.1.is_empty() {
+                    continue;
+                }
+                match index_of.get(&symptom) {
+                    Some(&index) => model.merge_error(index, fault.probability),
+                    None => {
+                        index_of.insert(symptom.clone(), model.mechanisms().len());
+                        model.add_error(fault.probability, symptom.0, symptom.1);
+                    }
+                }
+            }
+        }
+        model
+    }
+
+    /// Runs up to 64 faults through the circuit at once, bit i of each frame word standing
+    /// for fault i, and returns which of them flip each detector and each observable
+    fn propagate(&self, faults: &[Fault]) -> (Vec<u64>, Vec<u64>) {
+        let mut x = vec![0u64; self.num_qubits];
+        let mut z = vec![0u64; self.num_qubits];
+        let mut record = Vec::with_capacity(self.num_measurements);
+        let mut detectors = Vec::with_capacity(self.num_detectors);
+        let mut observables = vec![0u64; self.num_observables];
+        let parity = |record: &[u64], measurements: &[usize]| {
+            measurements.iter().fold(0, |word, &measurement| word ^ record[measurement])
+        };
+        let mut next_fault = 0;
+
+        for (operation, step) in self.operations.iter().enumerate() {
+            match step {
+                Operation::Gate(CliffordGate::H(q)) => (x[*q], z[*q]) = (z[*q], x[*q]),
+                Operation::Gate(CliffordGate::S(q) | CliffordGate::SDag(q)) => z[*q] ^= x[*q],
+                Operation::Gate(CliffordGate::Cnot { control, target }) => {
+                    x[*target] ^= x[*control];
+                    z[*control] ^= z[*target];
+                }
+                Operation::Gate(CliffordGate::Cz(a, b)) => {
+                    z[*a] ^= x[*b];
+                    z[*b] ^= x[*a];
+                }
+                Operation::Reset(q) => (x[*q], z[*q]) = (0, 0),
+                Operation::Measure { qubit, .. } => record.push(x[*qubit]),
+                Operation::MeasurePauli { pauli, .. } => {
+                    // The result flips for the faults whose frame anticommutes with the product
+                    record.push(pauli.support().into_iter().fold(0, |word, q| {
+                        let p = pauli.get(q);
+                        word ^ (if p.z_bit() { x[q] } else { 0 }) ^ (if p.x_bit() { z[q] } else { 0 })
+                    }));
+                }
+                Operation::Detector(measurements) => detectors.push(parity(&record, measurements)),
+                Operation::Observable { index, measurements } => {
+                    observables[*index] ^= parity(&record, measurements);
+                }
+                Operation::PauliNoise { .. } | Operation::Depolarize2 { .. } => {}
+            }
+
+            while next_fault < faults.len() && faults[next_fault].operation == operation {
+                let bit = 1u64 << next_fault;
+                match &faults[next_fault].effect {
+                    FaultEffect::Pauli(paulis) => {
+                        for &(qubit, pauli) in paulis {
+                            if pauli.x_bit() {
+                                x[qubit] ^= bit;
+                            }
+                            if pauli.z_bit() {
+                                z[qubit] ^= bit;
+                            }
+                        }
+                    }
+                    FaultEffect::MeasurementFlip(measurement) => record[*measurement] ^= bit,
+                }
+                next_fault += 1;
+            }
+        }
+        (detectors, observables)
+    }
+}
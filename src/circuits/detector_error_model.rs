@@ -0,0 +1,433 @@
+use crate::matching::DecodingGraph;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// An independent fault that flips a set of detectors and logical observables
+#[derive(Clone, Debug, PartialEq)]
+pub struct ErrorMechanism {
+    pub probability: f64,
+    /// Flipped detectors, in increasing order
+    pub detectors: Vec<usize>,
+    /// Flipped observables, in increasing order
+    pub observables: Vec<usize>,
+}
+
+/// A detector error model (DEM): the independent error mechanisms of a noisy circuit, each
+/// described only by the detectors and logical observables it flips.
+///
+/// The text form is the common DEM syntax, one instruction per line, such as
+/// `error(0.001) D0 D1 L0` for a mechanism flipping detectors 0 and 1 and observable 0.
+/// `detector D5` and `logical_observable L1` lines declare detectors and observables no
+/// mechanism flips, and `#` starts a comment. Reading also accepts detector coordinates and
+/// the `^` separators of suggested decompositions, which are ignored; `repeat` blocks and
+/// `shift_detectors` are not supported.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DetectorErrorModel {
+    num_detectors: usize,
+    num_observables: usize,
+    mechanisms: Vec<ErrorMechanism>,
+}
+
+/// Why a detector error model could not be read or turned into a decoding graph
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DetectorErrorModelError {
+    /// A line of the text form is not a valid instruction
+    InvalidLine { line: usize, text: String },
+    /// A line uses an instruction that is not supported
+    UnsupportedInstruction { line: usize, instruction: String },
+    /// A mechanism flips more than two detectors, and is not a combination of mechanisms
+    /// that flip at most two
+    NotGraphlike { mechanism: usize },
+    /// Decoding graphs track observables in a 64-bit mask
+    TooManyObservables { found: usize },
+}
+
+impl fmt::Display for DetectorErrorModelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DetectorErrorModelError::InvalidLine { line, text } => {
+                write!(f, "line {} is not a valid instruction: \"{}\"", line, text)
+            }
+            DetectorErrorModelError::UnsupportedInstruction { line, instruction } => {
+                write!(f, "line {} uses the unsupported instruction \"{}\"", line, instruction)
+            }
+            DetectorErrorModelError::NotGraphlike { mechanism } => write!(
+                f,
+                "error mechanism {} flips more than two detectors and cannot be decomposed",
+                mechanism
+            ),
+            DetectorErrorModelError::TooManyObservables { found } => {
+                write!(f, "the model has {} observables, at most 64 are supported", found)
+            }
+        }
+    }
+}
+
+impl Error for DetectorErrorModelError {}
+
+impl DetectorErrorModel {
+    pub fn new(num_detectors: usize, num_observables: usize) -> Self {
+        Self {
+            num_detectors,
+            num_observables,
+            mechanisms: Vec::new(),
+        }
+    }
+
+    /// Adds a mechanism. Targets listed twice cancel, and the model grows to hold any
+    /// detector or observable beyond its current ones.
+    pub fn add_error(&mut self, probability: f64, detectors: Vec<usize>, observables: Vec<usize>) {
+        assert!((0.0..=1.0).contains(&probability), "probabilities must lie between 0 and 1");
+        let detectors = cancel_pairs(detectors);
+        let observables = cancel_pairs(observables);
+        if let Some(&last) = detectors.last() {
+            self.num_detectors = self.num_detectors.max(last + 1);
+        }
+        if let Some(&last) = observables.last() {
+            self.num_observables = self.num_observables.max(last + 1);
+        }
+        self.mechanisms.push(ErrorMechanism { probability, detectors, observables });
+    }
+
+    /// Folds an independent fault with the same effect into mechanism `index`: exactly one
+    /// of the two has to happen for the effect to show
+    pub(super) fn merge_error(&mut self, index: usize, probability: f64) {
+        let merged = &mut self.mechanisms[index].probability;
+        *merged = *merged * (1.0 - probability) + probability * (1.0 - *merged);
+    }
+
+    pub fn num_detectors(&self) -> usize {
+        self.num_detectors
+    }
+
+    pub fn num_observables(&self) -> usize {
+        self.num_observables
+    }
+
+    pub fn mechanisms(&self) -> &[ErrorMechanism] {
+        &self.mechanisms
+    }
+
+    /// Fires every mechanism independently, returning the detectors left flipped and the
+    /// observables flipped, bit i for observable i
+    pub fn sample(&self) -> (Vec<usize>, u64) {
+        assert!(self.num_observables <= 64, "samples track up to 64 observables");
+        let mut flipped = vec![false; self.num_detectors];
+        let mut observables = 0;
+        for mechanism in &self.mechanisms {
+            if rand::random::<f64>() < mechanism.probability {
+                for &detector in &mechanism.detectors {
+                    flipped[detector] ^= true;
+                }
+                observables ^= observable_mask(&mechanism.observables);
+            }
+        }
+        ((0..self.num_detectors).filter(|&detector| flipped[detector]).collect(), observables)
+    }
+
+    /// The matching graph of the model: a node per detector and a boundary node at index
+    /// `num_detectors`, with a fault edge for each mechanism flipping one or two detectors.
+    ///
+    /// Mechanisms flipping more detectors, such as Y errors that trip both the X and the Z
+    /// checks around a qubit, are split into parts that each match a mechanism flipping at
+    /// most two, and whose observables add up to theirs. Each part becomes a fault of the
+    /// mechanism's probability. Faults on the same edge merge as independent faults; when
+    /// they flip different observables, the likelier one is kept. Mechanisms flipping no
+    /// detector cannot be decoded and are left out.
+    pub fn decoding_graph(&self) -> Result<DecodingGraph, DetectorErrorModelError> {
+        self.decoding_graph_with_sources().map(|(graph, _)| graph)
+    }
+
+    /// `decoding_graph`, along with the mechanism each edge stands for: one that flips the
+    /// edge's detectors and observables
+    pub fn decoding_graph_with_sources(
+        &self,
+    ) -> Result<(DecodingGraph, Vec<usize>), DetectorErrorModelError> {
+        if self.num_observables > 64 {
+            return Err(DetectorErrorModelError::TooManyObservables { found: self.num_observables });
+        }
+        let boundary = self.num_detectors;
+        let edge_of = |detectors: &[usize]| match *detectors {
+            [a] => Some((a, boundary)),
+            [a, b] => Some((a, b)),
+            _ => None,
+        };
+
+        // Every effect some mechanism has on each edge, with the first mechanism to have it
+        let mut edges = EdgeFaults::default();
+        let mut known: HashMap<(usize, usize), Vec<EdgeEffect>> = HashMap::new();
+        for (index, mechanism) in self.mechanisms.iter().enumerate() {
+            if let Some(edge) = edge_of(&mechanism.detectors) {
+                let observables = observable_mask(&mechanism.observables);
+                edges.add(EdgeFault { edge, probability: mechanism.probability, observables, source: index });
+                let effects = known.entry(edge).or_default();
+                if effects.iter().all(|&(seen, _)| seen != observables) {
+                    effects.push((observables, index));
+                }
+            }
+        }
+        for (index, mechanism) in self.mechanisms.iter().enumerate() {
+            if mechanism.detectors.len() <= 2 {
+                continue;
+            }
+            let observables = observable_mask(&mechanism.observables);
+            let parts = decompose(&mechanism.detectors, observables, &known, boundary)
+                .ok_or(DetectorErrorModelError::NotGraphlike { mechanism: index })?;
+            for (edge, (observables, source)) in parts {
+                edges.add(EdgeFault { edge, probability: mechanism.probability, observables, source });
+            }
+        }
+
+        let mut graph = DecodingGraph::new(self.num_detectors + 1);
+        graph.set_boundary(boundary);
+        let mut sources = Vec::new();
+        for fault in edges.faults {
+            graph.add_fault(fault.edge.0, fault.edge.1, fault.probability, fault.observables);
+            sources.push(fault.source);
+        }
+        Ok((graph, sources))
+    }
+}
+
+/// Observables flipped by a mechanism on an edge, and the mechanism
+type EdgeEffect = (u64, usize);
+
+/// A fault of a decoding graph being built, and the mechanism it stands for
+struct EdgeFault {
+    edge: (usize, usize),
+    probability: f64,
+    observables: u64,
+    source: usize,
+}
+
+/// The faults of a decoding graph being built, one per edge
+#[derive(Default)]
+struct EdgeFaults {
+    faults: Vec<EdgeFault>,
+    index_of: HashMap<(usize, usize), usize>,
+}
+
+impl EdgeFaults {
+    fn add(&mut self, fault: EdgeFault) {
+        match self.index_of.get(&fault.edge) {
+            Some(&index) => {
+                let kept = &mut self.faults[index];
+                if kept.observables == fault.observables {
+                    let (p, q) = (kept.probability, fault.probability);
+                    kept.probability = p * (1.0 - q) + q * (1.0 - p);
+                } else if fault.probability > kept.probability {
+                    *kept = fault;
+                }
+            }
+            None => {
+                self.index_of.insert(fault.edge, self.faults.len());
+                self.faults.push(fault);
+            }
+        }
+    }
+}
+
+/// Splits `detectors` into singles and pairs that are all edges of `known`, each with one
+/// of its effects, so that the observables add up to `observables`
+fn decompose(
+    detectors: &[usize],
+    observables: u64,
+    known: &HashMap<(usize, usize), Vec<EdgeEffect>>,
+    boundary: usize,
+) -> Option<Vec<((usize, usize), EdgeEffect)>> {
+    let Some((&first, rest)) = detectors.split_first() else {
+        return (observables == 0).then(Vec::new);
+    };
+    let partners = std::iter::once(None).chain((0..rest.len()).map(Some));
+    for partner in partners {
+        let edge = partner.map_or((first, boundary), |i| (first, rest[i]));
+        let Some(effects) = known.get(&edge) else {
+            continue;
+        };
+        let remaining: Vec<usize> =
+            (0..rest.len()).filter(|&i| Some(i) != partner).map(|i| rest[i]).collect();
+        for &effect in effects {
+            if let Some(mut parts) = decompose(&remaining, observables ^ effect.0, known, boundary) {
+                parts.push((edge, effect));
+                return Some(parts);
+            }
+        }
+    }
+    None
+}
+
+fn observable_mask(observables: &[usize]) -> u64 {
+    observables.iter().fold(0, |mask, &observable| mask ^ 1 << observable)
+}
+
+/// Sorts the targets and removes those that appear an even number of times
+fn cancel_pairs(mut targets: Vec<usize>) -> Vec<usize> {
+    targets.sort_unstable();
+    let mut kept: Vec<usize> = Vec::with_capacity(targets.len());
+    for target in targets {
+        if kept.last() == Some(&target) {
+            kept.pop();
+        } else {
+            kept.push(target);
+        }
+    }
+    kept
+}
+
+impl fmt::Display for DetectorErrorModel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut detector_used = vec![false; self.num_detectors];
+        let mut observable_used = vec![false; self.num_observables];
+        for mechanism in &self.mechanisms {
+            write!(f, "error({})", mechanism.probability)?;
+            for &detector in &mechanism.detectors {
+                detector_used[detector] = true;
+                write!(f, " D{}", detector)?;
+            }
+            for &observable in &mechanism.observables {
+                observable_used[observable] = true;
+                write!(f, " L{}", observable)?;
+            }
+            writeln!(f)?;
+        }
+        for detector in (0..self.num_detectors).filter(|&detector| !detector_used[detector]) {
+            writeln!(f, "detector D{}", detector)?;
+        }
+        for observable in (0..self.num_observables).filter(|&observable| !observable_used[observable]) {
+            writeln!(f, "logical_observable L{}", observable)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for DetectorErrorModel {
+    type Err = DetectorErrorModelError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut model = Self::default();
+        for (number, text) in s.lines().enumerate() {
+            let line = number + 1;
+            let instruction = text.split('#').next().unwrap_or("").trim();
+            if instruction.is_empty() {
+                continue;
+            }
+            let invalid = || DetectorErrorModelError::InvalidLine { line, text: text.to_string() };
+
+            // The name, its optional parenthesised arguments, and the targets
+            let name_end =
+                instruction.find(|c: char| c == '(' || c.is_whitespace()).unwrap_or(instruction.len());
+            let (name, rest) = instruction.split_at(name_end);
+            if !matches!(name, "error" | "detector" | "logical_observable") {
+                return Err(DetectorErrorModelError::UnsupportedInstruction {
+                    line,
+                    instruction: name.to_string(),
+                });
+            }
+            let (arguments, targets) = match rest.trim_start().strip_prefix('(') {
+                Some(rest) => rest.split_once(')').ok_or_else(invalid)?,
+                None => ("", rest),
+            };
+
+            let mut detectors = Vec::new();
+            let mut observables = Vec::new();
+            for target in targets.split_whitespace().filter(|&target| target != "^") {
+                let index = |digits: &str| digits.parse::<usize>().map_err(|_| invalid());
+                if let Some(digits) = target.strip_prefix('D') {
+                    detectors.push(index(digits)?);
+                } else if let Some(digits) = target.strip_prefix('L') {
+                    observables.push(index(digits)?);
+                } else {
+                    return Err(invalid());
+                }
+            }
+
+            match name {
+                "error" => {
+                    let probability: f64 = arguments.trim().parse().map_err(|_| invalid())?;
+                    if !(0.0..=1.0).contains(&probability) {
+                        return Err(invalid());
+                    }
+                    model.add_error(probability, detectors, observables);
+                }
+                "detector" if observables.is_empty() => {
+                    for detector in detectors {
+                        model.num_detectors = model.num_detectors.max(detector + 1);
+                    }
+                }
+                "logical_observable" if detectors.is_empty() => {
+                    for observable in observables {
+                        model.num_observables = model.num_observables.max(observable + 1);
+                    }
+                }
+                _ => return Err(invalid()),
+            }
+        }
+        Ok(model)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuits::{CircuitNoise, NoisyCircuit};
+    use crate::correction_codes::{CorrectionCode, XzzxCode};
+
+    #[test]
+    fn text_round_trips_a_hand_built_model() {
+        let mut model = DetectorErrorModel::new(6, 3);
+        model.add_error(0.1 + 0.2, vec![0, 1], vec![0]);
+        model.add_error(1e-7, vec![4, 2, 2, 1], vec![]);
+        model.add_error(0.5, vec![], vec![2]);
+        model.add_error(0.0, vec![], vec![]);
+        let text = model.to_string();
+        assert_eq!(text.parse::<DetectorErrorModel>(), Ok(model.clone()), "{}", text);
+        assert!(text.contains("detector D3\n") && text.contains("detector D5\n"), "{}", text);
+        assert!(text.contains("logical_observable L1\n"), "{}", text);
+        assert_eq!(model.mechanisms()[1].detectors, vec![1, 4]);
+    }
+
+    #[test]
+    fn text_round_trips_a_circuit_model() {
+        let code = XzzxCode::new(3);
+        let circuit = NoisyCircuit::memory_experiment(
+            &code.stabilizer_generators(),
+            code.logical_z(),
+            3,
+            &CircuitNoise::uniform(0.003),
+        );
+        let model = circuit.detector_error_model();
+        assert!(!model.mechanisms().is_empty());
+        assert_eq!(model.to_string().parse::<DetectorErrorModel>(), Ok(model));
+    }
+
+    #[test]
+    fn reading_skips_coordinates_decompositions_and_comments() {
+        let text = "# a model\n\
+                    detector(1, 0, 0) D0\n\
+                    error(0.01) D0 D1 ^ D2 L0   # decomposed\n\
+                    \n\
+                    logical_observable L1\n";
+        let model: DetectorErrorModel = text.parse().unwrap();
+        assert_eq!(model.num_detectors(), 3);
+        assert_eq!(model.num_observables(), 2);
+        assert_eq!(
+            model.mechanisms(),
+            &[ErrorMechanism { probability: 0.01, detectors: vec![0, 1, 2], observables: vec![0] }]
+        );
+        assert_eq!(model.to_string(), "error(0.01) D0 D1 D2 L0\nlogical_observable L1\n");
+    }
+
+    #[test]
+    fn reading_reports_the_offending_line() {
+        assert_eq!(
+            "error(0.1) D0\nrepeat 3 {".parse::<DetectorErrorModel>(),
+            Err(DetectorErrorModelError::UnsupportedInstruction { line: 2, instruction: "repeat".to_string() })
+        );
+        for text in ["error(1.5) D0", "error(0.1) D0 X1", "error(0.1 D0", "detector L0"] {
+            let error = text.parse::<DetectorErrorModel>().unwrap_err();
+            assert_eq!(error, DetectorErrorModelError::InvalidLine { line: 1, text: text.to_string() });
+        }
+    }
+}
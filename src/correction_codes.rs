@@ -1,96 +1,372 @@
 use crate::qubit::Qubit;
-use crate::gates::*;
+use crate::decoders::{Decoder, LookupTableDecoder};
+use crate::hardware::SoftSyndrome;
+use crate::pauli::{Pauli, PauliString};
+use std::error::Error;
+use std::fmt;
 use std::time::{Duration, Instant};
 
+mod bacon_shor;
+mod color;
+mod concatenated;
+mod css;
+mod distance;
+mod enumerators;
+mod floquet;
+mod heavy_hex;
+mod qldpc;
+mod reed_muller;
+mod stabilizer;
+mod subsystem;
+mod toric;
+mod xzzx;
+
+pub use bacon_shor::BaconShorCode;
+pub use color::{Color, ColorCode, ColorCodeLattice};
+pub use concatenated::Concatenated;
+pub use css::CssCode;
+pub use distance::{
+    exhaustive_distance, information_set_distance, minimum_distance, CodeDistance,
+};
+pub use enumerators::{LogicalErrorPolynomial, WeightEnumerators};
+pub use floquet::{FloquetCode, FloquetMemory};
+pub use heavy_hex::HeavyHexCode;
+pub use reed_muller::ReedMullerCode;
+pub use stabilizer::StabilizerCode;
+pub use subsystem::{GaugeMeasurement, SubsystemCode};
+pub use toric::ToricCode;
+pub use xzzx::XzzxCode;
+
 pub trait CorrectionCode {
     fn encode(&self, data: &Qubit) -> Vec<Qubit>;
     fn syndrome_measurement(&self, encoded_qubits: &mut Vec<Qubit>) -> Vec<bool>;
     fn correct(&mut self, encoded_qubits: &mut Vec<Qubit>, syndromes: Vec<bool>); // Changed to &mut self
     fn decode(&self, encoded_qubits: &mut Vec<Qubit>) -> Qubit;
 
+    /// Corrects from repeated rounds of syndrome readout, each outcome with the probability
+    /// that it was read out wrongly, followed by a perfectly read final syndrome of the
+    /// errors now on the block. Codes whose decoders cannot use the rounds correct from the
+    /// final syndrome alone.
+    fn correct_soft(
+        &mut self,
+        encoded_qubits: &mut Vec<Qubit>,
+        _rounds: &[SoftSyndrome],
+        final_syndromes: Vec<bool>,
+    ) {
+        self.correct(encoded_qubits, final_syndromes)
+    }
+
     fn get_average_correction_time(&self) -> f64;
+
+    /// Residual logical Pauli on each encoded qubit after correction.
+    /// Codes that only compare the decoded qubit against the input return an empty Vec.
+    fn logical_errors(&self, _encoded_qubits: &[Qubit]) -> Vec<Pauli> {
+        Vec::new()
+    }
+
+    /// Physical qubits in a code block
+    fn num_qubits(&self) -> usize;
+
+    /// Logical qubits of the code, including any that `encode` leaves unused
+    fn num_logical_qubits(&self) -> usize;
+
+    /// Generators of the stabilizer group, in syndrome order for codes that measure them
+    /// directly. They need not be independent: the toric code lists every star and plaquette.
+    fn stabilizer_generators(&self) -> Vec<PauliString>;
+
+    /// A representative (X̄, Z̄) pair for each logical qubit; `encode` uses the first
+    fn logical_operators(&self) -> Vec<(PauliString, PauliString)>;
+
+    /// Number of outcomes `syndrome_measurement` returns. Codes that measure gauge operators
+    /// rather than stabilizers override the default.
+    fn syndrome_length(&self) -> usize {
+        self.stabilizer_generators().len()
+    }
+
+    /// [[n, k, d]], with the distance only when the code knows it without a search
+    fn parameters(&self) -> CodeParameters {
+        CodeParameters {
+            n: self.num_qubits(),
+            k: self.num_logical_qubits(),
+            d: None,
+        }
+    }
 }
 
+/// Boxed codes are codes too, so that a level count chosen at run time can be built up as
+/// `Concatenated<Box<dyn CorrectionCode>, _>`
+impl<C: CorrectionCode + ?Sized> CorrectionCode for Box<C> {
+    fn encode(&self, data: &Qubit) -> Vec<Qubit> {
+        (**self).encode(data)
+    }
+
+    fn syndrome_measurement(&self, encoded_qubits: &mut Vec<Qubit>) -> Vec<bool> {
+        (**self).syndrome_measurement(encoded_qubits)
+    }
+
+    fn correct(&mut self, encoded_qubits: &mut Vec<Qubit>, syndromes: Vec<bool>) {
+        (**self).correct(encoded_qubits, syndromes)
+    }
+
+    fn decode(&self, encoded_qubits: &mut Vec<Qubit>) -> Qubit {
+        (**self).decode(encoded_qubits)
+    }
+
+    fn correct_soft(
+        &mut self,
+        encoded_qubits: &mut Vec<Qubit>,
+        rounds: &[SoftSyndrome],
+        final_syndromes: Vec<bool>,
+    ) {
+        (**self).correct_soft(encoded_qubits, rounds, final_syndromes)
+    }
+
+    fn get_average_correction_time(&self) -> f64 {
+        (**self).get_average_correction_time()
+    }
+
+    fn logical_errors(&self, encoded_qubits: &[Qubit]) -> Vec<Pauli> {
+        (**self).logical_errors(encoded_qubits)
+    }
+
+    fn num_qubits(&self) -> usize {
+        (**self).num_qubits()
+    }
+
+    fn num_logical_qubits(&self) -> usize {
+        (**self).num_logical_qubits()
+    }
+
+    fn stabilizer_generators(&self) -> Vec<PauliString> {
+        (**self).stabilizer_generators()
+    }
+
+    fn logical_operators(&self) -> Vec<(PauliString, PauliString)> {
+        (**self).logical_operators()
+    }
+
+    fn syndrome_length(&self) -> usize {
+        (**self).syndrome_length()
+    }
+
+    fn parameters(&self) -> CodeParameters {
+        (**self).parameters()
+    }
+}
+
+/// The parameters [[n, k, d]] of a code; `d` is `None` when the distance is not known
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CodeParameters {
+    /// Physical qubits
+    pub n: usize,
+    /// Logical qubits
+    pub k: usize,
+    /// Code distance
+    pub d: Option<usize>,
+}
+
+impl fmt::Display for CodeParameters {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.d {
+            Some(d) => write!(f, "[[{},{},{}]]", self.n, self.k, d),
+            None => write!(f, "[[{},{},?]]", self.n, self.k),
+        }
+    }
+}
+
+/// Reasons a code description is rejected by one of the generic constructors
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CodeConstructionError {
+    /// The X and Z check matrices act on different numbers of qubits
+    DimensionMismatch { x_qubits: usize, z_qubits: usize },
+    /// An X check and a Z check overlap on an odd number of qubits
+    NonCommutingChecks { x_check: usize, z_check: usize },
+    /// The checks leave no room for a logical qubit
+    NoLogicalQubits,
+    /// A generator is not a string over I, X, Y and Z
+    InvalidPauliString(String),
+    /// A generator acts on a different number of qubits than the first one
+    LengthMismatch { generator: usize, expected: usize, found: usize },
+    /// Two stabilizer generators anticommute
+    NonCommutingGenerators { first: usize, second: usize },
+    /// A generator is a product of the ones before it
+    DependentGenerators { generator: usize },
+    /// No generators were given
+    NoGenerators,
+    /// A measurement circuit needs a two-qubit gate between device qubits with no coupler
+    UncoupledQubits { first: usize, second: usize },
+}
+
+impl fmt::Display for CodeConstructionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CodeConstructionError::DimensionMismatch { x_qubits, z_qubits } => write!(
+                f,
+                "X checks act on {} qubits but Z checks act on {}",
+                x_qubits, z_qubits
+            ),
+            CodeConstructionError::NonCommutingChecks { x_check, z_check } => write!(
+                f,
+                "X check {} and Z check {} do not commute",
+                x_check, z_check
+            ),
+            CodeConstructionError::NoLogicalQubits => write!(f, "the code encodes no logical qubits"),
+            CodeConstructionError::InvalidPauliString(generator) => {
+                write!(f, "\"{}\" is not a Pauli string", generator)
+            }
+            CodeConstructionError::LengthMismatch { generator, expected, found } => write!(
+                f,
+                "generator {} acts on {} qubits, expected {}",
+                generator, found, expected
+            ),
+            CodeConstructionError::NonCommutingGenerators { first, second } => write!(
+                f,
+                "generators {} and {} do not commute",
+                first, second
+            ),
+            CodeConstructionError::DependentGenerators { generator } => write!(
+                f,
+                "generator {} is a product of the previous generators",
+                generator
+            ),
+            CodeConstructionError::NoGenerators => write!(f, "no stabilizer generators were given"),
+            CodeConstructionError::UncoupledQubits { first, second } => write!(
+                f,
+                "device qubits {} and {} are not coupled",
+                first, second
+            ),
+        }
+    }
+}
+
+impl Error for CodeConstructionError {}
+
+/// Frame reference qubits for a code block, with the input's Pauli frame carried onto the
+/// given logical operators
+pub(crate) fn encode_frame(
+    num_qubits: usize,
+    data: &Qubit,
+    logical_x: &PauliString,
+    logical_z: &PauliString,
+) -> Vec<Qubit> {
+    let mut encoded_qubits = vec![Qubit::frame_reference(); num_qubits];
+
+    let data_frame = data.pauli_frame();
+    if data_frame.x_bit() {
+        logical_x.apply_to(&mut encoded_qubits);
+    }
+    if data_frame.z_bit() {
+        logical_z.apply_to(&mut encoded_qubits);
+    }
+
+    encoded_qubits
+}
+
+/// Logical Pauli implied by a physical frame, given one logical X/Z pair
+pub(crate) fn logical_pauli(frame: &PauliString, logical_x: &PauliString, logical_z: &PauliString) -> Pauli {
+    Pauli::from_bits(!frame.commutes_with(logical_z), !frame.commutes_with(logical_x))
+}
+
+/// Mean of the recorded correction durations in seconds
+pub(crate) fn average_duration(times: &[Duration]) -> f64 {
+    if times.is_empty() {
+        return 0.0;
+    }
+
+    let total = times.iter().fold(Duration::new(0, 0), |acc, &x| acc + x);
+
+    total.as_secs_f64() / times.len() as f64
+}
+
+/// The three-qubit repetition code against X errors, tracked as a Pauli frame
 pub struct BitFlipCode{
+    decoder: LookupTableDecoder,
     correction_time: Vec<Duration>,
 }
 
 impl BitFlipCode{
     pub fn new() -> Self {
         Self {
+            decoder: LookupTableDecoder::minimum_weight(&Self::stabilizer_generators()),
             correction_time: Vec::new(),
         }
     }
+
+    /// Z₀Z₁ and Z₀Z₂, matching the two parities of the syndrome
+    fn stabilizer_generators() -> Vec<PauliString> {
+        vec![
+            PauliString::from_support(3, &[0, 1], Pauli::Z),
+            PauliString::from_support(3, &[0, 2], Pauli::Z),
+        ]
+    }
+
+    /// X̄ = XXX and Z̄ = ZII
+    fn logical_operators() -> (PauliString, PauliString) {
+        (PauliString::from_support(3, &[0, 1, 2], Pauli::X), PauliString::from_support(3, &[0], Pauli::Z))
+    }
 }
 
-// Only declare PhaseFlipCode once
+impl Default for BitFlipCode {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The three-qubit repetition code against Z errors, tracked as a Pauli frame
 pub struct PhaseFlipCode {
+    decoder: LookupTableDecoder,
     correction_time: Vec<Duration>,
 }
 
 impl PhaseFlipCode {
     pub fn new() -> Self {
         Self {
+            decoder: LookupTableDecoder::minimum_weight(&Self::stabilizer_generators()),
             correction_time: Vec::new(),
         }
     }
+
+    /// X₀X₁ and X₀X₂, matching the two parities of the syndrome
+    fn stabilizer_generators() -> Vec<PauliString> {
+        vec![
+            PauliString::from_support(3, &[0, 1], Pauli::X),
+            PauliString::from_support(3, &[0, 2], Pauli::X),
+        ]
+    }
+
+    /// X̄ = XII and Z̄ = ZZZ
+    fn logical_operators() -> (PauliString, PauliString) {
+        (PauliString::from_support(3, &[0], Pauli::X), PauliString::from_support(3, &[0, 1, 2], Pauli::Z))
+    }
+}
+
+impl Default for PhaseFlipCode {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl CorrectionCode for BitFlipCode {
     /// Implement the Bit Flip
     fn encode(&self, data: &Qubit) -> Vec<Qubit> {
-        // Create three qubits with the same state as the input qubit
-        let mut encoded_qubits = vec![data.clone(), data.clone(), data.clone()];
-
-        // Split the vector into disjoint mutable slices
-        let (q0_slice, rest) = encoded_qubits.split_at_mut(1);
-        let q0 = &mut q0_slice[0];
-        let (q1, q2) = rest.split_at_mut(1);
-
-        // Apply CNOT gates
-        CNOT::apply(q0, &mut q1[0]);
-        CNOT::apply(q0, &mut q2[0]);
-
-        // Return the encoded qubits
-        encoded_qubits
+        // Three frame reference qubits, with the input's frame carried by X̄ = XXX, Z̄ = ZII
+        let (logical_x, logical_z) = Self::logical_operators();
+        encode_frame(3, data, &logical_x, &logical_z)
     }
 
     fn syndrome_measurement(&self, encoded_qubits: &mut Vec<Qubit>) -> Vec<bool> {
-        // For a 3-qubit bit flip code, we need 2 syndrome bits
-        let mut syndromes = vec![false, false];
+        let frame = PauliString::from_frame(encoded_qubits);
+        let flipped = frame.x_bits();
 
-        // Get measurement results (we should measure only once per qubit)
-        let q0_result = encoded_qubits[0].measure();
-        let q1_result = encoded_qubits[1].measure();
-        let q2_result = encoded_qubits[2].measure();
-
-        // Calculate syndromes
-        // First syndrome is parity of qubit 0 and 1
-        syndromes[0] = q0_result ^ q1_result;
-
-        // Second syndrome is parity of qubit 0 and 2
-        syndromes[1] = q0_result ^ q2_result;
-
-        syndromes
+        // First syndrome is parity of qubit 0 and 1, second of qubit 0 and 2
+        vec![flipped[0] ^ flipped[1], flipped[0] ^ flipped[2]]
     }
 
     fn correct(&mut self, encoded_qubits: &mut Vec<Qubit>, syndromes: Vec<bool>) {
         let start = Instant::now();
 
-        // Use the syndromes parameter instead of looking for a syndromes variable
-        if syndromes.len() >= 2 {
-            if syndromes[0] && syndromes[1] {
-                PauliX.apply(&mut encoded_qubits[0]);
-            }
-            // Only first syndrome true: error on qubit 1
-            else if syndromes[0] {
-                PauliX.apply(&mut encoded_qubits[1]);
-            }
-            // Only second syndrome true: error on qubit 2
-            else if syndromes[1] {
-                PauliX.apply(&mut encoded_qubits[2]);
-            }
-        }
+        // The lightest X error with this syndrome
+        self.decoder.decode(&syndromes).apply_to(encoded_qubits);
 
         let duration = start.elapsed();
 
@@ -98,132 +374,103 @@ impl CorrectionCode for BitFlipCode {
     }
 
     fn decode(&self, encoded_qubits: &mut Vec<Qubit>) -> Qubit {
-        // Return the corrected logical qubit
-        let mut decoded_qubit = encoded_qubits[0].clone();
+        let frame = PauliString::from_frame(encoded_qubits);
+        let (logical_x, logical_z) = Self::logical_operators();
 
-        // Apply CNOT gates to decode the qubits
-        CNOT::apply(&mut decoded_qubit, &mut encoded_qubits[1]);
-        CNOT::apply(&mut decoded_qubit, &mut encoded_qubits[2]);
+        let mut decoded_qubit = Qubit::frame_reference();
+        logical_pauli(&frame, &logical_x, &logical_z).apply(&mut decoded_qubit);
 
-        // Return the decoded qubit
         decoded_qubit
     }
 
     fn get_average_correction_time(&self) -> f64 {
-        if self.correction_time.is_empty(){
-            return 0.0
-        }
-
-        let total = self.correction_time.iter()
-            .fold(Duration::new(0, 0), |acc, &x| acc + x);
-
-        total.as_secs_f64() / self.correction_time.len() as f64
+        average_duration(&self.correction_time)
     }
-}
 
-// Keep only ONE implementation for PhaseFlipCode
-impl CorrectionCode for PhaseFlipCode {
-    fn encode(&self, data: &Qubit) -> Vec<Qubit> {
-        // Create three qubits in the |+⟩ state if data is |+⟩, or |-⟩ state if data is |-⟩
-        let mut encoded_qubits = vec![data.clone(), data.clone(), data.clone()];
+    fn logical_errors(&self, encoded_qubits: &[Qubit]) -> Vec<Pauli> {
+        let frame = PauliString::from_frame(encoded_qubits);
+        let (logical_x, logical_z) = Self::logical_operators();
+        vec![logical_pauli(&frame, &logical_x, &logical_z)]
+    }
 
-        // Apply Hadamard gates to convert to the |+⟩/|-⟩ basis
-        for qubit in &mut encoded_qubits {
-            Hadamard.apply(qubit);
-        }
+    fn num_qubits(&self) -> usize {
+        3
+    }
 
-        // Apply CZ gates (controlled-Z) from first qubit to others
-        // Since you might not have CZ implemented, we can simulate with H + CNOT + H
-        let (q0_slice, rest) = encoded_qubits.split_at_mut(1);
-        let q0 = &mut q0_slice[0];
-        let (q1, q2) = rest.split_at_mut(1);
+    fn num_logical_qubits(&self) -> usize {
+        1
+    }
 
-        // Apply H to target, CNOT, then H again to simulate CZ
-        Hadamard.apply(&mut q1[0]);
-        CNOT::apply(q0, &mut q1[0]);
-        Hadamard.apply(&mut q1[0]);
+    fn stabilizer_generators(&self) -> Vec<PauliString> {
+        Self::stabilizer_generators()
+    }
 
-        Hadamard.apply(&mut q2[0]);
-        CNOT::apply(q0, &mut q2[0]);
-        Hadamard.apply(&mut q2[0]);
+    fn logical_operators(&self) -> Vec<(PauliString, PauliString)> {
+        vec![Self::logical_operators()]
+    }
+}
 
-        encoded_qubits
+impl CorrectionCode for PhaseFlipCode {
+    fn encode(&self, data: &Qubit) -> Vec<Qubit> {
+        // The bit flip code in the |+⟩/|-⟩ basis: X̄ = XII, Z̄ = ZZZ
+        let (logical_x, logical_z) = Self::logical_operators();
+        encode_frame(3, data, &logical_x, &logical_z)
     }
 
     fn syndrome_measurement(&self, encoded_qubits: &mut Vec<Qubit>) -> Vec<bool> {
-        // Convert to X-basis for measurement
-        for qubit in encoded_qubits.iter_mut() {
-            Hadamard.apply(qubit);
-        }
-
-        // Now measure in computational basis
-        let q0_result = encoded_qubits[0].measure();
-        let q1_result = encoded_qubits[1].measure();
-        let q2_result = encoded_qubits[2].measure();
-
-        // Convert back to Z-basis
-        for qubit in encoded_qubits.iter_mut() {
-            Hadamard.apply(qubit);
-        }
-
-        // Calculate parity checks
-        let syndromes = vec![
-            q0_result ^ q1_result,  // Parity between qubit 0 and 1
-            q0_result ^ q2_result   // Parity between qubit 0 and 2
-        ];
-
-        syndromes
+        let frame = PauliString::from_frame(encoded_qubits);
+        let flipped = frame.z_bits();
+
+        // Calculate parity checks in the X basis
+        vec![
+            flipped[0] ^ flipped[1], // Parity between qubit 0 and 1
+            flipped[0] ^ flipped[2], // Parity between qubit 0 and 2
+        ]
     }
 
     fn correct(&mut self, encoded_qubits: &mut Vec<Qubit>, syndromes: Vec<bool>) {
         let start = Instant::now();
 
-        if syndromes.len() >= 2 {
-            // Both syndromes true: error on qubit 0
-            if syndromes[0] && syndromes[1] {
-                PauliZ.apply(&mut encoded_qubits[0]);
-            }
-            // Only first syndrome true: error on qubit 1
-            else if syndromes[0] {
-                PauliZ.apply(&mut encoded_qubits[1]);
-            }
-            // Only second syndrome true: error on qubit 2
-            else if syndromes[1] {
-                PauliZ.apply(&mut encoded_qubits[2]);
-            }
-        }
+        // The lightest Z error with this syndrome
+        self.decoder.decode(&syndromes).apply_to(encoded_qubits);
 
         let duration = start.elapsed();
         self.correction_time.push(duration);
     }
 
     fn decode(&self, encoded_qubits: &mut Vec<Qubit>) -> Qubit {
-        // Apply Hadamard gates to convert back to computational basis
-        for qubit in encoded_qubits.iter_mut() {
-            Hadamard.apply(qubit);
-        }
+        let frame = PauliString::from_frame(encoded_qubits);
+        let (logical_x, logical_z) = Self::logical_operators();
 
-        // Extract the logical qubit (similar to bit flip code)
-        let mut decoded_qubit = encoded_qubits[0].clone();
-
-        // Apply CNOT gates to decode
-        CNOT::apply(&mut decoded_qubit, &mut encoded_qubits[1]);
-        CNOT::apply(&mut decoded_qubit, &mut encoded_qubits[2]);
-
-        // Convert back from |+⟩/|-⟩ basis to computational basis
-        Hadamard.apply(&mut decoded_qubit);
+        let mut decoded_qubit = Qubit::frame_reference();
+        logical_pauli(&frame, &logical_x, &logical_z).apply(&mut decoded_qubit);
 
         decoded_qubit
     }
 
     fn get_average_correction_time(&self) -> f64 {
-        if self.correction_time.is_empty(){
-            return 0.0
-        }
+        average_duration(&self.correction_time)
+    }
 
-        let total = self.correction_time.iter()
-            .fold(Duration::new(0, 0), |acc, &x| acc + x);
+    fn logical_errors(&self, encoded_qubits: &[Qubit]) -> Vec<Pauli> {
+        let frame = PauliString::from_frame(encoded_qubits);
+        let (logical_x, logical_z) = Self::logical_operators();
+        vec![logical_pauli(&frame, &logical_x, &logical_z)]
+    }
+
+    fn num_qubits(&self) -> usize {
+        3
+    }
+
+    fn num_logical_qubits(&self) -> usize {
+        1
+    }
+
+    fn stabilizer_generators(&self) -> Vec<PauliString> {
+        Self::stabilizer_generators()
+    }
 
-        total.as_secs_f64() / self.correction_time.len() as f64
+    fn logical_operators(&self) -> Vec<(PauliString, PauliString)> {
+        vec![Self::logical_operators()]
     }
-}
\ No newline at end of file
+}
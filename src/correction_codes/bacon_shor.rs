@@ -0,0 +1,269 @@
+use crate::correction_codes::{
+    average_duration, encode_frame, logical_pauli, CodeParameters, CorrectionCode, SubsystemCode,
+};
+use crate::pauli::{Pauli, PauliString};
+use crate::qubit::Qubit;
+use std::time::{Duration, Instant};
+
+/// The Bacon–Shor subsystem code on a grid of `rows` × `cols` qubits.
+///
+/// Qubit (r, c) has index `r * cols + c`. The gauge group is generated by XX on vertically
+/// neighbouring qubits and ZZ on horizontally neighbouring ones, all of weight two. Their
+/// products over a pair of adjacent rows are the X-type stabilizers and their products over a
+/// pair of adjacent columns the Z-type stabilizers. Logical X is X along a row and logical Z
+/// is Z along a column, so the code distance is min(rows, cols).
+///
+/// Gauge outcomes come first for the XX generators, `(rows - 1) * cols` of them ordered by
+/// their upper qubit, then for the `rows * (cols - 1)` ZZ generators ordered by their left
+/// qubit. `correct` treats the row and column parities as two repetition codes and flips
+/// the lighter of the two patterns consistent with the stabilizers.
+pub struct BaconShorCode {
+    rows: usize,
+    cols: usize,
+    subsystem: SubsystemCode,
+    logical_x: PauliString,
+    logical_z: PauliString,
+    correction_time: Vec<Duration>,
+}
+
+impl BaconShorCode {
+    pub fn new(rows: usize, cols: usize) -> Self {
+        assert!(rows >= 2 && cols >= 2, "the Bacon–Shor code needs a grid of at least 2×2");
+        let n = rows * cols;
+        let qubit = |r: usize, c: usize| r * cols + c;
+
+        let mut gauge_generators = Vec::with_capacity((rows - 1) * cols + rows * (cols - 1));
+        for r in 0..rows - 1 {
+            for c in 0..cols {
+                let pair = [qubit(r, c), qubit(r + 1, c)];
+                gauge_generators.push(PauliString::from_support(n, &pair, Pauli::X));
+            }
+        }
+        for r in 0..rows {
+            for c in 0..cols - 1 {
+                let pair = [qubit(r, c), qubit(r, c + 1)];
+                gauge_generators.push(PauliString::from_support(n, &pair, Pauli::Z));
+            }
+        }
+        let subsystem = SubsystemCode::from_pauli_strings(gauge_generators)
+            .expect("the Bacon–Shor gauge group encodes one logical qubit")
+            .with_distance(rows.min(cols));
+
+        let row: Vec<usize> = (0..cols).map(|c| qubit(0, c)).collect();
+        let column: Vec<usize> = (0..rows).map(|r| qubit(r, 0)).collect();
+
+        Self {
+            rows,
+            cols,
+            subsystem,
+            logical_x: PauliString::from_support(n, &row, Pauli::X),
+            logical_z: PauliString::from_support(n, &column, Pauli::Z),
+            correction_time: Vec::new(),
+        }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn num_qubits(&self) -> usize {
+        self.rows * self.cols
+    }
+
+    pub fn distance(&self) -> usize {
+        self.rows.min(self.cols)
+    }
+
+    /// The underlying gauge group, e.g. for gauge fixing
+    pub fn as_subsystem(&self) -> &SubsystemCode {
+        &self.subsystem
+    }
+
+    pub fn logical_x(&self) -> &PauliString {
+        &self.logical_x
+    }
+
+    pub fn logical_z(&self) -> &PauliString {
+        &self.logical_z
+    }
+
+    /// Values of the X-type stabilizers on rows (r, r+1), from the XX gauge outcomes
+    pub fn row_pair_parities(&self, gauge_outcomes: &[bool]) -> Vec<bool> {
+        gauge_outcomes[..(self.rows - 1) * self.cols]
+            .chunks(self.cols)
+            .map(|row| row.iter().fold(false, |parity, &bit| parity ^ bit))
+            .collect()
+    }
+
+    /// Values of the Z-type stabilizers on columns (c, c+1), from the ZZ gauge outcomes
+    pub fn column_pair_parities(&self, gauge_outcomes: &[bool]) -> Vec<bool> {
+        let zz_outcomes = &gauge_outcomes[(self.rows - 1) * self.cols..];
+        (0..self.cols - 1)
+            .map(|c| {
+                (0..self.rows).fold(false, |parity, r| parity ^ zz_outcomes[r * (self.cols - 1) + c])
+            })
+            .collect()
+    }
+}
+
+impl CorrectionCode for BaconShorCode {
+    fn encode(&self, data: &Qubit) -> Vec<Qubit> {
+        encode_frame(self.num_qubits(), data, &self.logical_x, &self.logical_z)
+    }
+
+    fn syndrome_measurement(&self, encoded_qubits: &mut Vec<Qubit>) -> Vec<bool> {
+        self.subsystem.measure_gauge(encoded_qubits)
+    }
+
+    fn correct(&mut self, encoded_qubits: &mut Vec<Qubit>, syndromes: Vec<bool>) {
+        let start = Instant::now();
+
+        // Z errors flip the parity of their row; one Z per flagged row, on its first qubit,
+        // differs from the error by ZZ gauge operators only. X errors likewise per column.
+        let flagged_rows = lighter_repetition_pattern(&self.row_pair_parities(&syndromes));
+        let flagged_columns = lighter_repetition_pattern(&self.column_pair_parities(&syndromes));
+        for (r, _) in flagged_rows.iter().enumerate().filter(|(_, &flagged)| flagged) {
+            Pauli::Z.apply(&mut encoded_qubits[r * self.cols]);
+        }
+        for (c, _) in flagged_columns.iter().enumerate().filter(|(_, &flagged)| flagged) {
+            Pauli::X.apply(&mut encoded_qubits[c]);
+        }
+
+        let duration = start.elapsed();
+        self.correction_time.push(duration);
+    }
+
+    fn decode(&self, encoded_qubits: &mut Vec<Qubit>) -> Qubit {
+        let frame = PauliString::from_frame(encoded_qubits);
+
+        let mut decoded_qubit = Qubit::frame_reference();
+        logical_pauli(&frame, &self.logical_x, &self.logical_z).apply(&mut decoded_qubit);
+
+        decoded_qubit
+    }
+
+    fn get_average_correction_time(&self) -> f64 {
+        average_duration(&self.correction_time)
+    }
+
+    fn logical_errors(&self, encoded_qubits: &[Qubit]) -> Vec<Pauli> {
+        let frame = PauliString::from_frame(encoded_qubits);
+        vec![logical_pauli(&frame, &self.logical_x, &self.logical_z)]
+    }
+
+    fn num_qubits(&self) -> usize {
+        BaconShorCode::num_qubits(self)
+    }
+
+    fn num_logical_qubits(&self) -> usize {
+        1
+    }
+
+    fn stabilizer_generators(&self) -> Vec<PauliString> {
+        self.subsystem.stabilizers().to_vec()
+    }
+
+    fn logical_operators(&self) -> Vec<(PauliString, PauliString)> {
+        vec![(self.logical_x.clone(), self.logical_z.clone())]
+    }
+
+    /// One outcome per gauge generator
+    fn syndrome_length(&self) -> usize {
+        self.subsystem.syndrome_length()
+    }
+
+    fn parameters(&self) -> CodeParameters {
+        CodeParameters {
+            n: BaconShorCode::num_qubits(self),
+            k: 1,
+            d: Some(self.distance()),
+        }
+    }
+}
+
+/// Decodes a repetition code whose checks compare neighbouring bits, returning the lighter
+/// of the two bit patterns with the given differences
+pub(super) fn lighter_repetition_pattern(differences: &[bool]) -> Vec<bool> {
+    let mut pattern = vec![false];
+    for &difference in differences {
+        let last = pattern[pattern.len() - 1];
+        pattern.push(last ^ difference);
+    }
+    let weight = pattern.iter().filter(|&&bit| bit).count();
+    if 2 * weight > pattern.len() {
+        for bit in pattern.iter_mut() {
+            *bit = !*bit;
+        }
+    }
+    pattern
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::correction_codes::{minimum_distance, CodeConstructionError};
+
+    /// Encodes, applies `error` to one qubit, corrects and reads off the logical error
+    fn residual_logical_error(code: &mut dyn CorrectionCode, qubit: usize, error: Pauli) -> Vec<Pauli> {
+        let mut encoded = code.encode(&Qubit::new());
+        error.apply(&mut encoded[qubit]);
+        let syndromes = code.syndrome_measurement(&mut encoded);
+        code.correct(&mut encoded, syndromes);
+        code.logical_errors(&encoded)
+    }
+
+    #[test]
+    fn three_by_three_is_a_nine_qubit_code_with_four_gauge_qubits() {
+        let code = BaconShorCode::new(3, 3);
+        assert_eq!(code.parameters(), CodeParameters { n: 9, k: 1, d: Some(3) });
+        let subsystem = code.as_subsystem();
+        assert_eq!(subsystem.num_gauge_qubits(), 4);
+        assert_eq!(subsystem.stabilizers().len(), 4);
+        assert_eq!(subsystem.parameters(), code.parameters());
+        // The dressed distance, over the whole gauge group
+        let logicals = [code.logical_x().clone(), code.logical_z().clone()];
+        assert_eq!(minimum_distance(subsystem.gauge_generators(), &logicals).distance, 3);
+    }
+
+    #[test]
+    fn corrects_every_single_qubit_error() {
+        let mut code = BaconShorCode::new(3, 3);
+        let gauge_generators = code.as_subsystem().gauge_generators().to_vec();
+        let mut subsystem = SubsystemCode::from_pauli_strings(gauge_generators)
+            .expect("the Bacon–Shor gauge group encodes one logical qubit");
+        for qubit in 0..9 {
+            for error in [Pauli::X, Pauli::Y, Pauli::Z] {
+                let residual = residual_logical_error(&mut code, qubit, error);
+                assert_eq!(residual, [Pauli::I], "{:?} on {}", error, qubit);
+                let residual = residual_logical_error(&mut subsystem, qubit, error);
+                assert_eq!(residual, [Pauli::I], "{:?} on {}", error, qubit);
+            }
+        }
+    }
+
+    #[test]
+    fn gauge_fixing_keeps_the_logical_qubit_and_distance() {
+        let code = BaconShorCode::new(3, 3);
+        let subsystem = code.as_subsystem();
+        // Fixing every ZZ generator gives Shor's code
+        let zz: Vec<usize> = (6..12).collect();
+        let fixed = subsystem.gauge_fix(&zz).expect("the ZZ generators commute");
+        assert_eq!(fixed.parameters(), CodeParameters { n: 9, k: 1, d: Some(3) });
+        let logicals = [fixed.logical_x(0).clone(), fixed.logical_z(0).clone()];
+        assert_eq!(minimum_distance(fixed.generators(), &logicals).distance, 3);
+
+        // Fixing only some leaves the other gauge qubits as logical qubits of unknown distance
+        let partial = subsystem.gauge_fix(&zz[..2]).expect("the ZZ generators commute");
+        assert!(partial.num_logical_qubits() > 1);
+        assert_eq!(partial.parameters().d, None);
+
+        assert_eq!(
+            subsystem.gauge_fix(&[0, 6]).err(),
+            Some(CodeConstructionError::NonCommutingGenerators { first: 0, second: 6 })
+        );
+    }
+}
@@ -0,0 +1,720 @@
+use crate::correction_codes::{average_duration, CodeParameters, CorrectionCode, CssCode};
+use crate::gates::CliffordGate;
+use crate::gf2::{add_into, BinaryMatrix};
+use crate::matching::DecodingGraph;
+use crate::pauli::{Pauli, PauliString};
+use crate::qubit::Qubit;
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+/// The three face colours of a color code
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Color {
+    Red,
+    Green,
+    Blue,
+}
+
+impl Color {
+    pub const ALL: [Color; 3] = [Color::Red, Color::Green, Color::Blue];
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    fn from_index(index: usize) -> Self {
+        Self::ALL[index % 3]
+    }
+}
+
+/// The tilings a triangular color code can be cut from
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorCodeLattice {
+    /// The 6.6.6 (honeycomb) tiling, with n = (3d² + 1)/4
+    Hexagonal,
+    /// The 4.8.8 (square-octagon) tiling, with n = d² − d + 1 for the layout built here
+    SquareOctagon,
+}
+
+/// A triangular 2D color code of odd distance on the 6.6.6 or 4.8.8 tiling.
+///
+/// Every face carries both an X-type and a Z-type stabilizer, so the code is a self-dual
+/// `CssCode` and H, S and CNOT are all transversal. The three sides of the triangle are
+/// boundaries of the three colours; a boundary of colour c is where faces of colour c are
+/// missing.
+///
+/// `correct` runs the restriction decoder of Kubica and Delfosse: defects are matched on
+/// the three two-colour restricted lattices with `DecodingGraph`, and the matched edges
+/// are lifted to qubits locally around the faces of one colour. The lift is tried for each
+/// colour and the lightest correction wins. When ties between equally short matchings
+/// leave that correction heavier than (d − 1)/2, the lightest correction up to that weight
+/// is searched for directly, so every error of weight up to (d − 1)/2 is corrected.
+pub struct ColorCode {
+    lattice: ColorCodeLattice,
+    distance: usize,
+    css: CssCode,
+    faces: Vec<Vec<usize>>,
+    face_colors: Vec<Color>,
+    /// For each qubit its face of each colour, or `faces.len() + colour` on that boundary
+    qubit_vertices: Vec<[usize; 3]>,
+    restricted_lattices: Vec<RestrictedLattice>,
+    stars: Vec<Star>,
+    correction_time: Vec<Duration>,
+}
+
+/// Matching graph on the faces and boundaries of two colours. Nodes are numbered like
+/// `qubit_vertices`, so nodes of the third colour are simply left isolated.
+struct RestrictedLattice {
+    colors: [Color; 2],
+    graph: DecodingGraph,
+}
+
+/// The qubits around one face or boundary and the other faces and boundaries they touch,
+/// used to lift matched edges back to qubits
+struct Star {
+    color: Color,
+    center: usize,
+    qubits: Vec<usize>,
+    neighbours: Vec<usize>,
+    incidence: BinaryMatrix,
+    kernel: BinaryMatrix,
+}
+
+impl ColorCode {
+    /// The 6.6.6 triangle of the given odd distance: 7, 19, 37, ... qubits
+    pub fn hexagonal(distance: usize) -> Self {
+        assert!(distance >= 3 && distance % 2 == 1, "color codes need an odd distance of at least 3");
+
+        // Sites (r, c) with 0 ≤ c ≤ r ≤ B of a triangular grid. Sites with r + c ≡ 1 (mod 3)
+        // are hexagon centres and all others are qubits.
+        let size = 3 * (distance - 1) / 2;
+        let is_face = |r: usize, c: usize| (r + c) % 3 == 1;
+        let mut qubit_index = BTreeMap::new();
+        for r in 0..=size {
+            for c in 0..=r {
+                if !is_face(r, c) {
+                    let next = qubit_index.len();
+                    qubit_index.insert((r, c), next);
+                }
+            }
+        }
+
+        let mut faces = Vec::new();
+        for r in 0..=size {
+            for c in (0..=r).filter(|&c| is_face(r, c)) {
+                let (r, c) = (r as isize, c as isize);
+                let around = [(r - 1, c - 1), (r - 1, c), (r, c + 1), (r + 1, c + 1), (r + 1, c), (r, c - 1)];
+                let qubits: Vec<usize> = around
+                    .iter()
+                    .filter(|&&(r, c)| r >= 0 && c >= 0)
+                    .filter_map(|&(r, c)| qubit_index.get(&(r as usize, c as usize)).copied())
+                    .collect();
+                faces.push((Color::from_index(r as usize), qubits));
+            }
+        }
+
+        Self::from_faces(ColorCodeLattice::Hexagonal, distance, qubit_index.len(), faces)
+    }
+
+    /// The 4.8.8 triangle of the given odd distance: 7, 21, 43, ... qubits.
+    ///
+    /// The triangle is cut straight along the lattice, with the octagon-octagon boundary
+    /// along one axis and the two other boundaries on the diagonals, so every face is a tile
+    /// of the tiling with the part beyond a cut removed. This is not the standard family of
+    /// Landahl, Anderson and Rice, with n = (d² − 1)/2 + d (17 qubits at d = 5, 31 at
+    /// d = 7): no straight cut of the tiling leaves its boundary faces, and it is not built
+    /// here. Overheads measured on this layout are larger than on that family.
+    pub fn square_octagon(distance: usize) -> Self {
+        assert!(distance >= 3 && distance % 2 == 1, "color codes need an odd distance of at least 3");
+
+        // Work on the dual lattice: octagons sit at integer points (i, j) and squares at
+        // (i + ½, j + ½), both stored in doubled coordinates. Every qubit is a triangle
+        // made of one square and two neighbouring octagons, or a triangle that is closed
+        // off by one or two boundaries.
+        let limit = 2 * (distance as i64 - 2);
+        let inside = |(x, y): (i64, i64)| y >= 0 && x - y >= 0 && x + y <= limit;
+        let color = |(x, y): (i64, i64)| {
+            if x % 2 != 0 {
+                Color::Blue
+            } else {
+                Color::from_index(((x + y) / 2) as usize % 2)
+            }
+        };
+
+        let mut triangles: Vec<Vec<Vertex>> = Vec::new();
+        for y in (1..=limit).step_by(2) {
+            for x in (1..=limit + 1).step_by(2) {
+                if !inside((x, y)) {
+                    continue;
+                }
+                let corners = [(x - 1, y - 1), (x + 1, y - 1), (x + 1, y + 1), (x - 1, y + 1)];
+                for k in 0..4 {
+                    let (a, b) = (corners[k], corners[(k + 1) % 4]);
+                    if inside(a) && inside(b) {
+                        triangles.push(vec![Vertex::Face(x, y), Vertex::Face(a.0, a.1), Vertex::Face(b.0, b.1)]);
+                    }
+                }
+            }
+        }
+
+        // Edges used by a single triangle lie on a boundary: the qubit on the other side
+        // has that boundary in place of its missing face. Faces that meet two boundaries
+        // are corners and get one more qubit.
+        let mut edge_uses: BTreeMap<(Vertex, Vertex), usize> = BTreeMap::new();
+        for triangle in &triangles {
+            for k in 0..3 {
+                let (a, b) = (triangle[k], triangle[(k + 1) % 3]);
+                *edge_uses.entry((a.min(b), a.max(b))).or_insert(0) += 1;
+            }
+        }
+        let vertex_color = |vertex: Vertex| match vertex {
+            Vertex::Face(x, y) => color((x, y)),
+            Vertex::Boundary(color) => color,
+        };
+        let mut boundaries_at: BTreeMap<Vertex, BTreeSet<Color>> = BTreeMap::new();
+        for (&(a, b), _) in edge_uses.iter().filter(|(_, &uses)| uses == 1) {
+            let missing = Color::from_index(3 - vertex_color(a).index() - vertex_color(b).index());
+            triangles.push(vec![a, b, Vertex::Boundary(missing)]);
+            boundaries_at.entry(a).or_default().insert(missing);
+            boundaries_at.entry(b).or_default().insert(missing);
+        }
+        for (&vertex, colors) in boundaries_at.iter().filter(|(_, colors)| colors.len() == 2) {
+            let mut triangle = vec![vertex];
+            triangle.extend(colors.iter().map(|&color| Vertex::Boundary(color)));
+            triangles.push(triangle);
+        }
+
+        let face_vertices: BTreeSet<Vertex> = triangles
+            .iter()
+            .flatten()
+            .copied()
+            .filter(|vertex| matches!(vertex, Vertex::Face(..)))
+            .collect();
+        let faces = face_vertices
+            .iter()
+            .map(|&vertex| {
+                let qubits = (0..triangles.len()).filter(|&q| triangles[q].contains(&vertex)).collect();
+                (vertex_color(vertex), qubits)
+            })
+            .collect();
+
+        Self::from_faces(ColorCodeLattice::SquareOctagon, distance, triangles.len(), faces)
+    }
+
+    /// Builds the code and its restriction decoder from coloured faces
+    fn from_faces(
+        lattice: ColorCodeLattice,
+        distance: usize,
+        num_qubits: usize,
+        faces: Vec<(Color, Vec<usize>)>,
+    ) -> Self {
+        let (face_colors, faces): (Vec<Color>, Vec<Vec<usize>>) = faces.into_iter().unzip();
+        let checks = BinaryMatrix::from_rows(
+            num_qubits,
+            faces
+                .iter()
+                .map(|face| (0..num_qubits).map(|q| face.contains(&q)).collect())
+                .collect(),
+        );
+        let css = CssCode::from_parity_checks(checks.clone(), checks)
+            .expect("color code faces overlap evenly and leave one logical qubit");
+
+        // Each qubit touches one face of each colour, or the boundary of that colour.
+        // Vertices of the decoding lattices are the faces followed by the three boundaries.
+        let boundary = |color: Color| faces.len() + color.index();
+        let vertex_color = |vertex: usize| {
+            if vertex < faces.len() {
+                face_colors[vertex]
+            } else {
+                Color::from_index(vertex - faces.len())
+            }
+        };
+        let mut qubit_vertices = vec![[usize::MAX; 3]; num_qubits];
+        for (f, face) in faces.iter().enumerate() {
+            for &q in face {
+                qubit_vertices[q][face_colors[f].index()] = f;
+            }
+        }
+        for vertices in qubit_vertices.iter_mut() {
+            for color in Color::ALL {
+                if vertices[color.index()] == usize::MAX {
+                    vertices[color.index()] = boundary(color);
+                }
+            }
+        }
+
+        let num_vertices = faces.len() + 3;
+        let restricted_lattices = [[Color::Red, Color::Green], [Color::Red, Color::Blue], [Color::Green, Color::Blue]]
+            .into_iter()
+            .map(|colors| {
+                let mut graph = DecodingGraph::new(num_vertices);
+                for color in colors {
+                    graph.set_boundary(boundary(color));
+                }
+                let mut seen = BTreeSet::new();
+                for vertices in &qubit_vertices {
+                    let (a, b) = (vertices[colors[0].index()], vertices[colors[1].index()]);
+                    // Boundary-to-boundary edges are never worth crossing
+                    if (a < faces.len() || b < faces.len()) && seen.insert((a, b)) {
+                        graph.add_edge(a, b, 1.0);
+                    }
+                }
+                RestrictedLattice { colors, graph }
+            })
+            .collect();
+
+        let stars = (0..num_vertices)
+            .map(|center| {
+                let color = vertex_color(center);
+                let qubits: Vec<usize> = (0..num_qubits)
+                    .filter(|&q| qubit_vertices[q][color.index()] == center)
+                    .collect();
+                // Edges between two boundaries are in neither restricted lattice and are
+                // left unconstrained
+                let neighbours: Vec<usize> = qubits
+                    .iter()
+                    .flat_map(|&q| qubit_vertices[q])
+                    .filter(|&vertex| vertex != center)
+                    .filter(|&vertex| center < faces.len() || vertex < faces.len())
+                    .collect::<BTreeSet<_>>()
+                    .into_iter()
+                    .collect();
+                let incidence = BinaryMatrix::from_rows(
+                    qubits.len(),
+                    neighbours
+                        .iter()
+                        .map(|n| qubits.iter().map(|&q| qubit_vertices[q].contains(n)).collect())
+                        .collect(),
+                );
+                let kernel = incidence.kernel();
+                Star { color, center, qubits, neighbours, incidence, kernel }
+            })
+            .collect();
+
+        Self {
+            lattice,
+            distance,
+            css,
+            faces,
+            face_colors,
+            qubit_vertices,
+            restricted_lattices,
+            stars,
+            correction_time: Vec::new(),
+        }
+    }
+
+    pub fn lattice(&self) -> ColorCodeLattice {
+        self.lattice
+    }
+
+    pub fn distance(&self) -> usize {
+        self.distance
+    }
+
+    pub fn num_qubits(&self) -> usize {
+        self.css.num_qubits()
+    }
+
+    /// Qubits of each face; every face is both an X and a Z stabilizer
+    pub fn faces(&self) -> &[Vec<usize>] {
+        &self.faces
+    }
+
+    pub fn face_color(&self, face: usize) -> Color {
+        self.face_colors[face]
+    }
+
+    /// The code as a plain CSS code, with Hx = Hz
+    pub fn as_css(&self) -> &CssCode {
+        &self.css
+    }
+
+    pub fn logical_x(&self) -> &PauliString {
+        self.css.logical_x(0)
+    }
+
+    pub fn logical_z(&self) -> &PauliString {
+        self.css.logical_z(0)
+    }
+
+    /// H on every qubit, which implements the logical Hadamard
+    pub fn transversal_hadamard(&self) -> Vec<CliffordGate> {
+        (0..self.num_qubits()).map(CliffordGate::H).collect()
+    }
+
+    /// S on one sublattice and S† on the other, which implements the logical phase gate.
+    ///
+    /// The qubits form a bipartite graph in which every face alternates between the two
+    /// sublattices, so the phases picked up by each X stabilizer cancel. X̄ picks up i for
+    /// each S and −i for each S† on its support, so the sublattice that gets S is chosen to
+    /// make that i, mapping X̄ to i·X̄Z̄ rather than −i·X̄Z̄.
+    pub fn transversal_phase(&self) -> Vec<CliffordGate> {
+        let mut sublattices = self.sublattices();
+        let quarter_turns: usize =
+            self.logical_x().support().iter().map(|&q| if sublattices[q] { 3 } else { 1 }).sum();
+        if quarter_turns % 4 == 3 {
+            sublattices.iter_mut().for_each(|odd| *odd = !*odd);
+        }
+        sublattices
+            .into_iter()
+            .enumerate()
+            .map(|(q, odd)| if odd { CliffordGate::SDag(q) } else { CliffordGate::S(q) })
+            .collect()
+    }
+
+    /// Qubit-wise CNOT from this block, qubits 0..n, onto a second copy, qubits n..2n,
+    /// which implements the logical CNOT between the two blocks
+    pub fn transversal_cnot(&self) -> Vec<CliffordGate> {
+        let n = self.num_qubits();
+        (0..n).map(|q| CliffordGate::Cnot { control: q, target: n + q }).collect()
+    }
+
+    /// Two-colours the qubits so that neighbours around every face alternate. Two qubits
+    /// are neighbours when they share two faces, or a face and the boundary they sit on.
+    /// Panics if the lattice gives a graph that is not bipartite.
+    fn sublattices(&self) -> Vec<bool> {
+        let n = self.num_qubits();
+        let vertices_of: Vec<[usize; 3]> = self
+            .qubit_vertices
+            .iter()
+            .map(|vertices| {
+                let mut sorted = *vertices;
+                sorted.sort_unstable();
+                sorted
+            })
+            .collect();
+
+        let mut sharing: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+        for (q, vertices) in vertices_of.iter().enumerate() {
+            for (a, b) in [(0, 1), (0, 2), (1, 2)] {
+                sharing.entry((vertices[a], vertices[b])).or_default().push(q);
+            }
+        }
+
+        let mut side = vec![None; n];
+        for start in 0..n {
+            if side[start].is_some() {
+                continue;
+            }
+            side[start] = Some(false);
+            let mut queue = VecDeque::from([start]);
+            while let Some(q) = queue.pop_front() {
+                let vertices = &vertices_of[q];
+                for (a, b) in [(0, 1), (0, 2), (1, 2)] {
+                    for &other in &sharing[&(vertices[a], vertices[b])] {
+                        if side[other].is_none() {
+                            side[other] = side[q].map(|odd| !odd);
+                            queue.push_back(other);
+                        } else {
+                            assert!(
+                                other == q || side[other] != side[q],
+                                "qubits {} and {} are neighbours on the same sublattice",
+                                q,
+                                other
+                            );
+                        }
+                    }
+                }
+            }
+        }
+        side.into_iter().map(|odd| odd.unwrap_or(false)).collect()
+    }
+
+    /// Restriction decoder: qubits to flip for the given flagged faces.
+    ///
+    /// Any two corrections of weight at most t = (d − 1)/2 differ by less than d qubits and
+    /// so by a stabilizer. A lift heavier than t is therefore replaced by a correction of
+    /// weight at most t whenever one exists.
+    fn restriction_decode(&self, flagged: &[bool]) -> Vec<usize> {
+        // Highlighted edges of the restricted lattices, keyed by their endpoints
+        let mut highlighted = BTreeSet::new();
+        for restricted in &self.restricted_lattices {
+            let defects: Vec<usize> = (0..self.faces.len())
+                .filter(|&f| flagged[f] && restricted.colors.contains(&self.face_colors[f]))
+                .collect();
+            for edge in restricted.graph.match_defects(&defects) {
+                let (a, b, _) = restricted.graph.edge(edge);
+                if !highlighted.remove(&(a.min(b), a.max(b))) {
+                    highlighted.insert((a.min(b), a.max(b)));
+                }
+            }
+        }
+
+        let lifted = Color::ALL
+            .iter()
+            .map(|&color| self.lift(color, &highlighted))
+            .filter(|flips| self.css.hx().mul_vec(flips) == flagged)
+            .map(|flips| (0..self.num_qubits()).filter(|&q| flips[q]).collect::<Vec<_>>())
+            .min_by_key(|qubits| qubits.len())
+            .unwrap_or_default();
+
+        let correctable = (self.distance - 1) / 2;
+        if lifted.len() <= correctable {
+            return lifted;
+        }
+        self.lightest_correction(flagged, correctable).unwrap_or(lifted)
+    }
+
+    /// The lightest set of at most `max_weight` qubits whose faces are exactly the flagged
+    /// ones, by iterative deepening
+    fn lightest_correction(&self, flagged: &[bool], max_weight: usize) -> Option<Vec<usize>> {
+        let mut unresolved = flagged.to_vec();
+        let mut chosen = Vec::new();
+        (1..=max_weight)
+            .find(|&weight| self.search_correction(&mut unresolved, weight, &mut chosen))
+            .map(|_| chosen)
+    }
+
+    /// Depth-first search for `budget` qubits more that clear the flagged faces. Some qubit
+    /// of the first flagged face must be flipped, so only those are branched on.
+    fn search_correction(&self, flagged: &mut [bool], budget: usize, chosen: &mut Vec<usize>) -> bool {
+        let Some(face) = flagged.iter().position(|&bit| bit) else {
+            return true;
+        };
+        // Each qubit clears at most three faces
+        if budget == 0 || flagged.iter().filter(|&&bit| bit).count() > 3 * budget {
+            return false;
+        }
+        for &q in &self.faces[face] {
+            self.flip_faces(q, flagged);
+            chosen.push(q);
+            if self.search_correction(flagged, budget - 1, chosen) {
+                return true;
+            }
+            chosen.pop();
+            self.flip_faces(q, flagged);
+        }
+        false
+    }
+
+    /// Toggles the faces that contain qubit `q`
+    fn flip_faces(&self, q: usize, flagged: &mut [bool]) {
+        for &vertex in self.qubit_vertices[q].iter().filter(|&&vertex| vertex < self.faces.len()) {
+            flagged[vertex] ^= true;
+        }
+    }
+
+    /// Around each vertex of one colour, picks the lightest set of qubits whose boundary
+    /// matches the highlighted edges leaving that vertex
+    fn lift(&self, color: Color, highlighted: &BTreeSet<(usize, usize)>) -> Vec<bool> {
+        let mut flips = vec![false; self.num_qubits()];
+        for star in self.stars.iter().filter(|star| star.color == color) {
+            let rhs: Vec<bool> = star
+                .neighbours
+                .iter()
+                .map(|&n| highlighted.contains(&(star.center.min(n), star.center.max(n))))
+                .collect();
+            if !rhs.iter().any(|&bit| bit) {
+                continue;
+            }
+            let Some(lift) = star.lift(&rhs) else {
+                continue;
+            };
+            for (i, &q) in star.qubits.iter().enumerate() {
+                flips[q] ^= lift[i];
+            }
+        }
+        flips
+    }
+}
+
+impl Star {
+    /// Lightest subset of the star's qubits with the given boundary around the centre
+    fn lift(&self, rhs: &[bool]) -> Option<Vec<bool>> {
+        // Kernels of stars on these lattices have at most a couple of vectors, so every
+        // solution is tried
+        let solution = self.incidence.solve(rhs)?;
+        let kernel_size = self.kernel.num_rows();
+
+        let weight = |v: &[bool]| v.iter().filter(|&&bit| bit).count();
+        let mut best = solution.clone();
+        for combination in 1..(1usize << kernel_size) {
+            let mut candidate = solution.clone();
+            for (k, row) in self.kernel.rows().iter().enumerate() {
+                if combination >> k & 1 == 1 {
+                    add_into(&mut candidate, row);
+                }
+            }
+            if weight(&candidate) < weight(&best) {
+                best = candidate;
+            }
+        }
+        Some(best)
+    }
+}
+
+/// A vertex of the dual lattice used to lay out the 4.8.8 triangle
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Vertex {
+    Face(i64, i64),
+    Boundary(Color),
+}
+
+impl CorrectionCode for ColorCode {
+    fn encode(&self, data: &Qubit) -> Vec<Qubit> {
+        self.css.encode(data)
+    }
+
+    fn syndrome_measurement(&self, encoded_qubits: &mut Vec<Qubit>) -> Vec<bool> {
+        // X-type face outcomes first, then Z-type, as for any CssCode
+        self.css.syndrome_measurement(encoded_qubits)
+    }
+
+    fn correct(&mut self, encoded_qubits: &mut Vec<Qubit>, syndromes: Vec<bool>) {
+        let start = Instant::now();
+
+        // Hx = Hz, so the same decoder handles Z errors (seen by X-type faces) and X errors
+        let (x_check_outcomes, z_check_outcomes) = syndromes.split_at(self.faces.len());
+        for q in self.restriction_decode(x_check_outcomes) {
+            Pauli::Z.apply(&mut encoded_qubits[q]);
+        }
+        for q in self.restriction_decode(z_check_outcomes) {
+            Pauli::X.apply(&mut encoded_qubits[q]);
+        }
+
+        let duration = start.elapsed();
+        self.correction_time.push(duration);
+    }
+
+    fn decode(&self, encoded_qubits: &mut Vec<Qubit>) -> Qubit {
+        self.css.decode(encoded_qubits)
+    }
+
+    fn get_average_correction_time(&self) -> f64 {
+        average_duration(&self.correction_time)
+    }
+
+    fn logical_errors(&self, encoded_qubits: &[Qubit]) -> Vec<Pauli> {
+        self.css.logical_errors(encoded_qubits)
+    }
+
+    fn num_qubits(&self) -> usize {
+        ColorCode::num_qubits(self)
+    }
+
+    fn num_logical_qubits(&self) -> usize {
+        1
+    }
+
+    fn stabilizer_generators(&self) -> Vec<PauliString> {
+        self.css.stabilizer_generators()
+    }
+
+    fn logical_operators(&self) -> Vec<(PauliString, PauliString)> {
+        vec![(self.logical_x().clone(), self.logical_z().clone())]
+    }
+
+    fn parameters(&self) -> CodeParameters {
+        CodeParameters {
+            n: ColorCode::num_qubits(self),
+            k: 1,
+            d: Some(self.distance),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gf2::dot;
+
+    /// Number of X errors of weight at most (d − 1)/2 that the decoder leaves as a logical
+    /// error. Hx = Hz, so Z errors are decoded identically.
+    fn uncorrected_low_weight_errors(code: &ColorCode) -> usize {
+        fn visit(code: &ColorCode, error: &mut Vec<usize>, start: usize, failures: &mut usize) {
+            if !error.is_empty() {
+                let mut residual = vec![false; code.num_qubits()];
+                for &q in error.iter() {
+                    residual[q] ^= true;
+                }
+                let flagged = code.as_css().hz().mul_vec(&residual);
+                for q in code.restriction_decode(&flagged) {
+                    residual[q] ^= true;
+                }
+                assert!(code.as_css().hz().mul_vec(&residual).iter().all(|&bit| !bit));
+                if dot(&residual, code.logical_z().z_bits()) {
+                    *failures += 1;
+                }
+            }
+            if error.len() == (code.distance() - 1) / 2 {
+                return;
+            }
+            for q in start..code.num_qubits() {
+                error.push(q);
+                visit(code, error, q + 1, failures);
+                error.pop();
+            }
+        }
+
+        let mut failures = 0;
+        visit(code, &mut Vec::new(), 0, &mut failures);
+        failures
+    }
+
+    #[test]
+    fn corrects_every_error_up_to_half_the_distance() {
+        for distance in [3, 5, 7] {
+            for code in [ColorCode::hexagonal(distance), ColorCode::square_octagon(distance)] {
+                assert_eq!(uncorrected_low_weight_errors(&code), 0, "{:?}, d = {}", code.lattice(), distance);
+            }
+        }
+    }
+
+    /// The image of the X-type operator on `support` under S and S† gates, written as
+    /// i^k · X_support · Z_support: each S turns X into Y = iXZ, and each S† into −Y
+    fn phase_image_quarter_turns(gates: &[CliffordGate], support: &[usize]) -> usize {
+        support
+            .iter()
+            .map(|&q| match gates[q] {
+                CliffordGate::S(_) => 1,
+                CliffordGate::SDag(_) => 3,
+                gate => panic!("unexpected {:?} in a transversal phase", gate),
+            })
+            .sum::<usize>()
+            % 4
+    }
+
+    #[test]
+    fn transversal_phase_keeps_stabilizer_signs_and_maps_x_to_y() {
+        for distance in [3, 5, 7] {
+            for code in [ColorCode::hexagonal(distance), ColorCode::square_octagon(distance)] {
+                let gates = code.transversal_phase();
+                let on_own_qubit = |(q, gate): (usize, &CliffordGate)| {
+                    matches!(gate, CliffordGate::S(p) | CliffordGate::SDag(p) if *p == q)
+                };
+                assert!(gates.iter().enumerate().all(on_own_qubit));
+                // X_f ↦ i^k X_f Z_f, the product of the face's X and Z stabilizers when k = 0;
+                // the Z stabilizers are untouched
+                for face in code.faces() {
+                    assert_eq!(phase_image_quarter_turns(&gates, face), 0, "{:?}", face);
+                }
+                // X̄ ↦ i^k X̄ Z_support, where Z on X̄'s support is a Z̄ in this self-dual code
+                let support = code.logical_x().support();
+                let mut z_on_support = code.logical_z().z_bits().to_vec();
+                for &q in &support {
+                    z_on_support[q] ^= true;
+                }
+                assert!(code.as_css().hz().row_space_contains(&z_on_support));
+                let quarter_turns = phase_image_quarter_turns(&gates, &support);
+                assert_eq!(quarter_turns, 1, "{:?}, d = {}", code.lattice(), distance);
+            }
+        }
+    }
+
+    #[test]
+    fn triangles_have_the_expected_parameters() {
+        for (distance, hexagonal_qubits, square_octagon_qubits) in [(3, 7, 7), (5, 19, 21), (7, 37, 43)] {
+            let hexagonal = ColorCode::hexagonal(distance);
+            let square_octagon = ColorCode::square_octagon(distance);
+            assert_eq!(hexagonal.num_qubits(), hexagonal_qubits);
+            assert_eq!(square_octagon.num_qubits(), square_octagon_qubits);
+            for code in [hexagonal, square_octagon] {
+                assert_eq!(code.as_css().num_logical_qubits(), 1);
+                let logicals = [code.logical_x().clone(), code.logical_z().clone()];
+                let found = crate::correction_codes::minimum_distance(&code.stabilizer_generators(), &logicals);
+                assert_eq!(found.distance, distance, "{:?}", code.lattice());
+            }
+        }
+    }
+}
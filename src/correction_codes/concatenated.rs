@@ -0,0 +1,250 @@
+use crate::correction_codes::{average_duration, CodeParameters, CorrectionCode};
+use crate::pauli::{Pauli, PauliString};
+use crate::qubit::Qubit;
+use std::time::{Duration, Instant};
+
+/// A code whose physical qubits are each protected by another code.
+///
+/// The input is encoded with `Outer`, and every qubit of that block is encoded again with
+/// `Inner` into its logical qubit 0. Physical qubits are laid out block by block, so inner
+/// block i holds outer qubit i. Nesting a `Concatenated` as the outer code adds a level.
+///
+/// `syndrome_measurement` reports the inner blocks' syndromes one block after another. The
+/// outer syndrome only makes sense once the blocks are corrected, so `correct` works level by
+/// level: it corrects each inner block, decodes the blocks to the outer qubits, measures and
+/// corrects the outer code on them, and re-encodes the blocks. Re-encoding drops residual
+/// errors that are stabilizers of the inner code, which do not change any logical state.
+pub struct Concatenated<Outer, Inner> {
+    outer: Outer,
+    inner: Inner,
+    inner_block_size: usize,
+    correction_time: Vec<Duration>,
+}
+
+impl<Outer: CorrectionCode, Inner: CorrectionCode> Concatenated<Outer, Inner> {
+    pub fn new(outer: Outer, inner: Inner) -> Self {
+        let inner_block_size = inner.encode(&Qubit::new()).len();
+        Self {
+            outer,
+            inner,
+            inner_block_size,
+            correction_time: Vec::new(),
+        }
+    }
+
+    pub fn outer(&self) -> &Outer {
+        &self.outer
+    }
+
+    pub fn inner(&self) -> &Inner {
+        &self.inner
+    }
+
+    /// Physical qubits in each inner block
+    pub fn inner_block_size(&self) -> usize {
+        self.inner_block_size
+    }
+
+    /// Decodes every inner block to the outer qubit it carries
+    fn decode_blocks(&self, encoded_qubits: &[Qubit]) -> Vec<Qubit> {
+        encoded_qubits
+            .chunks(self.inner_block_size)
+            .map(|block| self.inner.decode(&mut block.to_vec()))
+            .collect()
+    }
+
+    /// The physical operator that acts as `outer_operator` on the outer qubits: each outer
+    /// Pauli becomes the inner code's logical Pauli on that block
+    fn lift(&self, outer_operator: &PauliString, inner_logical: &(PauliString, PauliString)) -> PauliString {
+        let mut blocks = Vec::with_capacity(outer_operator.len());
+        for block in 0..outer_operator.len() {
+            let pauli = outer_operator.get(block);
+            let mut block_operator = PauliString::identity(self.inner_block_size);
+            if pauli.x_bit() {
+                block_operator.multiply(&inner_logical.0);
+            }
+            if pauli.z_bit() {
+                block_operator.multiply(&inner_logical.1);
+            }
+            blocks.push(block_operator);
+        }
+        concatenate_blocks(&blocks)
+    }
+}
+
+/// Operators on consecutive blocks joined into one operator on all of them
+fn concatenate_blocks(blocks: &[PauliString]) -> PauliString {
+    let x = blocks.iter().flat_map(|block| block.x_bits().iter().copied()).collect();
+    let z = blocks.iter().flat_map(|block| block.z_bits().iter().copied()).collect();
+    PauliString::from_bits(x, z)
+}
+
+impl<Outer: CorrectionCode, Inner: CorrectionCode> CorrectionCode for Concatenated<Outer, Inner> {
+    fn encode(&self, data: &Qubit) -> Vec<Qubit> {
+        self.outer
+            .encode(data)
+            .iter()
+            .flat_map(|outer_qubit| self.inner.encode(outer_qubit))
+            .collect()
+    }
+
+    /// The inner syndrome of each block in turn, `inner.syndrome_length()` bits per block and
+    /// nothing from the outer code, whose checks `correct` measures itself once the blocks
+    /// are corrected. It is therefore shorter than `stabilizer_generators`, which also lists
+    /// the lifted outer stabilizers, and is only meant for this code's own `correct`.
+    fn syndrome_measurement(&self, encoded_qubits: &mut Vec<Qubit>) -> Vec<bool> {
+        encoded_qubits
+            .chunks(self.inner_block_size)
+            .flat_map(|block| self.inner.syndrome_measurement(&mut block.to_vec()))
+            .collect()
+    }
+
+    fn correct(&mut self, encoded_qubits: &mut Vec<Qubit>, syndromes: Vec<bool>) {
+        let start = Instant::now();
+        let num_blocks = encoded_qubits.len() / self.inner_block_size;
+        let block_syndrome_length = syndromes.len() / num_blocks;
+
+        // Level 1: every inner block corrects itself from its own syndrome
+        for (index, block) in encoded_qubits.chunks_mut(self.inner_block_size).enumerate() {
+            let mut block_qubits = block.to_vec();
+            let offset = index * block_syndrome_length;
+            let block_syndromes = syndromes[offset..offset + block_syndrome_length].to_vec();
+            self.inner.correct(&mut block_qubits, block_syndromes);
+            block.clone_from_slice(&block_qubits);
+        }
+
+        // Level 2: the outer code sees the logical qubits of the corrected blocks
+        let mut outer_qubits = self.decode_blocks(encoded_qubits);
+        let outer_syndromes = self.outer.syndrome_measurement(&mut outer_qubits);
+        self.outer.correct(&mut outer_qubits, outer_syndromes);
+        *encoded_qubits = outer_qubits
+            .iter()
+            .flat_map(|outer_qubit| self.inner.encode(outer_qubit))
+            .collect();
+
+        let duration = start.elapsed();
+        self.correction_time.push(duration);
+    }
+
+    fn decode(&self, encoded_qubits: &mut Vec<Qubit>) -> Qubit {
+        let mut outer_qubits = self.decode_blocks(encoded_qubits);
+        self.outer.decode(&mut outer_qubits)
+    }
+
+    fn get_average_correction_time(&self) -> f64 {
+        average_duration(&self.correction_time)
+    }
+
+    /// The outer code's logical errors, given each block's logical error on its qubit 0;
+    /// empty unless both codes report logical errors
+    fn logical_errors(&self, encoded_qubits: &[Qubit]) -> Vec<Pauli> {
+        let mut outer_qubits = Vec::new();
+        for block in encoded_qubits.chunks(self.inner_block_size) {
+            let Some(&error) = self.inner.logical_errors(block).first() else {
+                return Vec::new();
+            };
+            let mut outer_qubit = Qubit::frame_reference();
+            error.apply(&mut outer_qubit);
+            outer_qubits.push(outer_qubit);
+        }
+        self.outer.logical_errors(&outer_qubits)
+    }
+
+    fn num_qubits(&self) -> usize {
+        self.outer.num_qubits() * self.inner_block_size
+    }
+
+    fn num_logical_qubits(&self) -> usize {
+        self.outer.num_logical_qubits()
+    }
+
+    /// Every inner stabilizer on every block, then the outer stabilizers lifted to the
+    /// blocks' logical qubits
+    fn stabilizer_generators(&self) -> Vec<PauliString> {
+        let num_blocks = self.outer.num_qubits();
+        let identity = PauliString::identity(self.inner_block_size);
+        let mut generators = Vec::new();
+        for block in 0..num_blocks {
+            for inner_generator in self.inner.stabilizer_generators() {
+                let mut blocks = vec![identity.clone(); num_blocks];
+                blocks[block] = inner_generator;
+                generators.push(concatenate_blocks(&blocks));
+            }
+        }
+        let inner_logical = &self.inner.logical_operators()[0];
+        for outer_generator in self.outer.stabilizer_generators() {
+            generators.push(self.lift(&outer_generator, inner_logical));
+        }
+        generators
+    }
+
+    fn logical_operators(&self) -> Vec<(PauliString, PauliString)> {
+        let inner_logical = &self.inner.logical_operators()[0];
+        self.outer
+            .logical_operators()
+            .iter()
+            .map(|(x, z)| (self.lift(x, inner_logical), self.lift(z, inner_logical)))
+            .collect()
+    }
+
+    /// The inner syndromes of all blocks, as `syndrome_measurement` reports them
+    fn syndrome_length(&self) -> usize {
+        self.outer.num_qubits() * self.inner.syndrome_length()
+    }
+
+    /// The distance is d_outer·d_inner when both are known: a logical error has to flip at
+    /// least d_outer blocks, each by an operator of weight at least d_inner. This is a lower
+    /// bound, which codes with different X and Z distances can exceed.
+    fn parameters(&self) -> CodeParameters {
+        let (outer, inner) = (self.outer.parameters(), self.inner.parameters());
+        CodeParameters {
+            n: self.num_qubits(),
+            k: outer.k,
+            d: outer.d.zip(inner.d).map(|(outer, inner)| outer * inner),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::correction_codes::{BitFlipCode, CssCode, PhaseFlipCode};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    /// Encodes, applies `error`, corrects and reads off the logical error
+    fn residual_logical_error(code: &mut impl CorrectionCode, error: &PauliString) -> Vec<Pauli> {
+        let mut encoded = code.encode(&Qubit::new());
+        error.apply_to(&mut encoded);
+        let syndromes = code.syndrome_measurement(&mut encoded);
+        code.correct(&mut encoded, syndromes);
+        code.logical_errors(&encoded)
+    }
+
+    #[test]
+    fn shor_code_corrects_every_single_qubit_error() {
+        let mut shor = Concatenated::new(PhaseFlipCode::new(), BitFlipCode::new());
+        assert_eq!(shor.parameters(), CodeParameters { n: 9, k: 1, d: None });
+        for qubit in 0..9 {
+            for pauli in [Pauli::X, Pauli::Y, Pauli::Z] {
+                let error = PauliString::from_support(9, &[qubit], pauli);
+                assert_eq!(residual_logical_error(&mut shor, &error), [Pauli::I], "{:?} on {}", pauli, qubit);
+            }
+        }
+    }
+
+    #[test]
+    fn steane_in_steane_corrects_errors_of_weight_three() {
+        let mut code = Concatenated::new(CssCode::steane(), CssCode::steane());
+        assert_eq!(code.parameters(), CodeParameters { n: 49, k: 1, d: Some(9) });
+        let mut rng = StdRng::seed_from_u64(33);
+        for _ in 0..3000 {
+            let mut error = PauliString::identity(49);
+            for _ in 0..rng.gen_range(1..=3) {
+                let pauli = [Pauli::X, Pauli::Y, Pauli::Z][rng.gen_range(0..3)];
+                error.set(rng.gen_range(0..49), pauli);
+            }
+            assert_eq!(residual_logical_error(&mut code, &error), [Pauli::I], "{}", error);
+        }
+    }
+}
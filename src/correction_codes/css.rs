@@ -0,0 +1,326 @@
+use crate::correction_codes::{
+    average_duration, encode_frame, logical_pauli, CodeConstructionError, CodeParameters, CorrectionCode,
+};
+use crate::gates::DiagonalGate;
+use crate::gf2::{add_into, dot, BinaryMatrix, EchelonBasis};
+use crate::pauli::{Pauli, PauliString};
+use crate::qubit::Qubit;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// A Calderbank–Shor–Steane code described entirely by two classical parity-check matrices.
+///
+/// Rows of `hx` are X-type stabilizers and rows of `hz` are Z-type stabilizers. Logical
+/// operators are derived by Gaussian elimination, so a new CSS code is just a pair of
+/// matrices. Physical qubits are tracked as a Pauli frame, as in `ToricCode`.
+///
+/// `correct` looks the X and Z syndromes up independently and undoes any single-qubit error.
+pub struct CssCode {
+    hx: BinaryMatrix,
+    hz: BinaryMatrix,
+    logical_x: Vec<PauliString>,
+    logical_z: Vec<PauliString>,
+    /// Known distance, set by constructors that can derive it
+    distance: Option<usize>,
+    x_corrections: HashMap<Vec<bool>, usize>,
+    z_corrections: HashMap<Vec<bool>, usize>,
+    correction_time: Vec<Duration>,
+}
+
+impl CssCode {
+    /// Builds the code after checking that Hx·Hzᵀ = 0 over GF(2)
+    pub fn from_parity_checks(hx: BinaryMatrix, hz: BinaryMatrix) -> Result<Self, CodeConstructionError> {
+        if hx.num_cols() != hz.num_cols() {
+            return Err(CodeConstructionError::DimensionMismatch {
+                x_qubits: hx.num_cols(),
+                z_qubits: hz.num_cols(),
+            });
+        }
+        for (x_check, x_row) in hx.rows().iter().enumerate() {
+            for (z_check, z_row) in hz.rows().iter().enumerate() {
+                if dot(x_row, z_row) {
+                    return Err(CodeConstructionError::NonCommutingChecks { x_check, z_check });
+                }
+            }
+        }
+
+        let n = hx.num_cols();
+        let x_vectors = logical_basis(&hz, &hx);
+        let mut z_vectors = logical_basis(&hx, &hz);
+        if x_vectors.is_empty() {
+            return Err(CodeConstructionError::NoLogicalQubits);
+        }
+
+        // Pair the representatives up so that X̄ᵢ anticommutes with Z̄ⱼ only when i = j
+        let lx = BinaryMatrix::from_rows(n, x_vectors);
+        let lz = BinaryMatrix::from_rows(n, z_vectors);
+        let overlaps = lx.multiply(&lz.transpose());
+        let change_of_basis = overlaps
+            .inverse()
+            .expect("logical X and Z representatives must pair up symplectically")
+            .transpose();
+        z_vectors = change_of_basis.multiply(&lz).rows().to_vec();
+
+        let logical_x = lx
+            .rows()
+            .iter()
+            .map(|row| PauliString::from_bits(row.clone(), vec![false; n]))
+            .collect();
+        let logical_z = z_vectors
+            .into_iter()
+            .map(|row| PauliString::from_bits(vec![false; n], row))
+            .collect();
+
+        Ok(Self {
+            x_corrections: single_qubit_lookup(&hz),
+            z_corrections: single_qubit_lookup(&hx),
+            hx,
+            hz,
+            logical_x,
+            logical_z,
+            distance: None,
+            correction_time: Vec::new(),
+        })
+    }
+
+    /// The [[7,1,3]] Steane code, built from two copies of the [7,4,3] Hamming code
+    pub fn steane() -> Self {
+        let hamming = BinaryMatrix::from_bits(&[
+            &[0, 0, 0, 1, 1, 1, 1],
+            &[0, 1, 1, 0, 0, 1, 1],
+            &[1, 0, 1, 0, 1, 0, 1],
+        ]);
+        Self::from_parity_checks(hamming.clone(), hamming)
+            .expect("the Steane code is a valid CSS code")
+            .with_distance(3)
+    }
+
+    /// Records a distance known from the construction
+    pub(crate) fn with_distance(mut self, distance: usize) -> Self {
+        self.distance = Some(distance);
+        self
+    }
+
+    pub fn num_qubits(&self) -> usize {
+        self.hx.num_cols()
+    }
+
+    pub fn num_logical_qubits(&self) -> usize {
+        self.logical_x.len()
+    }
+
+    /// [[n, k, d]], with the distance only when the constructor knows it
+    pub fn parameters(&self) -> CodeParameters {
+        CodeParameters {
+            n: self.num_qubits(),
+            k: self.num_logical_qubits(),
+            d: self.distance,
+        }
+    }
+
+    /// X-type checks, one row per stabilizer
+    pub fn hx(&self) -> &BinaryMatrix {
+        &self.hx
+    }
+
+    /// Z-type checks, one row per stabilizer
+    pub fn hz(&self) -> &BinaryMatrix {
+        &self.hz
+    }
+
+    pub fn logical_x(&self, logical_qubit: usize) -> &PauliString {
+        &self.logical_x[logical_qubit]
+    }
+
+    pub fn logical_z(&self, logical_qubit: usize) -> &PauliString {
+        &self.logical_z[logical_qubit]
+    }
+
+    /// Logical action of a diagonal circuit on a code with one logical qubit: `Some(k)` when
+    /// it maps |0̄⟩ and |1̄⟩ to themselves with relative phase ω^k, up to a global phase, and
+    /// `None` when it takes the code space elsewhere.
+    ///
+    /// |0̄⟩ is the uniform superposition over the span of the X checks and |1̄⟩ that span
+    /// shifted by X̄, so all 2^rank(Hx) codewords are visited; this is meant for small codes.
+    pub fn logical_diagonal_phase(&self, circuit: &[DiagonalGate]) -> Option<u8> {
+        assert_eq!(self.num_logical_qubits(), 1, "only codes with one logical qubit are supported");
+        let mut checks = self.hx.clone();
+        let rank = checks.row_reduce().len();
+
+        let mut zero_word = vec![false; self.num_qubits()];
+        let mut one_word = self.logical_x[0].x_bits().to_vec();
+        let zero_phase = DiagonalGate::phase_on(circuit, &zero_word);
+        let one_phase = DiagonalGate::phase_on(circuit, &one_word);
+
+        // Gray-code walk over the span, adding one check per step
+        for step in 1..1usize << rank {
+            let check = checks.row(step.trailing_zeros() as usize);
+            add_into(&mut zero_word, check);
+            add_into(&mut one_word, check);
+            if DiagonalGate::phase_on(circuit, &zero_word) != zero_phase
+                || DiagonalGate::phase_on(circuit, &one_word) != one_phase
+            {
+                return None;
+            }
+        }
+
+        Some((8 + one_phase - zero_phase) % 8)
+    }
+}
+
+impl CorrectionCode for CssCode {
+    fn encode(&self, data: &Qubit) -> Vec<Qubit> {
+        encode_frame(self.num_qubits(), data, &self.logical_x[0], &self.logical_z[0])
+    }
+
+    fn syndrome_measurement(&self, encoded_qubits: &mut Vec<Qubit>) -> Vec<bool> {
+        let frame = PauliString::from_frame(encoded_qubits);
+
+        // X checks see Z errors and come first, followed by the Z checks that see X errors
+        let mut syndromes = self.hx.mul_vec(frame.z_bits());
+        syndromes.extend(self.hz.mul_vec(frame.x_bits()));
+        syndromes
+    }
+
+    fn correct(&mut self, encoded_qubits: &mut Vec<Qubit>, syndromes: Vec<bool>) {
+        let start = Instant::now();
+
+        let (x_check_outcomes, z_check_outcomes) = syndromes.split_at(self.hx.num_rows());
+        if let Some(&qubit) = self.z_corrections.get(x_check_outcomes) {
+            Pauli::Z.apply(&mut encoded_qubits[qubit]);
+        }
+        if let Some(&qubit) = self.x_corrections.get(z_check_outcomes) {
+            Pauli::X.apply(&mut encoded_qubits[qubit]);
+        }
+
+        let duration = start.elapsed();
+        self.correction_time.push(duration);
+    }
+
+    fn decode(&self, encoded_qubits: &mut Vec<Qubit>) -> Qubit {
+        let frame = PauliString::from_frame(encoded_qubits);
+
+        let mut decoded_qubit = Qubit::frame_reference();
+        logical_pauli(&frame, &self.logical_x[0], &self.logical_z[0]).apply(&mut decoded_qubit);
+
+        decoded_qubit
+    }
+
+    fn get_average_correction_time(&self) -> f64 {
+        average_duration(&self.correction_time)
+    }
+
+    fn logical_errors(&self, encoded_qubits: &[Qubit]) -> Vec<Pauli> {
+        let frame = PauliString::from_frame(encoded_qubits);
+        self.logical_x
+            .iter()
+            .zip(&self.logical_z)
+            .map(|(x, z)| logical_pauli(&frame, x, z))
+            .collect()
+    }
+
+    fn num_qubits(&self) -> usize {
+        CssCode::num_qubits(self)
+    }
+
+    fn num_logical_qubits(&self) -> usize {
+        CssCode::num_logical_qubits(self)
+    }
+
+    /// The X checks as X strings, then the Z checks as Z strings
+    fn stabilizer_generators(&self) -> Vec<PauliString> {
+        let n = CssCode::num_qubits(self);
+        let x_checks = self.hx.rows().iter().map(|row| PauliString::from_bits(row.clone(), vec![false; n]));
+        let z_checks = self.hz.rows().iter().map(|row| PauliString::from_bits(vec![false; n], row.clone()));
+        x_checks.chain(z_checks).collect()
+    }
+
+    fn logical_operators(&self) -> Vec<(PauliString, PauliString)> {
+        self.logical_x.iter().cloned().zip(self.logical_z.iter().cloned()).collect()
+    }
+
+    fn parameters(&self) -> CodeParameters {
+        CssCode::parameters(self)
+    }
+}
+
+/// Vectors of ker(`commuting_with`) that are independent modulo the row space of `modulo`.
+/// For X-type logicals pass (Hz, Hx); for Z-type logicals pass (Hx, Hz).
+fn logical_basis(commuting_with: &BinaryMatrix, modulo: &BinaryMatrix) -> Vec<Vec<bool>> {
+    let mut span = EchelonBasis::new();
+    for row in modulo.rows() {
+        span.insert(row);
+    }
+
+    commuting_with
+        .kernel()
+        .rows()
+        .iter()
+        .filter(|vector| span.insert(vector))
+        .cloned()
+        .collect()
+}
+
+/// Maps the syndrome of a single flipped qubit to that qubit, keeping the first qubit
+/// when several share a syndrome
+fn single_qubit_lookup(checks: &BinaryMatrix) -> HashMap<Vec<bool>, usize> {
+    let mut lookup = HashMap::new();
+    for qubit in 0..checks.num_cols() {
+        let syndrome = checks.column(qubit);
+        if syndrome.iter().any(|&bit| bit) {
+            lookup.entry(syndrome).or_insert(qubit);
+        }
+    }
+    lookup
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every logical commutes with every stabilizer, and X̄ᵢ anticommutes with Z̄ⱼ exactly
+    /// when i = j
+    fn assert_symplectic_logicals(code: &CssCode) {
+        let stabilizers = code.stabilizer_generators();
+        let logicals = code.logical_operators();
+        for (i, (x, z)) in logicals.iter().enumerate() {
+            assert!(stabilizers.iter().all(|s| s.commutes_with(x) && s.commutes_with(z)));
+            for (j, (other_x, other_z)) in logicals.iter().enumerate() {
+                assert_eq!(x.commutes_with(other_z), i != j, "X̄{} and Z̄{}", i, j);
+                assert!(x.commutes_with(other_x) && z.commutes_with(other_z));
+            }
+        }
+    }
+
+    #[test]
+    fn steane_code_has_one_paired_logical_qubit() {
+        let code = CssCode::steane();
+        assert_eq!(code.parameters(), CodeParameters { n: 7, k: 1, d: Some(3) });
+        assert_symplectic_logicals(&code);
+    }
+
+    #[test]
+    fn hypergraph_products_have_paired_logicals() {
+        let repetition = BinaryMatrix::from_bits(&[&[1, 1, 0], &[0, 1, 1]]);
+        let hamming = BinaryMatrix::from_bits(&[
+            &[0, 0, 0, 1, 1, 1, 1],
+            &[0, 1, 1, 0, 0, 1, 1],
+            &[1, 0, 1, 0, 1, 0, 1],
+        ]);
+        for (classical, k) in [(repetition, 1), (hamming, 16)] {
+            let code = CssCode::hypergraph_product(&classical, &classical).expect("products are CSS codes");
+            assert_eq!(code.num_logical_qubits(), k);
+            assert_symplectic_logicals(&code);
+        }
+    }
+
+    #[test]
+    fn rejects_checks_that_do_not_commute() {
+        let hx = BinaryMatrix::from_bits(&[&[1, 1, 0]]);
+        let hz = BinaryMatrix::from_bits(&[&[1, 1, 0], &[0, 1, 1]]);
+        assert_eq!(
+            CssCode::from_parity_checks(hx, hz).err(),
+            Some(CodeConstructionError::NonCommutingChecks { x_check: 0, z_check: 1 })
+        );
+    }
+}
@@ -0,0 +1,339 @@
+use super::stabilizer::symplectic_vector;
+use crate::correction_codes::{CssCode, StabilizerCode, SubsystemCode};
+use crate::gf2::{BinaryMatrix, EchelonBasis};
+use crate::pauli::PauliString;
+use rand::seq::SliceRandom;
+
+/// Largest number of independent operators whose span is enumerated exhaustively
+const MAX_EXHAUSTIVE_DIMENSION: usize = 24;
+
+/// Random information sets tried when the span is too large to enumerate
+const INFORMATION_SET_ITERATIONS: usize = 500;
+
+/// The outcome of a distance search: the lightest nontrivial logical operator found
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CodeDistance {
+    pub distance: usize,
+    /// A nontrivial logical operator of weight `distance`
+    pub witness: PauliString,
+    /// Whether every logical operator was examined. A randomized search only gives an upper
+    /// bound, which is tight with high probability after enough iterations.
+    pub exact: bool,
+}
+
+/// The minimum weight of an operator in the span of `stabilizers` and `logicals` that is not
+/// in the span of `stabilizers` alone, with an operator achieving it.
+///
+/// For a stabilizer code, pass the generators and every X̄ and Z̄ to get the code distance.
+/// Passing gauge generators instead gives the dressed distance of a subsystem code, and
+/// passing only X-type checks and X̄s gives the X distance of a CSS code.
+///
+/// The span is enumerated when it has at most 2^24 elements; larger codes fall back to
+/// `information_set_distance`.
+pub fn minimum_distance(stabilizers: &[PauliString], logicals: &[PauliString]) -> CodeDistance {
+    let search = DistanceSearch::new(stabilizers, logicals);
+    if search.basis.len() <= MAX_EXHAUSTIVE_DIMENSION {
+        search.exhaustive()
+    } else {
+        search.information_sets(INFORMATION_SET_ITERATIONS)
+    }
+}
+
+/// `minimum_distance` by walking every element of the span, which takes 2^m steps for m
+/// independent operators. Panics if m is 64 or more.
+pub fn exhaustive_distance(stabilizers: &[PauliString], logicals: &[PauliString]) -> CodeDistance {
+    DistanceSearch::new(stabilizers, logicals).exhaustive()
+}
+
+/// An upper bound on `minimum_distance` from random information sets.
+///
+/// Each iteration shuffles the qubits and brings the span to reduced row echelon form with
+/// the qubits in that order. Every row then has a single pivot among the leading qubits and
+/// is often light; rows that are nontrivial logicals are candidates. This is the randomized
+/// search of the QDistRnd package: a lightest logical is found whenever its support misses
+/// all the pivots but one, which a random order does with fair probability.
+pub fn information_set_distance(
+    stabilizers: &[PauliString],
+    logicals: &[PauliString],
+    iterations: usize,
+) -> CodeDistance {
+    DistanceSearch::new(stabilizers, logicals).information_sets(iterations)
+}
+
+/// A basis of the span to search: independent stabilizers first, then the logicals
+struct DistanceSearch {
+    num_qubits: usize,
+    basis: Vec<PauliString>,
+    num_stabilizers: usize,
+}
+
+impl DistanceSearch {
+    fn new(stabilizers: &[PauliString], logicals: &[PauliString]) -> Self {
+        assert!(!logicals.is_empty(), "a code without logical operators has no distance");
+        let num_qubits = logicals[0].len();
+
+        let mut span = EchelonBasis::new();
+        let mut basis: Vec<PauliString> = stabilizers
+            .iter()
+            .filter(|stabilizer| span.insert(&symplectic_vector(stabilizer)))
+            .cloned()
+            .collect();
+        let num_stabilizers = basis.len();
+        for logical in logicals {
+            assert!(
+                span.insert(&symplectic_vector(logical)),
+                "logical operators must be independent of each other and of the stabilizers"
+            );
+            basis.push(logical.clone());
+        }
+        Self {
+            num_qubits,
+            basis,
+            num_stabilizers,
+        }
+    }
+
+    /// Walks the span in Gray-code order, one basis operator added or removed per step
+    fn exhaustive(&self) -> CodeDistance {
+        assert!(self.basis.len() < 64, "the span is too large to enumerate");
+        let packed: Vec<PackedPauli> = self.basis.iter().map(PackedPauli::new).collect();
+        let mut current = PackedPauli::identity(self.num_qubits);
+        let mut coefficients = 0u64;
+        let mut best: Option<(usize, u64)> = None;
+        for step in 1..1u64 << self.basis.len() {
+            let index = step.trailing_zeros() as usize;
+            current.multiply(&packed[index]);
+            coefficients ^= 1 << index;
+            if coefficients >> self.num_stabilizers == 0 {
+                continue;
+            }
+            let weight = current.weight();
+            if best.is_none_or(|(lightest, _)| weight < lightest) {
+                best = Some((weight, coefficients));
+            }
+        }
+
+        let (distance, coefficients) = best.expect("the logicals are nontrivial");
+        let mut witness = PauliString::identity(self.num_qubits);
+        for (index, operator) in self.basis.iter().enumerate() {
+            if coefficients >> index & 1 == 1 {
+                witness.multiply(operator);
+            }
+        }
+        CodeDistance {
+            distance,
+            witness,
+            exact: true,
+        }
+    }
+
+    fn information_sets(&self, iterations: usize) -> CodeDistance {
+        let n = self.num_qubits;
+        let num_logicals = self.basis.len() - self.num_stabilizers;
+        let mut order: Vec<usize> = (0..n).collect();
+        let mut rng = rand::thread_rng();
+
+        // Any logical in the basis is a first candidate
+        let (mut distance, mut witness) = self.basis[self.num_stabilizers..]
+            .iter()
+            .map(|logical| (logical.weight(), logical.clone()))
+            .min_by_key(|(weight, _)| *weight)
+            .expect("there is at least one logical");
+
+        for _ in 0..iterations {
+            order.shuffle(&mut rng);
+
+            // Columns are the X and Z bits of the qubits in the shuffled order, followed by the
+            // coefficients of the logicals, which tell the nontrivial rows apart
+            let rows = self
+                .basis
+                .iter()
+                .enumerate()
+                .map(|(index, operator)| {
+                    let mut row: Vec<bool> =
+                        order.iter().flat_map(|&q| [operator.x_bits()[q], operator.z_bits()[q]]).collect();
+                    row.extend((0..num_logicals).map(|l| index == self.num_stabilizers + l));
+                    row
+                })
+                .collect();
+            let mut matrix = BinaryMatrix::from_rows(2 * n + num_logicals, rows);
+            matrix.row_reduce();
+
+            for row in matrix.rows() {
+                if !row[2 * n..].contains(&true) {
+                    continue;
+                }
+                let weight = row[..2 * n].chunks(2).filter(|pair| pair[0] || pair[1]).count();
+                if weight < distance {
+                    let mut x = vec![false; n];
+                    let mut z = vec![false; n];
+                    for (position, &q) in order.iter().enumerate() {
+                        x[q] = row[2 * position];
+                        z[q] = row[2 * position + 1];
+                    }
+                    distance = weight;
+                    witness = PauliString::from_bits(x, z);
+                }
+            }
+        }
+        CodeDistance {
+            distance,
+            witness,
+            exact: false,
+        }
+    }
+}
+
+/// A Pauli string packed 64 qubits to a word, for fast products and weights
+#[derive(Clone)]
+pub(super) struct PackedPauli {
+    x: Vec<u64>,
+    z: Vec<u64>,
+}
+
+impl PackedPauli {
+    pub(super) fn identity(num_qubits: usize) -> Self {
+        let words = num_qubits.div_ceil(64);
+        Self {
+            x: vec![0; words],
+            z: vec![0; words],
+        }
+    }
+
+    pub(super) fn new(operator: &PauliString) -> Self {
+        let mut packed = Self::identity(operator.len());
+        for q in 0..operator.len() {
+            packed.x[q / 64] |= u64::from(operator.x_bits()[q]) << (q % 64);
+            packed.z[q / 64] |= u64::from(operator.z_bits()[q]) << (q % 64);
+        }
+        packed
+    }
+
+    pub(super) fn multiply(&mut self, other: &PackedPauli) {
+        for (word, &other_word) in self.x.iter_mut().zip(&other.x) {
+            *word ^= other_word;
+        }
+        for (word, &other_word) in self.z.iter_mut().zip(&other.z) {
+            *word ^= other_word;
+        }
+    }
+
+    pub(super) fn weight(&self) -> usize {
+        self.x.iter().zip(&self.z).map(|(x, z)| (x | z).count_ones() as usize).sum()
+    }
+
+    /// The numbers of X, Y and Z factors
+    pub(super) fn pauli_counts(&self) -> (usize, usize, usize) {
+        let count = |bits: fn(u64, u64) -> u64| {
+            self.x.iter().zip(&self.z).map(|(&x, &z)| bits(x, z).count_ones() as usize).sum()
+        };
+        (count(|x, z| x & !z), count(|x, z| x & z), count(|x, z| !x & z))
+    }
+}
+
+impl StabilizerCode {
+    /// The minimum weight of a nontrivial logical operator, with one such operator
+    pub fn distance(&self) -> CodeDistance {
+        let logicals: Vec<PauliString> = (0..self.num_logical_qubits())
+            .flat_map(|l| [self.logical_x(l).clone(), self.logical_z(l).clone()])
+            .collect();
+        minimum_distance(self.generators(), &logicals)
+    }
+}
+
+impl CssCode {
+    /// The minimum weight of a nontrivial logical operator, with one such operator.
+    ///
+    /// A lightest logical can always be taken to be X-type or Z-type, so the X and Z
+    /// distances are searched separately over n bits each instead of over all 2n.
+    pub fn distance(&self) -> CodeDistance {
+        let n = self.num_qubits();
+        let k = self.num_logical_qubits();
+        let x_checks = checks_as_pauli_strings(self.hx(), |row| PauliString::from_bits(row, vec![false; n]));
+        let z_checks = checks_as_pauli_strings(self.hz(), |row| PauliString::from_bits(vec![false; n], row));
+        let logical_x: Vec<PauliString> = (0..k).map(|l| self.logical_x(l).clone()).collect();
+        let logical_z: Vec<PauliString> = (0..k).map(|l| self.logical_z(l).clone()).collect();
+
+        let x_distance = minimum_distance(&x_checks, &logical_x);
+        let z_distance = minimum_distance(&z_checks, &logical_z);
+        let exact = x_distance.exact && z_distance.exact;
+        let lightest = if z_distance.distance < x_distance.distance { z_distance } else { x_distance };
+        CodeDistance { exact, ..lightest }
+    }
+}
+
+impl SubsystemCode {
+    /// The dressed distance: the minimum weight of a bare logical times any gauge operator,
+    /// with one such operator
+    pub fn distance(&self) -> CodeDistance {
+        let logicals: Vec<PauliString> = (0..self.num_logical_qubits())
+            .flat_map(|l| [self.logical_x(l).clone(), self.logical_z(l).clone()])
+            .collect();
+        minimum_distance(self.gauge_generators(), &logicals)
+    }
+}
+
+fn checks_as_pauli_strings(
+    checks: &BinaryMatrix,
+    to_pauli: impl Fn(Vec<bool>) -> PauliString,
+) -> Vec<PauliString> {
+    checks.rows().iter().map(|row| to_pauli(row.clone())).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::correction_codes::{CorrectionCode, ToricCode};
+    use crate::pauli::Pauli;
+
+    fn logicals(code: &dyn CorrectionCode) -> Vec<PauliString> {
+        code.logical_operators().into_iter().flat_map(|(x, z)| [x, z]).collect()
+    }
+
+    #[test]
+    fn five_qubit_and_steane_codes_have_distance_three() {
+        let five_qubit = StabilizerCode::five_qubit();
+        let steane = CssCode::steane();
+        for code in [&five_qubit as &dyn CorrectionCode, &steane] {
+            let (stabilizers, logicals) = (code.stabilizer_generators(), logicals(code));
+            let found = minimum_distance(&stabilizers, &logicals);
+            assert_eq!((found.distance, found.exact), (3, true));
+            assert_eq!(found.witness.weight(), 3);
+            assert!(stabilizers.iter().all(|s| s.commutes_with(&found.witness)));
+            assert_eq!(exhaustive_distance(&stabilizers, &logicals), found);
+            assert_eq!(information_set_distance(&stabilizers, &logicals, 100).distance, 3);
+        }
+        assert_eq!(five_qubit.distance().distance, 3);
+        assert_eq!(steane.distance().distance, 3);
+    }
+
+    #[test]
+    fn toric_code_distance_is_the_lattice_size() {
+        let code = ToricCode::new(4);
+        let (stabilizers, logicals) = (code.stabilizer_generators(), logicals(&code));
+
+        // The 34 operators are too many to enumerate together, but a lightest logical can be
+        // taken X-type or Z-type, and each half is small
+        let of_type = |operators: &[PauliString], pauli: Pauli| -> Vec<PauliString> {
+            operators.iter().filter(|o| o.support().iter().all(|&q| o.get(q) == pauli)).cloned().collect()
+        };
+        for pauli in [Pauli::X, Pauli::Z] {
+            let found = exhaustive_distance(&of_type(&stabilizers, pauli), &of_type(&logicals, pauli));
+            assert_eq!(found.distance, 4, "{:?}", pauli);
+        }
+
+        let found = minimum_distance(&stabilizers, &logicals);
+        assert!(!found.exact);
+        assert_eq!(found.distance, 4);
+    }
+
+    #[test]
+    fn information_sets_bound_the_gross_code_distance() {
+        let code = CssCode::gross();
+        let found = code.distance();
+        assert!(!found.exact);
+        assert!(found.distance <= 12, "found a logical of weight {}", found.distance);
+        assert_eq!(found.witness.weight(), found.distance);
+        assert!(code.stabilizer_generators().iter().all(|s| s.commutes_with(&found.witness)));
+    }
+}
@@ -0,0 +1,320 @@
+use super::distance::PackedPauli;
+use super::stabilizer::symplectic_vector;
+use crate::correction_codes::CorrectionCode;
+use crate::error_models::PauliChannel;
+use crate::gf2::{BinaryMatrix, EchelonBasis};
+use crate::pauli::{Pauli, PauliString};
+use crate::qubit::Qubit;
+
+/// Largest number of independent operators whose span is enumerated, and largest base-2
+/// logarithm of the number of errors counted for a decoder
+const MAX_ENUMERATED_DIMENSION: usize = 30;
+
+/// The weight distributions of a stabilizer group S and of its normalizer N(S), the Paulis
+/// commuting with all of S. Entry w of each counts the elements of weight w, up to phase.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WeightEnumerators {
+    pub stabilizer: Vec<u64>,
+    pub normalizer: Vec<u64>,
+}
+
+impl WeightEnumerators {
+    /// The enumerators of the group generated by `stabilizers`, which may be redundant.
+    ///
+    /// S is walked element by element and N(S) follows from the quantum MacWilliams identity
+    /// B(x, y) = A(x + 3y, x − y) / |S|, where A and B are the homogeneous enumerators
+    /// Σ x^(n−w) y^w of S and N(S).
+    pub fn new(stabilizers: &[PauliString]) -> Self {
+        let n = stabilizers[0].len();
+        // The terms of the MacWilliams sum add up in absolute value to at most |S| 4^n, and
+        // |S| is at most 2^30 to be walked at all, so the sum stays within 2^126 for n ≤ 48
+        assert!(n <= 48, "enumerators are only computed for up to 48 qubits");
+        let basis = independent_operators(stabilizers);
+
+        let mut stabilizer = vec![0; n + 1];
+        walk_span(PackedPauli::identity(n), &basis, |element| stabilizer[element.weight()] += 1);
+        let normalizer = macwilliams_transform(&stabilizer, basis.len());
+        Self { stabilizer, normalizer }
+    }
+
+    pub fn num_qubits(&self) -> usize {
+        self.stabilizer.len() - 1
+    }
+
+    /// The lowest weight at which N(S) outnumbers S. This is the distance of a code without
+    /// gauge qubits; None when N(S) = S and nothing is encoded.
+    pub fn distance(&self) -> Option<usize> {
+        self.stabilizer.iter().zip(&self.normalizer).position(|(a, b)| b > a)
+    }
+}
+
+/// The exact failure probability of a decoder under i.i.d. Pauli noise.
+///
+/// Every Pauli error on the block is classified once, by whether the decoder brings it back
+/// to a stabilizer or gauge operator, and the failing errors are counted by their numbers
+/// of X, Y and Z factors. An error with a X, b Y and c Z factors occurs with
+/// probability p_x^a p_y^b p_z^c (1 − p)^(n−a−b−c), so the counts give the failure
+/// probability of any Pauli channel as a polynomial.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LogicalErrorPolynomial {
+    num_qubits: usize,
+    /// Failing errors with a X, b Y and c Z factors, at index (a (n + 1) + b) (n + 1) + c
+    failures: Vec<u64>,
+}
+
+impl LogicalErrorPolynomial {
+    /// Counts the errors the decoder of `code` fails on: those it leaves with a logical
+    /// residual and those it does not return to the code space at all.
+    ///
+    /// The decoder's correction for each syndrome is found by running `correct` once on an
+    /// error with that syndrome, and is assumed to be the same for every error sharing it up
+    /// to stabilizers, or gauge operators for a subsystem code. The errors it then corrects
+    /// are that correction times the group of operators commuting with every stabilizer and
+    /// logical, and each such coset is walked element by element. This takes 4^(n−k) steps
+    /// for a stabilizer code, so it is meant for small codes.
+    pub fn new<C: CorrectionCode + ?Sized>(code: &mut C) -> Self {
+        let n = code.num_qubits();
+        let stabilizers = code.stabilizer_generators();
+        let logicals: Vec<PauliString> =
+            code.logical_operators().into_iter().flat_map(|(x, z)| [x, z]).collect();
+
+        let trivial_basis = independent_operators(&commutant(&stabilizers, &logicals));
+        let syndrome_basis = syndrome_basis(&stabilizers);
+        assert!(
+            syndrome_basis.len() + trivial_basis.len() <= MAX_ENUMERATED_DIMENSION,
+            "the code is too large to enumerate every error"
+        );
+
+        let size = n + 1;
+        let mut corrected = vec![0u64; size * size * size];
+        let mut error = PauliString::identity(n);
+        for step in 0..1u64 << syndrome_basis.len() {
+            if step > 0 {
+                error.multiply(&syndrome_basis[step.trailing_zeros() as usize]);
+            }
+            // A correction that misses the syndrome leaves every error with it outside the code
+            let correction = decoder_correction(code, &error);
+            if stabilizers.iter().any(|s| correction.commutes_with(s) != error.commutes_with(s)) {
+                continue;
+            }
+            walk_span(PackedPauli::new(&correction), &trivial_basis, |element| {
+                let (a, b, c) = element.pauli_counts();
+                corrected[(a * size + b) * size + c] += 1;
+            });
+        }
+
+        let mut failures = corrected;
+        for (index, failing) in failures.iter_mut().enumerate() {
+            let (a, b, c) = (index / (size * size), index / size % size, index % size);
+            if a + b + c <= n {
+                *failing = multinomial(n, [a, b, c]) - *failing;
+            }
+        }
+        Self { num_qubits: n, failures }
+    }
+
+    pub fn num_qubits(&self) -> usize {
+        self.num_qubits
+    }
+
+    /// The number of failing errors of each weight
+    pub fn failures_by_weight(&self) -> Vec<u64> {
+        let mut by_weight = vec![0; self.num_qubits + 1];
+        for ((a, b, c), count) in self.counts() {
+            by_weight[a + b + c] += count;
+        }
+        by_weight
+    }
+
+    /// The probability of a logical failure when every qubit suffers `channel` independently
+    pub fn failure_probability(&self, channel: &PauliChannel) -> f64 {
+        let (p_x, p_y, p_z) = channel.pauli_probabilities();
+        let p_none = 1.0 - channel.probability();
+        self.counts()
+            .map(|((a, b, c), count)| {
+                let weight = a + b + c;
+                count as f64
+                    * p_x.powi(a as i32)
+                    * p_y.powi(b as i32)
+                    * p_z.powi(c as i32)
+                    * p_none.powi((self.num_qubits - weight) as i32)
+            })
+            .sum()
+    }
+
+    /// Coefficients c_0, …, c_n of the failure probability Σ c_j p^j when each qubit fails
+    /// with probability p, split between X, Y and Z in the proportions `shares`, which add up
+    /// to 1. Depolarizing noise has shares (1/3, 1/3, 1/3).
+    pub fn coefficients(&self, shares: (f64, f64, f64)) -> Vec<f64> {
+        let n = self.num_qubits;
+        let mut coefficients = vec![0.0; n + 1];
+        let (x_share, y_share, z_share) = shares;
+        for ((a, b, c), count) in self.counts() {
+            let weight = a + b + c;
+            let term =
+                count as f64 * x_share.powi(a as i32) * y_share.powi(b as i32) * z_share.powi(c as i32);
+            // p^w (1 − p)^(n−w), expanded binomially
+            for j in 0..=n - weight {
+                let sign = if j.is_multiple_of(2) { 1.0 } else { -1.0 };
+                coefficients[weight + j] += sign * term * binomial(n - weight, j) as f64;
+            }
+        }
+        coefficients
+    }
+
+    /// The nonzero counts, keyed by the numbers of X, Y and Z factors
+    fn counts(&self) -> impl Iterator<Item = ((usize, usize, usize), u64)> + '_ {
+        let size = self.num_qubits + 1;
+        self.failures
+            .iter()
+            .enumerate()
+            .filter(|(_, &count)| count > 0)
+            .map(move |(index, &count)| ((index / (size * size), index / size % size, index % size), count))
+    }
+}
+
+/// The correction the decoder of `code` applies for the syndrome of `error`
+fn decoder_correction<C: CorrectionCode + ?Sized>(code: &mut C, error: &PauliString) -> PauliString {
+    let mut encoded_qubits = code.encode(&Qubit::new());
+    error.apply_to(&mut encoded_qubits);
+    let syndromes = code.syndrome_measurement(&mut encoded_qubits);
+    code.correct(&mut encoded_qubits, syndromes);
+    PauliString::from_frame(&encoded_qubits).product(error)
+}
+
+/// Single-qubit errors whose syndromes form a basis of every syndrome the stabilizers can
+/// show, so that their products reach each syndrome exactly once
+fn syndrome_basis(stabilizers: &[PauliString]) -> Vec<PauliString> {
+    let n = stabilizers[0].len();
+    let mut syndromes = EchelonBasis::new();
+    let mut basis = Vec::new();
+    for qubit in 0..n {
+        for pauli in [Pauli::X, Pauli::Z] {
+            let error = PauliString::from_support(n, &[qubit], pauli);
+            let syndrome: Vec<bool> = stabilizers.iter().map(|s| !error.commutes_with(s)).collect();
+            if syndromes.insert(&syndrome) {
+                basis.push(error);
+            }
+        }
+    }
+    basis
+}
+
+/// A basis of the operators commuting with all of `stabilizers` and `logicals`: the
+/// stabilizer group itself for a stabilizer code, the gauge group for a subsystem code
+fn commutant(stabilizers: &[PauliString], logicals: &[PauliString]) -> Vec<PauliString> {
+    let n = stabilizers[0].len();
+    // u commutes with v when u · (v_z | v_x) = 0
+    let rows = stabilizers
+        .iter()
+        .chain(logicals)
+        .map(|operator| {
+            let mut row = operator.z_bits().to_vec();
+            row.extend_from_slice(operator.x_bits());
+            row
+        })
+        .collect();
+    BinaryMatrix::from_rows(2 * n, rows)
+        .kernel()
+        .rows()
+        .iter()
+        .map(|vector| PauliString::from_bits(vector[..n].to_vec(), vector[n..].to_vec()))
+        .collect()
+}
+
+fn independent_operators(operators: &[PauliString]) -> Vec<PackedPauli> {
+    let mut span = EchelonBasis::new();
+    let basis: Vec<PackedPauli> = operators
+        .iter()
+        .filter(|operator| span.insert(&symplectic_vector(operator)))
+        .map(PackedPauli::new)
+        .collect();
+    assert!(basis.len() <= MAX_ENUMERATED_DIMENSION, "the group is too large to enumerate");
+    basis
+}
+
+/// Visits `start` times every element of the span of `basis`, in Gray-code order
+fn walk_span(start: PackedPauli, basis: &[PackedPauli], mut visit: impl FnMut(&PackedPauli)) {
+    let mut current = start;
+    visit(&current);
+    for step in 1..1u64 << basis.len() {
+        current.multiply(&basis[step.trailing_zeros() as usize]);
+        visit(&current);
+    }
+}
+
+/// B_j = Σ_w A_w [y^j] (x + 3y)^(n−w) (x − y)^w / 2^dimension
+fn macwilliams_transform(stabilizer: &[u64], dimension: usize) -> Vec<u64> {
+    let n = stabilizer.len() - 1;
+    (0..=n)
+        .map(|j| {
+            let mut sum = 0i128;
+            for (w, &count) in stabilizer.iter().enumerate() {
+                for i in j.saturating_sub(w)..=j.min(n - w) {
+                    let sign = if (j - i).is_multiple_of(2) { 1 } else { -1 };
+                    let term = binomial(n - w, i) as i128 * 3i128.pow(i as u32) * binomial(w, j - i) as i128;
+                    sum += sign * count as i128 * term;
+                }
+            }
+            (sum >> dimension) as u64
+        })
+        .collect()
+}
+
+fn binomial(n: usize, k: usize) -> u64 {
+    (0..k).fold(1, |acc, i| acc * (n - i) as u64 / (i + 1) as u64)
+}
+
+/// n! / (a! b! c! (n − a − b − c)!)
+fn multinomial(n: usize, [a, b, c]: [usize; 3]) -> u64 {
+    binomial(n, a) * binomial(n - a, b) * binomial(n - a - b, c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::correction_codes::{BitFlipCode, CssCode, StabilizerCode};
+
+    /// B by checking every Pauli on the block against the stabilizers
+    fn normalizer_by_brute_force(stabilizers: &[PauliString]) -> Vec<u64> {
+        let n = stabilizers[0].len();
+        let mut normalizer = vec![0; n + 1];
+        for bits in 0..1usize << (2 * n) {
+            let x = (0..n).map(|q| bits >> q & 1 == 1).collect();
+            let z = (0..n).map(|q| bits >> (n + q) & 1 == 1).collect();
+            let operator = PauliString::from_bits(x, z);
+            if stabilizers.iter().all(|s| s.commutes_with(&operator)) {
+                normalizer[operator.weight()] += 1;
+            }
+        }
+        normalizer
+    }
+
+    #[test]
+    fn macwilliams_identity_gives_the_normalizer_enumerator() {
+        let five_qubit = StabilizerCode::five_qubit().stabilizer_generators();
+        let steane = CssCode::steane().stabilizer_generators();
+        let cases = [
+            (five_qubit, vec![1, 0, 0, 0, 15, 0], vec![1, 0, 0, 30, 15, 18]),
+            (steane, vec![1, 0, 0, 0, 21, 0, 42, 0], vec![1, 0, 0, 21, 21, 126, 42, 45]),
+        ];
+        for (stabilizers, a, b) in cases {
+            let enumerators = WeightEnumerators::new(&stabilizers);
+            assert_eq!(enumerators.stabilizer, a);
+            assert_eq!(enumerators.normalizer, b);
+            assert_eq!(enumerators.normalizer, normalizer_by_brute_force(&stabilizers));
+            assert_eq!(enumerators.distance(), Some(3));
+        }
+    }
+
+    #[test]
+    fn bit_flip_code_fails_on_two_or_three_flips() {
+        let polynomial = LogicalErrorPolynomial::new(&mut BitFlipCode::new());
+        assert_eq!(polynomial.coefficients((1.0, 0.0, 0.0)), vec![0.0, 0.0, 3.0, -2.0]);
+        for p in [0.01, 0.1, 0.3] {
+            let exact = 3.0 * p * p - 2.0 * p * p * p;
+            let found = polynomial.failure_probability(&PauliChannel::new(p, 0.0, 0.0));
+            assert!((found - exact).abs() < 1e-12, "p = {}: {} against {}", p, found, exact);
+        }
+    }
+}
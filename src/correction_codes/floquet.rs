@@ -0,0 +1,688 @@
+use super::stabilizer::symplectic_vector;
+use super::subsystem::bare_logicals;
+use crate::correction_codes::{average_duration, CodeConstructionError};
+use crate::error_models::{ErrorModel, PauliChannel};
+use crate::gf2::{BinaryMatrix, EchelonBasis};
+use crate::matching::DecodingGraph;
+use crate::pauli::{Pauli, PauliString};
+use crate::qubit::Qubit;
+use crate::simulation::SimulationResult;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::time::Instant;
+
+/// A measurement-only code whose checks are measured in a periodic schedule.
+///
+/// Each round of the period measures a set of commuting checks, but checks of different
+/// rounds need not commute, so the stabilizer group changes from round to round. After a few
+/// rounds it settles into an instantaneous stabilizer group (ISG) made of the checks just
+/// measured and the products of checks that every round preserves. The logical qubits are
+/// those of the ISG; their operators are carried along by multiplying them with stabilizers
+/// whenever a measurement would disturb them.
+///
+/// The static stabilizers are products of checks that commute with every check, such as the
+/// plaquettes of the honeycomb code. Their values are inferred again and again from the
+/// latest outcomes of their checks, and comparing two inferences gives a detector.
+///
+/// There is no static syndrome, so the code does not implement `CorrectionCode`; use
+/// `FloquetMemory` to run it for a number of rounds.
+#[derive(Clone, Debug)]
+pub struct FloquetCode {
+    num_qubits: usize,
+    checks: Vec<PauliString>,
+    /// Checks measured in each round of the period
+    schedule: Vec<Vec<usize>>,
+    /// Checks whose product is each static stabilizer
+    static_stabilizers: Vec<Vec<usize>>,
+}
+
+impl FloquetCode {
+    /// A code measuring `checks[i]` for every i in `schedule[r]` in round r of the period,
+    /// with static stabilizers given by the checks they are products of.
+    ///
+    /// Fails if the checks act on different numbers of qubits or two checks of the same
+    /// round anticommute.
+    pub fn new(
+        checks: Vec<PauliString>,
+        schedule: Vec<Vec<usize>>,
+        static_stabilizers: Vec<Vec<usize>>,
+    ) -> Result<Self, CodeConstructionError> {
+        let num_qubits = checks.first().ok_or(CodeConstructionError::NoGenerators)?.len();
+        for (index, check) in checks.iter().enumerate() {
+            if check.len() != num_qubits {
+                return Err(CodeConstructionError::LengthMismatch {
+                    generator: index,
+                    expected: num_qubits,
+                    found: check.len(),
+                });
+            }
+        }
+        for round in &schedule {
+            for (position, &first) in round.iter().enumerate() {
+                for &second in &round[position + 1..] {
+                    if !checks[first].commutes_with(&checks[second]) {
+                        return Err(CodeConstructionError::NonCommutingGenerators { first, second });
+                    }
+                }
+            }
+        }
+        Ok(Self {
+            num_qubits,
+            checks,
+            schedule,
+            static_stabilizers,
+        })
+    }
+
+    /// The honeycomb code of Hastings and Haah on a torus of `rows` × `cols` qubits.
+    ///
+    /// The honeycomb is drawn as a brick wall: qubit (r, c) has index `r * cols + c` and is
+    /// joined to its left and right neighbours and, when r + c is even, to the qubit below.
+    /// Each hexagon, or brick, with top-left qubit (r, c) is coloured c mod 3, which needs
+    /// `cols` to be a multiple of 6, and each edge gets the colour of neither brick beside it.
+    /// The check on an edge is ZZ for vertical edges and XX or YY for horizontal ones, so each
+    /// qubit meets one check of every type. Round r of the period measures the edges of colour
+    /// r. The hexagons are the static stabilizers: the ISG holds them and the edges just
+    /// measured, which leaves two logical qubits.
+    pub fn honeycomb(rows: usize, cols: usize) -> Self {
+        assert!(rows >= 2 && rows.is_multiple_of(2), "the honeycomb needs an even number of rows");
+        assert!(cols >= 6 && cols.is_multiple_of(6), "the honeycomb needs a multiple of 6 columns");
+        let n = rows * cols;
+        let qubit = |r: usize, c: usize| (r % rows) * cols + c % cols;
+
+        let mut edges = Vec::with_capacity(3 * n / 2);
+        let mut checks = Vec::with_capacity(3 * n / 2);
+        for r in 0..rows {
+            for c in 0..cols {
+                let pauli = if (r + c) % 2 == 0 { Pauli::X } else { Pauli::Y };
+                edges.push((qubit(r, c), qubit(r, c + 1)));
+                checks.push(PauliString::from_support(n, &[qubit(r, c), qubit(r, c + 1)], pauli));
+                if (r + c) % 2 == 0 {
+                    edges.push((qubit(r, c), qubit(r + 1, c)));
+                    checks.push(PauliString::from_support(n, &[qubit(r, c), qubit(r + 1, c)], Pauli::Z));
+                }
+            }
+        }
+        let edge_index: HashMap<(usize, usize), usize> =
+            edges.iter().enumerate().map(|(index, &edge)| (edge, index)).collect();
+
+        // Collect the two brick colours beside each edge; the edge takes the third
+        let mut beside: Vec<Vec<usize>> = vec![Vec::new(); edges.len()];
+        let mut bricks = Vec::with_capacity(n / 2);
+        for r in 0..rows {
+            for c in (r % 2..cols).step_by(2) {
+                let brick = [
+                    (qubit(r, c), qubit(r, c + 1)),
+                    (qubit(r, c + 1), qubit(r, c + 2)),
+                    (qubit(r + 1, c), qubit(r + 1, c + 1)),
+                    (qubit(r + 1, c + 1), qubit(r + 1, c + 2)),
+                    (qubit(r, c), qubit(r + 1, c)),
+                    (qubit(r, c + 2), qubit(r + 1, c + 2)),
+                ];
+                let brick_checks: Vec<usize> = brick.iter().map(|edge| edge_index[edge]).collect();
+                for &check in &brick_checks {
+                    beside[check].push(c % 3);
+                }
+                bricks.push(brick_checks);
+            }
+        }
+        let mut schedule = vec![Vec::new(); 3];
+        for (edge, colours) in beside.iter().enumerate() {
+            schedule[3 - colours[0] - colours[1]].push(edge);
+        }
+
+        Self::new(checks, schedule, bricks).expect("edges of one colour never share a qubit")
+    }
+
+    pub fn num_qubits(&self) -> usize {
+        self.num_qubits
+    }
+
+    pub fn checks(&self) -> &[PauliString] {
+        &self.checks
+    }
+
+    /// Check indices measured in each round of the period
+    pub fn schedule(&self) -> &[Vec<usize>] {
+        &self.schedule
+    }
+
+    /// Check indices whose product is each static stabilizer
+    pub fn static_stabilizers(&self) -> &[Vec<usize>] {
+        &self.static_stabilizers
+    }
+
+    pub fn period(&self) -> usize {
+        self.schedule.len()
+    }
+
+    /// Checks measured in round `round`, counting from 0
+    pub fn round_checks(&self, round: usize) -> &[usize] {
+        &self.schedule[round % self.period()]
+    }
+
+    /// Generators of the ISG after measuring the first `rounds` rounds, starting from the
+    /// maximally mixed state
+    pub fn instantaneous_stabilizers(&self, rounds: usize) -> Vec<PauliString> {
+        let mut tracker = StabilizerTracker::new(0);
+        for round in 0..rounds {
+            for &check in self.round_checks(round) {
+                tracker.measure(&self.checks[check], None);
+            }
+        }
+        tracker.generators
+    }
+
+    /// Logical qubits of the ISG once the schedule has settled
+    pub fn num_logical_qubits(&self) -> usize {
+        let stabilizers = self.instantaneous_stabilizers(2 * self.period());
+        let mut matrix = BinaryMatrix::from_rows(
+            2 * self.num_qubits,
+            stabilizers.iter().map(symplectic_vector).collect(),
+        );
+        self.num_qubits - matrix.row_reduce().len()
+    }
+}
+
+/// A stabilizer group under Pauli measurements, keeping with each generator the set of
+/// measurements whose outcomes multiply to its value
+struct StabilizerTracker {
+    generators: Vec<PauliString>,
+    records: Vec<Vec<bool>>,
+    num_measurements: usize,
+}
+
+/// What a measurement did to the group
+enum MeasurementEffect {
+    /// The outcome was random; the generator and record that anticommuted with the
+    /// measured operator were dropped, if there was one
+    Random(Option<(PauliString, Vec<bool>)>),
+    /// The outcome was fixed by earlier ones: these measurements, the new one included,
+    /// multiply to a known value
+    Deterministic(Vec<bool>),
+}
+
+impl StabilizerTracker {
+    fn new(num_measurements: usize) -> Self {
+        Self {
+            generators: Vec::new(),
+            records: Vec::new(),
+            num_measurements,
+        }
+    }
+
+    /// Measures `operator`, labelling the outcome as measurement `measurement` if given
+    fn measure(&mut self, operator: &PauliString, measurement: Option<usize>) -> MeasurementEffect {
+        let mut record = vec![false; self.num_measurements];
+        if let Some(measurement) = measurement {
+            record[measurement] = true;
+        }
+
+        let anticommuting: Vec<usize> = (0..self.generators.len())
+            .filter(|&g| !self.generators[g].commutes_with(operator))
+            .collect();
+        if let Some((&pivot, rest)) = anticommuting.split_first() {
+            for &g in rest {
+                let pivot_generator = self.generators[pivot].clone();
+                let pivot_record = self.records[pivot].clone();
+                self.generators[g].multiply(&pivot_generator);
+                add_records(&mut self.records[g], &pivot_record);
+            }
+            let dropped = (self.generators.remove(pivot), self.records.remove(pivot));
+            self.generators.push(operator.clone());
+            self.records.push(record);
+            return MeasurementEffect::Random(Some(dropped));
+        }
+
+        // The operator commutes with the group; it is fixed exactly when it lies in the span
+        let columns = BinaryMatrix::from_rows(
+            2 * operator.len(),
+            self.generators.iter().map(symplectic_vector).collect(),
+        )
+        .transpose();
+        match columns.solve(&symplectic_vector(operator)) {
+            Some(combination) if !self.generators.is_empty() => {
+                for (g, _) in combination.iter().enumerate().filter(|(_, &used)| used) {
+                    add_records(&mut record, &self.records[g]);
+                }
+                MeasurementEffect::Deterministic(record)
+            }
+            _ => {
+                self.generators.push(operator.clone());
+                self.records.push(record);
+                MeasurementEffect::Random(None)
+            }
+        }
+    }
+}
+
+/// A Pauli on one qubit, given as (qubit, Pauli)
+type SingleQubitPauli = (usize, Pauli);
+
+/// Parity of the flipped outcomes among the measurements of a record
+fn record_parity(record: &[bool], flips: &[bool]) -> bool {
+    record.iter().zip(flips).filter(|(&used, &flip)| used && flip).count() % 2 == 1
+}
+
+fn add_records(target: &mut [bool], source: &[bool]) {
+    for (bit, &other) in target.iter_mut().zip(source) {
+        *bit ^= other;
+    }
+}
+
+/// A memory experiment running a `FloquetCode` for a number of noisy rounds.
+///
+/// The experiment starts with two noiseless periods that bring the ISG to its steady state,
+/// which stands in for a perfect initialisation, then runs the noisy rounds, and ends with
+/// two noiseless periods so that every detector a noisy round can trip gets closed off.
+///
+/// Each detector compares two successive inferences of a static stabilizer. Whether two
+/// inferences agree without noise is read off the ISG, tracked together with the
+/// measurements behind each of its generators, so a detector is only kept when its outcomes
+/// are fixed. The observables are a logical X and Z for each logical qubit of the ISG at the
+/// start of the noisy rounds, each carried to the end as a Pauli operator times a product of
+/// measurement outcomes.
+///
+/// Every single-qubit Pauli before a noisy round and every flipped outcome in a noisy round
+/// is a fault. Faults that trip one or two detectors become the edges of a matching graph,
+/// weighted by their log-likelihood once `run` knows the noise; faults on the same pair of
+/// detectors merge when they flip the same observables and stay parallel edges otherwise.
+/// Heavier faults are left to paths of those edges: a Y error is an X and a Z error, and a
+/// flipped outcome is the same Pauli just before and just after the measurement.
+pub struct FloquetMemory {
+    code: FloquetCode,
+    warmup_rounds: usize,
+    noisy_rounds: usize,
+    /// Check measured by each measurement, in time order
+    measurement_checks: Vec<usize>,
+    /// First measurement of each round
+    round_offsets: Vec<usize>,
+    detectors: Vec<Vec<usize>>,
+    observables: Vec<Observable>,
+    /// Every single fault, found once and weighted by each run
+    faults: Vec<Fault>,
+}
+
+/// A logical operator at the end of the experiment together with the measurements it was
+/// multiplied by on the way
+struct Observable {
+    operator: PauliString,
+    record: Vec<bool>,
+}
+
+/// A single fault: the detectors it trips, the observables it flips, and the Pauli it is, or
+/// `None` for a flipped outcome
+struct Fault {
+    tripped: Vec<usize>,
+    observables: Vec<bool>,
+    pauli: Option<Pauli>,
+}
+
+impl FloquetMemory {
+    pub fn new(code: FloquetCode, noisy_rounds: usize) -> Self {
+        let period = code.period();
+        let warmup_rounds = 2 * period;
+        let total_rounds = warmup_rounds + noisy_rounds + 2 * period;
+
+        let mut measurement_checks = Vec::new();
+        let mut round_offsets = Vec::with_capacity(total_rounds + 1);
+        for round in 0..total_rounds {
+            round_offsets.push(measurement_checks.len());
+            measurement_checks.extend_from_slice(code.round_checks(round));
+        }
+        round_offsets.push(measurement_checks.len());
+        let num_measurements = measurement_checks.len();
+
+        let mut tracker = StabilizerTracker::new(num_measurements);
+        // Every product of outcomes that is fixed without noise, found as the ISG is tracked
+        let mut deterministic = EchelonBasis::new();
+        let mut latest_measurement: Vec<Option<usize>> = vec![None; code.checks.len()];
+        let mut inferences: Vec<Vec<Vec<bool>>> = vec![Vec::new(); code.static_stabilizers.len()];
+        let mut detectors = Vec::new();
+        let mut observables: Vec<Observable> = Vec::new();
+        for round in 0..total_rounds {
+            if round == warmup_rounds {
+                let (logical_x, logical_z) = bare_logicals(&tracker.generators, &tracker.generators);
+                observables = logical_x
+                    .into_iter()
+                    .zip(logical_z)
+                    .flat_map(|(x, z)| [x, z])
+                    .map(|operator| Observable {
+                        operator,
+                        record: vec![false; num_measurements],
+                    })
+                    .collect();
+            }
+            for measurement in round_offsets[round]..round_offsets[round + 1] {
+                let check = &code.checks[measurement_checks[measurement]];
+                latest_measurement[measurement_checks[measurement]] = Some(measurement);
+                match tracker.measure(check, Some(measurement)) {
+                    MeasurementEffect::Deterministic(record) => {
+                        deterministic.insert(&record);
+                    }
+                    MeasurementEffect::Random(dropped) => {
+                        for observable in observables.iter_mut() {
+                            if observable.operator.commutes_with(check) {
+                                continue;
+                            }
+                            let (generator, record) =
+                                dropped.as_ref().expect("a check anticommutes with a logical operator");
+                            observable.operator.multiply(generator);
+                            add_records(&mut observable.record, record);
+                        }
+                    }
+                }
+            }
+
+            // Infer each static stabilizer touched this round from the latest outcomes of its
+            // checks. An inference may be spoilt by later measurements on its own, so it only
+            // makes a detector with the most recent earlier one that agrees with it
+            let this_round = round_offsets[round]..round_offsets[round + 1];
+            for (stabilizer, stabilizer_checks) in code.static_stabilizers.iter().enumerate() {
+                let latest: Option<Vec<usize>> =
+                    stabilizer_checks.iter().map(|&c| latest_measurement[c]).collect();
+                let touched = |latest: &Vec<usize>| latest.iter().any(|m| this_round.contains(m));
+                let Some(latest) = latest.filter(touched) else {
+                    continue;
+                };
+                let mut inference = vec![false; num_measurements];
+                for m in latest {
+                    inference[m] ^= true;
+                }
+                let detector = inferences[stabilizer].iter().rev().find_map(|earlier| {
+                    let mut difference = inference.clone();
+                    add_records(&mut difference, earlier);
+                    deterministic.contains(&difference).then_some(difference)
+                });
+                // Detectors that end before the noise starts can never fire
+                if round >= warmup_rounds {
+                    if let Some(detector) = detector {
+                        detectors.push((0..num_measurements).filter(|&m| detector[m]).collect());
+                    }
+                }
+                inferences[stabilizer].push(inference);
+            }
+        }
+
+        let mut memory = Self {
+            code,
+            warmup_rounds,
+            noisy_rounds,
+            measurement_checks,
+            round_offsets,
+            detectors,
+            observables,
+            faults: Vec::new(),
+        };
+        memory.faults = memory.single_faults();
+        memory
+    }
+
+    pub fn code(&self) -> &FloquetCode {
+        &self.code
+    }
+
+    pub fn num_measurements(&self) -> usize {
+        self.measurement_checks.len()
+    }
+
+    /// Measurements making up each detector
+    pub fn detectors(&self) -> &[Vec<usize>] {
+        &self.detectors
+    }
+
+    /// Logical X and Z of each logical qubit as they stand at the end of the experiment
+    pub fn final_logical_operators(&self) -> Vec<&PauliString> {
+        self.observables.iter().map(|observable| &observable.operator).collect()
+    }
+
+    /// Samples `num_shots` runs with `error_model` applied to every qubit before each noisy
+    /// round and each noisy outcome flipped with probability `measurement_error_rate`.
+    ///
+    /// A logical qubit fails when the decoder mispredicts its X or Z observable. The decoder
+    /// weights faults with the probabilities of `error_model`'s Pauli channel; other models
+    /// are weighted as depolarizing noise at `measurement_error_rate`.
+    pub fn run(
+        &self,
+        error_model: &dyn ErrorModel,
+        measurement_error_rate: f64,
+        num_shots: usize,
+    ) -> SimulationResult {
+        let n = self.code.num_qubits();
+        let num_logical_qubits = self.observables.len() / 2;
+        let mut logical_failure_counts = vec![0; num_logical_qubits];
+        let mut success_count = 0;
+        let mut decoding_time = Vec::with_capacity(num_shots);
+        let channel = error_model.pauli_channel().unwrap_or_else(|| {
+            let p = measurement_error_rate / 3.0;
+            PauliChannel::new(p, p, p)
+        });
+        let (graph, edge_observables) = self.decoding_graph(&channel, measurement_error_rate);
+
+        for _ in 0..num_shots {
+            let mut frame = PauliString::identity(n);
+            let mut flips = vec![false; self.num_measurements()];
+            for round in 0..self.round_offsets.len() - 1 {
+                let noisy = self.is_noisy(round);
+                if noisy {
+                    for q in 0..n {
+                        let mut qubit = Qubit::frame_reference();
+                        error_model.apply_error(&mut qubit);
+                        frame.set(q, frame.get(q).multiply(qubit.pauli_frame()));
+                    }
+                }
+                let measurements = self.round_offsets[round]..self.round_offsets[round + 1];
+                let checks = &self.measurement_checks[measurements.clone()];
+                for (flip, &check) in flips[measurements].iter_mut().zip(checks) {
+                    let misread = noisy && rand::random::<f64>() < measurement_error_rate;
+                    *flip = !frame.commutes_with(&self.code.checks[check]) ^ misread;
+                }
+            }
+
+            let start = Instant::now();
+            let defects: Vec<usize> = self
+                .detectors
+                .iter()
+                .enumerate()
+                .filter(|(_, members)| members.iter().filter(|&&m| flips[m]).count() % 2 == 1)
+                .map(|(detector, _)| detector)
+                .collect();
+            let predicted = self.predict(&graph, &edge_observables, &defects);
+            decoding_time.push(start.elapsed());
+
+            let mut success = true;
+            for (logical_qubit, count) in logical_failure_counts.iter_mut().enumerate() {
+                let failed = (2 * logical_qubit..2 * logical_qubit + 2).any(|o| {
+                    let observable = &self.observables[o];
+                    let residual = !frame.commutes_with(&observable.operator);
+                    let actual = residual ^ record_parity(&observable.record, &flips);
+                    actual != predicted[o]
+                });
+                if failed {
+                    *count += 1;
+                    success = false;
+                }
+            }
+            if success {
+                success_count += 1;
+            }
+        }
+
+        let success_rate = success_count as f64 / num_shots as f64;
+        SimulationResult {
+            success_rate,
+            error_rate: 1.0 - success_rate,
+            average_correction_time: average_duration(&decoding_time),
+            logical_error_rates: logical_failure_counts
+                .iter()
+                .map(|&count| count as f64 / num_shots as f64)
+                .collect(),
+        }
+    }
+
+    fn is_noisy(&self, round: usize) -> bool {
+        (self.warmup_rounds..self.warmup_rounds + self.noisy_rounds).contains(&round)
+    }
+
+    /// Observables flipped by the edges matching `defects`
+    fn predict(&self, graph: &DecodingGraph, edge_observables: &[Vec<bool>], defects: &[usize]) -> Vec<bool> {
+        let mut predicted = vec![false; self.observables.len()];
+        for edge in graph.match_defects(defects) {
+            add_records(&mut predicted, &edge_observables[edge]);
+        }
+        predicted
+    }
+
+    /// Every single fault, with the detectors it trips and the observables it flips
+    fn single_faults(&self) -> Vec<Fault> {
+        let num_measurements = self.num_measurements();
+        let mut measurement_detectors = vec![Vec::new(); num_measurements];
+        for (detector, members) in self.detectors.iter().enumerate() {
+            for &m in members {
+                measurement_detectors[m].push(detector);
+            }
+        }
+
+        // Each fault is the list of measurements it flips, plus the Pauli it leaves behind
+        let n = self.code.num_qubits();
+        let mut faults: Vec<(Vec<usize>, Option<SingleQubitPauli>)> = Vec::new();
+        // Paulis just before the round after the last noisy one are never sampled, but a misread
+        // in the last noisy round equals one before and one after it, so the graph needs them
+        let last_round = self.warmup_rounds + self.noisy_rounds;
+        for round in self.warmup_rounds..=last_round {
+            for q in 0..n {
+                for pauli in [Pauli::X, Pauli::Y, Pauli::Z] {
+                    let flipped = (self.round_offsets[round]..num_measurements)
+                        .filter(|&m| {
+                            let check = &self.code.checks[self.measurement_checks[m]];
+                            !check.get(q).commutes_with(pauli)
+                        })
+                        .collect();
+                    faults.push((flipped, Some((q, pauli))));
+                }
+            }
+            if round < last_round {
+                for m in self.round_offsets[round]..self.round_offsets[round + 1] {
+                    faults.push((vec![m], None));
+                }
+            }
+        }
+
+        faults
+            .into_iter()
+            .map(|(flipped, residual)| {
+                let mut tripped: Vec<usize> = Vec::new();
+                for &m in &flipped {
+                    for &detector in &measurement_detectors[m] {
+                        match tripped.iter().position(|&d| d == detector) {
+                            Some(position) => {
+                                tripped.swap_remove(position);
+                            }
+                            None => tripped.push(detector),
+                        }
+                    }
+                }
+                tripped.sort_unstable();
+                let observables = self
+                    .observables
+                    .iter()
+                    .map(|observable| {
+                        let from_outcomes =
+                            flipped.iter().filter(|&&m| observable.record[m]).count() % 2 == 1;
+                        let from_residual = residual
+                            .is_some_and(|(q, pauli)| !observable.operator.get(q).commutes_with(pauli));
+                        from_outcomes ^ from_residual
+                    })
+                    .collect();
+                Fault { tripped, observables, pauli: residual.map(|(_, pauli)| pauli) }
+            })
+            .collect()
+    }
+
+    /// The matching graph of the faults that trip one or two detectors, with one boundary
+    /// node, and the observables flipped by each edge.
+    ///
+    /// Faults on the same detectors that flip the same observables are one edge, failing when
+    /// an odd number of them occur; faults that flip different observables are parallel
+    /// edges. Faults that never occur are left out.
+    fn decoding_graph(
+        &self,
+        channel: &PauliChannel,
+        measurement_error_rate: f64,
+    ) -> (DecodingGraph, Vec<Vec<bool>>) {
+        let (p_x, p_y, p_z) = channel.pauli_probabilities();
+        let boundary = self.detectors.len();
+        let mut merged: HashMap<(usize, usize, &[bool]), f64> = HashMap::new();
+        let mut order = Vec::new();
+        for fault in &self.faults {
+            let (a, b) = match fault.tripped[..] {
+                [a] => (a, boundary),
+                [a, b] => (a, b),
+                _ => continue,
+            };
+            let probability = match fault.pauli {
+                Some(Pauli::X) => p_x,
+                Some(Pauli::Y) => p_y,
+                Some(Pauli::Z) => p_z,
+                Some(Pauli::I) => 0.0,
+                None => measurement_error_rate,
+            };
+            let key = (a, b, &fault.observables[..]);
+            match merged.entry(key) {
+                Entry::Occupied(mut entry) => {
+                    let p = *entry.get();
+                    entry.insert(p * (1.0 - probability) + probability * (1.0 - p));
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(probability);
+                    order.push(key);
+                }
+            }
+        }
+
+        let mut graph = DecodingGraph::new(boundary + 1);
+        graph.set_boundary(boundary);
+        let mut edge_observables = Vec::new();
+        for key in order {
+            let probability = merged[&key];
+            if probability > 0.0 {
+                let (a, b, observables) = key;
+                graph.add_fault(a, b, probability, 0);
+                edge_observables.push(observables.to_vec());
+            }
+        }
+        (graph, edge_observables)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn honeycomb_code_has_two_logical_qubits() {
+        assert_eq!(FloquetCode::honeycomb(4, 6).num_logical_qubits(), 2);
+    }
+
+    #[test]
+    fn noiseless_memory_never_fails() {
+        let memory = FloquetMemory::new(FloquetCode::honeycomb(4, 6), 6);
+        let result = memory.run(&PauliChannel::new(0.0, 0.0, 0.0), 0.0, 20);
+        assert_eq!(result.success_rate, 1.0);
+        assert!(result.logical_error_rates.iter().all(|&rate| rate == 0.0));
+    }
+
+    #[test]
+    fn every_single_fault_is_decoded() {
+        // On the 4×6 lattice some misreads tie with a matching of the same weight that flips
+        // other observables, so this needs the wider one
+        let memory = FloquetMemory::new(FloquetCode::honeycomb(4, 12), 3);
+        let channel = PauliChannel::new(0.001, 0.001, 0.001);
+        let (graph, edge_observables) = memory.decoding_graph(&channel, 0.001);
+        for (i, fault) in memory.faults.iter().enumerate() {
+            let predicted = memory.predict(&graph, &edge_observables, &fault.tripped);
+            let pauli = fault.pauli;
+            assert_eq!(predicted, fault.observables, "fault {} ({:?}) trips {:?}", i, pauli, fault.tripped);
+        }
+    }
+}
@@ -0,0 +1,493 @@
+use super::bacon_shor::lighter_repetition_pattern;
+use crate::correction_codes::{
+    average_duration, encode_frame, logical_pauli, CodeConstructionError, CodeParameters, CorrectionCode,
+    GaugeMeasurement, SubsystemCode,
+};
+use crate::gates::CliffordGate;
+use crate::hardware::CouplingGraph;
+use crate::matching::DecodingGraph;
+use crate::pauli::{Pauli, PauliString};
+use crate::qubit::Qubit;
+use std::time::{Duration, Instant};
+
+/// The heavy-hexagon subsystem code on a d×d grid of data qubits, for odd d.
+///
+/// Data qubit (r, c) has index `r * d + c`. The X gauge generators are the X plaquettes of
+/// the rotated surface code: weight four on the checkerboard of squares whose top-left corner
+/// (r, c) has r + c even, and weight two along the top and bottom edges. The Z gauge
+/// generators are ZZ on every pair of vertically neighbouring qubits. The Z-type stabilizers
+/// are the remaining surface-code Z plaquettes, each the product of two ZZ gauges, and catch
+/// X errors by matching; the X-type stabilizers are X on pairs of adjacent columns, as in the
+/// Bacon–Shor code, and catch Z errors as a repetition code. Logical X is X down the first
+/// column and logical Z is Z along the first row.
+///
+/// Gauge outcomes come first for the X generators, in order of their top-left corner, then
+/// for the `d * (d - 1)` ZZ generators ordered by their upper qubit.
+///
+/// Every gauge is measured with ancillas of degree at most three. A flag qubit sits between
+/// each vertical pair of data qubits and measures its ZZ gauge directly. A weight-four X
+/// gauge uses a syndrome qubit joined to the flag qubits on its left and right, which spread
+/// the syndrome qubit onto the four data qubits and flag faults that reach two of them; a
+/// weight-two X gauge uses a syndrome qubit joined to both data qubits. In the native layout
+/// the flag qubits follow the data qubits, ordered like the ZZ gauges, and the syndrome
+/// qubits come last, ordered like the X gauges.
+///
+/// `correct` only reads gauge outcomes. `syndrome_measurement` measures the gauges
+/// directly rather than running the schedule, so no flag outcomes exist to condition the
+/// matching weights on; the flags matter only to a circuit-level simulation of the schedule.
+pub struct HeavyHexCode {
+    distance: usize,
+    subsystem: SubsystemCode,
+    /// Top-left corner of each X gauge, in gauge order
+    x_gauges: Vec<(isize, isize)>,
+    /// Top-left corner of each Z-type stabilizer, truncated at the left and right edges
+    z_plaquettes: Vec<(isize, isize)>,
+    /// Matching graph of the Z-type stabilizers, whose edges are X errors
+    graph: DecodingGraph,
+    /// Data qubit each graph edge stands for
+    corrections: Vec<usize>,
+    logical_x: PauliString,
+    logical_z: PauliString,
+    coupling_graph: CouplingGraph,
+    /// Device qubit of each layout qubit
+    placement: Vec<usize>,
+    schedule: Vec<Vec<GaugeMeasurement>>,
+    correction_time: Vec<Duration>,
+}
+
+impl HeavyHexCode {
+    /// The code on its native heavy-hex layout
+    pub fn new(distance: usize) -> Self {
+        let schedule = Self::layout_schedule(distance);
+        let layout_qubits = Self::num_layout_qubits(distance);
+        let mut coupling_graph = CouplingGraph::new(layout_qubits);
+        for measurement in schedule.iter().flatten() {
+            for gate in &measurement.circuit {
+                if let CliffordGate::Cnot { control, target } = *gate {
+                    coupling_graph.add_edge(control, target);
+                }
+            }
+        }
+        Self::on_device(distance, coupling_graph, &(0..layout_qubits).collect::<Vec<_>>())
+            .expect("the native layout has every coupler the schedule needs")
+    }
+
+    /// The code placed on a device, with layout qubit i on device qubit `placement[i]`.
+    ///
+    /// Fails if the measurement schedule needs a two-qubit gate between device qubits that
+    /// are not coupled.
+    pub fn on_device(
+        distance: usize,
+        coupling_graph: CouplingGraph,
+        placement: &[usize],
+    ) -> Result<Self, CodeConstructionError> {
+        let mut schedule = Self::layout_schedule(distance);
+        let layout_qubits = Self::num_layout_qubits(distance);
+        assert_eq!(placement.len(), layout_qubits, "every layout qubit needs a device qubit");
+        for (i, &device_qubit) in placement.iter().enumerate() {
+            let on_device = device_qubit < coupling_graph.num_qubits();
+            assert!(on_device, "device qubit {} is off the device", device_qubit);
+            assert!(!placement[..i].contains(&device_qubit), "device qubit {} is placed twice", device_qubit);
+        }
+
+        for measurement in schedule.iter_mut().flatten() {
+            measurement.syndrome_qubit = placement[measurement.syndrome_qubit];
+            for flag in measurement.flag_qubits.iter_mut() {
+                *flag = placement[*flag];
+            }
+            for gate in measurement.circuit.iter_mut() {
+                *gate = match *gate {
+                    CliffordGate::H(q) => CliffordGate::H(placement[q]),
+                    CliffordGate::Cnot { control, target } => CliffordGate::Cnot {
+                        control: placement[control],
+                        target: placement[target],
+                    },
+                    _ => unreachable!("gauge measurements only use H and CNOT"),
+                };
+            }
+            if let Some((first, second)) = coupling_graph.uncoupled_gate(&measurement.circuit) {
+                return Err(CodeConstructionError::UncoupledQubits { first, second });
+            }
+        }
+
+        let d = distance as isize;
+        let n = distance * distance;
+        let qubit = |r: isize, c: isize| {
+            ((0..d).contains(&r) && (0..d).contains(&c)).then(|| (r * d + c) as usize)
+        };
+
+        let mut x_gauges = Vec::new();
+        let mut z_plaquettes = Vec::new();
+        for r in -1..d {
+            for c in -1..d {
+                let interior_row = (0..d - 1).contains(&r);
+                let interior_col = (0..d - 1).contains(&c);
+                let even = (r + c).rem_euclid(2) == 0;
+                if even && interior_col && (interior_row || r == -1 || r == d - 1) {
+                    x_gauges.push((r, c));
+                }
+                if !even && interior_row && (interior_col || c == -1 || c == d - 1) {
+                    z_plaquettes.push((r, c));
+                }
+            }
+        }
+
+        let mut gauge_generators = Vec::with_capacity(x_gauges.len() + distance * (distance - 1));
+        for &(r, c) in &x_gauges {
+            let support: Vec<usize> = [(r, c), (r, c + 1), (r + 1, c), (r + 1, c + 1)]
+                .iter()
+                .filter_map(|&(r, c)| qubit(r, c))
+                .collect();
+            gauge_generators.push(PauliString::from_support(n, &support, Pauli::X));
+        }
+        for upper in 0..n - distance {
+            gauge_generators.push(PauliString::from_support(n, &[upper, upper + distance], Pauli::Z));
+        }
+        let subsystem = SubsystemCode::from_pauli_strings(gauge_generators)
+            .expect("the heavy-hex gauge group encodes one logical qubit")
+            .with_distance(distance);
+
+        // An X error trips the two diagonal neighbours among the four plaquettes around it that
+        // have the Z colour. The Z plaquettes are truncated, not missing, at the left and right
+        // edges, so chains of X errors end on the top and bottom edges; one extra node stands
+        // for both of them
+        let mut graph = DecodingGraph::new(z_plaquettes.len() + 1);
+        let boundary = z_plaquettes.len();
+        graph.set_boundary(boundary);
+        let mut corrections = Vec::with_capacity(n);
+        for r in 0..d {
+            for c in 0..d {
+                let node = |plaquette: (isize, isize)| {
+                    z_plaquettes.iter().position(|&p| p == plaquette).unwrap_or(boundary)
+                };
+                let (a, b) = if (r + c).rem_euclid(2) == 1 {
+                    (node((r - 1, c - 1)), node((r, c)))
+                } else {
+                    (node((r - 1, c)), node((r, c - 1)))
+                };
+                if a == boundary && b == boundary {
+                    continue;
+                }
+                graph.add_edge(a, b, 1.0);
+                corrections.push((r * d + c) as usize);
+            }
+        }
+
+        let column: Vec<usize> = (0..distance).map(|r| r * distance).collect();
+        let row: Vec<usize> = (0..distance).collect();
+
+        Ok(Self {
+            distance,
+            subsystem,
+            x_gauges,
+            z_plaquettes,
+            graph,
+            corrections,
+            logical_x: PauliString::from_support(n, &column, Pauli::X),
+            logical_z: PauliString::from_support(n, &row, Pauli::Z),
+            coupling_graph,
+            placement: placement.to_vec(),
+            schedule,
+            correction_time: Vec::new(),
+        })
+    }
+
+    pub fn distance(&self) -> usize {
+        self.distance
+    }
+
+    /// Data qubits
+    pub fn num_qubits(&self) -> usize {
+        self.distance * self.distance
+    }
+
+    /// Data, flag and syndrome qubits together
+    pub fn num_layout_qubits(distance: usize) -> usize {
+        let num_x_gauges = (distance - 1) * (distance - 1) / 2 + (distance - 1);
+        distance * distance + distance * (distance - 1) + num_x_gauges
+    }
+
+    /// The underlying gauge group, e.g. for gauge fixing
+    pub fn as_subsystem(&self) -> &SubsystemCode {
+        &self.subsystem
+    }
+
+    pub fn logical_x(&self) -> &PauliString {
+        &self.logical_x
+    }
+
+    pub fn logical_z(&self) -> &PauliString {
+        &self.logical_z
+    }
+
+    pub fn coupling_graph(&self) -> &CouplingGraph {
+        &self.coupling_graph
+    }
+
+    /// Device qubit of each layout qubit
+    pub fn placement(&self) -> &[usize] {
+        &self.placement
+    }
+
+    /// One round of gauge measurements on device qubits, in two steps: every X gauge, then
+    /// every Z gauge. The X and Z gauges do not commute, but within a step the measurements
+    /// commute and share no ancillas, so each step runs in parallel, gate i of every circuit
+    /// in layer i. No qubit is in two gates of the same layer.
+    pub fn measurement_schedule(&self) -> &[Vec<GaugeMeasurement>] {
+        &self.schedule
+    }
+
+    /// Values of the X-type stabilizers on columns (c, c+1), from the gauge outcomes
+    pub fn column_pair_parities(&self, gauge_outcomes: &[bool]) -> Vec<bool> {
+        (0..self.distance as isize - 1)
+            .map(|strip| {
+                self.x_gauges
+                    .iter()
+                    .zip(gauge_outcomes)
+                    .filter(|((_, c), _)| *c == strip)
+                    .fold(false, |parity, (_, &outcome)| parity ^ outcome)
+            })
+            .collect()
+    }
+
+    /// Values of the Z-type stabilizers, from the gauge outcomes
+    pub fn z_plaquette_parities(&self, gauge_outcomes: &[bool]) -> Vec<bool> {
+        let d = self.distance as isize;
+        let zz_outcomes = &gauge_outcomes[self.x_gauges.len()..];
+        self.z_plaquettes
+            .iter()
+            .map(|&(r, c)| {
+                (c..c + 2)
+                    .filter(|column| (0..d).contains(column))
+                    .fold(false, |parity, column| parity ^ zz_outcomes[(r * d + column) as usize])
+            })
+            .collect()
+    }
+
+    /// The schedule on layout qubits
+    fn layout_schedule(distance: usize) -> Vec<Vec<GaugeMeasurement>> {
+        assert!(distance >= 3 && distance % 2 == 1, "the heavy-hex code needs an odd distance of at least 3");
+        let d = distance as isize;
+        let n = distance * distance;
+        let data = |r: isize, c: isize| (r * d + c) as usize;
+        let flag = |r: isize, c: isize| n + (r * d + c) as usize;
+
+        let mut x_step = Vec::new();
+        let mut gauge = 0;
+        for r in -1..d {
+            for c in 0..d - 1 {
+                if (r + c).rem_euclid(2) != 0 {
+                    continue;
+                }
+                let syndrome_qubit = n + distance * (distance - 1) + gauge;
+                let mut circuit = vec![CliffordGate::H(syndrome_qubit)];
+                let mut flag_qubits = Vec::new();
+                if r == -1 || r == d - 1 {
+                    let row = r.max(0);
+                    for column in [c, c + 1] {
+                        let target = data(row, column);
+                        circuit.push(CliffordGate::Cnot { control: syndrome_qubit, target });
+                    }
+                } else {
+                    // Copy the syndrome qubit onto both flags, spread each flag onto its two
+                    // data qubits and uncopy, so the flags return to |0⟩ without faults
+                    flag_qubits = vec![flag(r, c), flag(r, c + 1)];
+                    for &target in &flag_qubits {
+                        circuit.push(CliffordGate::Cnot { control: syndrome_qubit, target });
+                    }
+                    for column in [c, c + 1] {
+                        for row in [r, r + 1] {
+                            let (control, target) = (flag(r, column), data(row, column));
+                            circuit.push(CliffordGate::Cnot { control, target });
+                        }
+                    }
+                    for &target in &flag_qubits {
+                        circuit.push(CliffordGate::Cnot { control: syndrome_qubit, target });
+                    }
+                }
+                circuit.push(CliffordGate::H(syndrome_qubit));
+                x_step.push(GaugeMeasurement {
+                    gauge,
+                    syndrome_qubit,
+                    flag_qubits,
+                    circuit,
+                });
+                gauge += 1;
+            }
+        }
+
+        let mut z_step = Vec::new();
+        for r in 0..d - 1 {
+            for c in 0..d {
+                z_step.push(GaugeMeasurement {
+                    gauge,
+                    syndrome_qubit: flag(r, c),
+                    flag_qubits: Vec::new(),
+                    circuit: vec![
+                        CliffordGate::Cnot { control: data(r, c), target: flag(r, c) },
+                        CliffordGate::Cnot { control: data(r + 1, c), target: flag(r, c) },
+                    ],
+                });
+                gauge += 1;
+            }
+        }
+
+        vec![x_step, z_step]
+    }
+}
+
+impl CorrectionCode for HeavyHexCode {
+    fn encode(&self, data: &Qubit) -> Vec<Qubit> {
+        encode_frame(self.num_qubits(), data, &self.logical_x, &self.logical_z)
+    }
+
+    fn syndrome_measurement(&self, encoded_qubits: &mut Vec<Qubit>) -> Vec<bool> {
+        self.subsystem.measure_gauge(encoded_qubits)
+    }
+
+    /// Decodes from the gauge outcomes alone; the flag qubits of the schedule are not read
+    fn correct(&mut self, encoded_qubits: &mut Vec<Qubit>, syndromes: Vec<bool>) {
+        let start = Instant::now();
+
+        // Z errors: one Z per flagged column, on its top qubit, differs from the error by ZZ
+        // gauge operators only
+        let flagged_columns = lighter_repetition_pattern(&self.column_pair_parities(&syndromes));
+        for (c, _) in flagged_columns.iter().enumerate().filter(|(_, &flagged)| flagged) {
+            Pauli::Z.apply(&mut encoded_qubits[c]);
+        }
+
+        // X errors: match the Z plaquettes as in the surface code
+        let defects: Vec<usize> = self
+            .z_plaquette_parities(&syndromes)
+            .iter()
+            .enumerate()
+            .filter_map(|(node, &flagged)| flagged.then_some(node))
+            .collect();
+        for edge in self.graph.match_defects(&defects) {
+            Pauli::X.apply(&mut encoded_qubits[self.corrections[edge]]);
+        }
+
+        let duration = start.elapsed();
+        self.correction_time.push(duration);
+    }
+
+    fn decode(&self, encoded_qubits: &mut Vec<Qubit>) -> Qubit {
+        let frame = PauliString::from_frame(encoded_qubits);
+
+        let mut decoded_qubit = Qubit::frame_reference();
+        logical_pauli(&frame, &self.logical_x, &self.logical_z).apply(&mut decoded_qubit);
+
+        decoded_qubit
+    }
+
+    fn get_average_correction_time(&self) -> f64 {
+        average_duration(&self.correction_time)
+    }
+
+    fn logical_errors(&self, encoded_qubits: &[Qubit]) -> Vec<Pauli> {
+        let frame = PauliString::from_frame(encoded_qubits);
+        vec![logical_pauli(&frame, &self.logical_x, &self.logical_z)]
+    }
+
+    fn num_qubits(&self) -> usize {
+        HeavyHexCode::num_qubits(self)
+    }
+
+    fn num_logical_qubits(&self) -> usize {
+        1
+    }
+
+    fn stabilizer_generators(&self) -> Vec<PauliString> {
+        self.subsystem.stabilizers().to_vec()
+    }
+
+    fn logical_operators(&self) -> Vec<(PauliString, PauliString)> {
+        vec![(self.logical_x.clone(), self.logical_z.clone())]
+    }
+
+    /// One outcome per gauge generator
+    fn syndrome_length(&self) -> usize {
+        self.subsystem.syndrome_length()
+    }
+
+    fn parameters(&self) -> CodeParameters {
+        CodeParameters {
+            n: HeavyHexCode::num_qubits(self),
+            k: 1,
+            d: Some(self.distance),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gate_qubits(gate: &CliffordGate) -> Vec<usize> {
+        match *gate {
+            CliffordGate::H(q) | CliffordGate::S(q) | CliffordGate::SDag(q) => vec![q],
+            CliffordGate::Cnot { control, target } => vec![control, target],
+            CliffordGate::Cz(a, b) => vec![a, b],
+        }
+    }
+
+    /// Every two-qubit gate lies on a coupler, and within each layer of each step every
+    /// qubit is in at most one gate
+    fn assert_schedule_fits(code: &HeavyHexCode) {
+        for step in code.measurement_schedule() {
+            for measurement in step {
+                assert_eq!(code.coupling_graph().uncoupled_gate(&measurement.circuit), None);
+            }
+            let depth = step.iter().map(|measurement| measurement.circuit.len()).max().unwrap_or(0);
+            for layer in 0..depth {
+                let mut busy = vec![false; code.coupling_graph().num_qubits()];
+                for gate in step.iter().filter_map(|measurement| measurement.circuit.get(layer)) {
+                    for qubit in gate_qubits(gate) {
+                        assert!(!busy[qubit], "qubit {} twice in layer {}", qubit, layer);
+                        busy[qubit] = true;
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn schedule_uses_couplers_and_runs_in_parallel_layers() {
+        for distance in [3, 5, 7] {
+            let code = HeavyHexCode::new(distance);
+            assert!(code.coupling_graph().max_degree() <= 3);
+            assert_schedule_fits(&code);
+            let measured: Vec<usize> =
+                code.measurement_schedule().iter().flatten().map(|measurement| measurement.gauge).collect();
+            assert_eq!(measured, (0..code.as_subsystem().gauge_generators().len()).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn on_device_relabels_the_schedule_and_rejects_missing_couplers() {
+        let native = HeavyHexCode::new(3);
+        let layout_qubits = HeavyHexCode::num_layout_qubits(3);
+        // The native layout reversed and shifted onto a larger device
+        let placement: Vec<usize> = (0..layout_qubits).map(|q| 2 * layout_qubits - q).collect();
+        let mut device = CouplingGraph::new(2 * layout_qubits + 1);
+        for (a, b) in native.coupling_graph().edges() {
+            device.add_edge(placement[a], placement[b]);
+        }
+        let placed = HeavyHexCode::on_device(3, device.clone(), &placement).expect("every coupler is there");
+        assert_eq!(placed.placement(), placement);
+        assert_schedule_fits(&placed);
+
+        let (a, b) = native.coupling_graph().edges()[0];
+        let dropped = (placement[a].min(placement[b]), placement[a].max(placement[b]));
+        let mut missing = CouplingGraph::new(device.num_qubits());
+        for edge in device.edges().into_iter().filter(|&edge| edge != dropped) {
+            missing.add_edge(edge.0, edge.1);
+        }
+        match HeavyHexCode::on_device(3, missing, &placement) {
+            Err(CodeConstructionError::UncoupledQubits { first, second }) => {
+                assert_eq!((first.min(second), first.max(second)), dropped);
+            }
+            _ => panic!("a missing coupler must be reported"),
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use crate::correction_codes::{CodeConstructionError, CssCode};
+use crate::gf2::{add_into, BinaryMatrix};
+
+/// Largest kernel dimension whose codewords are enumerated to find a classical distance
+const MAX_ENUMERATED_KERNEL: usize = 20;
+
+impl CssCode {
+    /// The hypergraph product of two classical codes with check matrices H1 (m1×n1) and
+    /// H2 (m2×n2).
+    ///
+    /// Qubits are the n1·n2 pairs of bits followed by the m1·m2 pairs of checks, with
+    ///
+    /// ```text
+    ///   Hx = [ H1 ⊗ I(n2) │ I(m1) ⊗ H2ᵀ ]
+    ///   Hz = [ I(n1) ⊗ H2 │ H1ᵀ ⊗ I(m2) ]
+    /// ```
+    ///
+    /// The distance is the smallest of the distances of ker H1, ker H2, ker H1ᵀ and ker H2ᵀ,
+    /// ignoring trivial kernels, and is recorded when those kernels are small enough to
+    /// enumerate. Two repetition codes give the planar surface code.
+    pub fn hypergraph_product(h1: &BinaryMatrix, h2: &BinaryMatrix) -> Result<Self, CodeConstructionError> {
+        let (m1, n1) = (h1.num_rows(), h1.num_cols());
+        let (m2, n2) = (h2.num_rows(), h2.num_cols());
+
+        let hx = h1
+            .kron(&BinaryMatrix::identity(n2))
+            .augment(&BinaryMatrix::identity(m1).kron(&h2.transpose()));
+        let hz = BinaryMatrix::identity(n1)
+            .kron(h2)
+            .augment(&h1.transpose().kron(&BinaryMatrix::identity(m2)));
+        let code = Self::from_parity_checks(hx, hz)?;
+
+        let classical = [
+            classical_distance(h1),
+            classical_distance(h2),
+            classical_distance(&h1.transpose()),
+            classical_distance(&h2.transpose()),
+        ];
+        if classical.iter().all(Option::is_some) {
+            if let Some(distance) = classical.into_iter().flatten().flatten().min() {
+                return Ok(code.with_distance(distance));
+            }
+        }
+        Ok(code)
+    }
+
+    /// A bivariate bicycle code on 2·l·m qubits.
+    ///
+    /// With x = Sₗ ⊗ Iₘ and y = Iₗ ⊗ Sₘ, where S is the cyclic shift, the polynomials
+    /// `a` and `b` are given as lists of monomials (i, j) meaning xⁱyʲ, and
+    ///
+    /// ```text
+    ///   Hx = [ A │ B ]
+    ///   Hz = [ Bᵀ │ Aᵀ ]
+    /// ```
+    ///
+    /// A and B commute, so the checks always do. The distance is not computed.
+    pub fn bivariate_bicycle(
+        l: usize,
+        m: usize,
+        a: &[(usize, usize)],
+        b: &[(usize, usize)],
+    ) -> Result<Self, CodeConstructionError> {
+        let a = bivariate_polynomial(l, m, a);
+        let b = bivariate_polynomial(l, m, b);
+        let hx = a.augment(&b);
+        let hz = b.transpose().augment(&a.transpose());
+        Self::from_parity_checks(hx, hz)
+    }
+
+    /// The [[144,12,12]] gross code, with l = 12, m = 6, A = x³ + y + y² and B = y³ + x + x²
+    pub fn gross() -> Self {
+        Self::bivariate_bicycle(12, 6, &[(3, 0), (0, 1), (0, 2)], &[(0, 3), (1, 0), (2, 0)])
+            .expect("the gross code is a valid CSS code")
+            .with_distance(12)
+    }
+}
+
+/// The l·m × l·m matrix of a sum of monomials xⁱyʲ, indexing row and column (u, v) as u·m + v
+fn bivariate_polynomial(l: usize, m: usize, monomials: &[(usize, usize)]) -> BinaryMatrix {
+    let mut matrix = BinaryMatrix::zeros(l * m, l * m);
+    for u in 0..l {
+        for v in 0..m {
+            for &(i, j) in monomials {
+                let col = (u + i) % l * m + (v + j) % m;
+                let entry = matrix.get(u * m + v, col);
+                matrix.set(u * m + v, col, !entry);
+            }
+        }
+    }
+    matrix
+}
+
+/// Minimum weight of a nonzero codeword of ker `checks`, by enumerating the kernel.
+///
+/// `Some(None)` means the kernel is trivial and `None` that it is too large to enumerate.
+fn classical_distance(checks: &BinaryMatrix) -> Option<Option<usize>> {
+    let kernel = checks.kernel();
+    let dimension = kernel.num_rows();
+    if dimension > MAX_ENUMERATED_KERNEL {
+        return None;
+    }
+
+    // Walk the kernel in Gray-code order so that each step adds a single basis vector
+    let mut codeword = vec![false; checks.num_cols()];
+    let mut best = None;
+    for step in 1..1usize << dimension {
+        add_into(&mut codeword, kernel.row(step.trailing_zeros() as usize));
+        let weight = codeword.iter().filter(|&&bit| bit).count();
+        if best.is_none_or(|best| weight < best) {
+            best = Some(weight);
+        }
+    }
+    Some(best)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::correction_codes::CodeParameters;
+
+    fn hamming() -> BinaryMatrix {
+        BinaryMatrix::from_bits(&[
+            &[0, 0, 0, 1, 1, 1, 1],
+            &[0, 1, 1, 0, 0, 1, 1],
+            &[1, 0, 1, 0, 1, 0, 1],
+        ])
+    }
+
+    #[test]
+    fn gross_code_parameters() {
+        assert_eq!(CssCode::gross().parameters(), CodeParameters { n: 144, k: 12, d: Some(12) });
+    }
+
+    #[test]
+    fn hypergraph_products_of_hamming_and_repetition_codes() {
+        // n = 7·7 + 3·3, k = 4·4 + 0·0, and the transposed kernels are trivial
+        let product = CssCode::hypergraph_product(&hamming(), &hamming()).expect("a valid CSS code");
+        assert_eq!(product.parameters(), CodeParameters { n: 58, k: 16, d: Some(3) });
+
+        let repetition = BinaryMatrix::from_bits(&[&[1, 1, 0], &[0, 1, 1]]);
+        let surface = CssCode::hypergraph_product(&repetition, &repetition).expect("a valid CSS code");
+        assert_eq!(surface.parameters(), CodeParameters { n: 13, k: 1, d: Some(3) });
+
+        let mixed = CssCode::hypergraph_product(&hamming(), &repetition).expect("a valid CSS code");
+        assert_eq!(mixed.parameters(), CodeParameters { n: 27, k: 4, d: Some(3) });
+    }
+
+    #[test]
+    fn bivariate_bicycle_checks_commute() {
+        for (l, m, k) in [(6, 6, 12), (12, 6, 12)] {
+            let code = CssCode::bivariate_bicycle(l, m, &[(3, 0), (0, 1), (0, 2)], &[(0, 3), (1, 0), (2, 0)])
+                .expect("a valid CSS code");
+            assert!(code.hx().multiply(&code.hz().transpose()).is_zero());
+            assert_eq!(code.parameters().n, 2 * l * m);
+            assert_eq!(code.parameters().k, k, "l = {}, m = {}", l, m);
+        }
+    }
+}
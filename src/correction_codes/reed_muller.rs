@@ -0,0 +1,241 @@
+use crate::correction_codes::{CodeParameters, CorrectionCode, CssCode};
+use crate::gates::{CliffordGate, DiagonalGate};
+use crate::gf2::{add_into, BinaryMatrix};
+use crate::pauli::{Pauli, PauliString};
+use crate::qubit::Qubit;
+
+/// The [[15,1,3]] quantum Reed–Muller code, built from the punctured Reed–Muller codes.
+///
+/// Qubit q stands for the nonzero 4-bit vector q + 1. The four X checks are the coordinate
+/// functions, i.e. the punctured first-order code RM(1,4)*, and the ten Z checks add their
+/// pairwise products, giving the even part of RM(2,4)*. Every X check has weight 8, any two
+/// overlap on 4 qubits and any three on 2, which makes T on every qubit act on the code
+/// space as a logical gate.
+///
+/// The code is a `CssCode`; X and Z syndromes are extracted and corrected independently.
+pub struct ReedMullerCode {
+    css: CssCode,
+}
+
+impl ReedMullerCode {
+    pub fn new() -> Self {
+        let coordinates: Vec<Vec<bool>> = (0..4)
+            .map(|bit| (1..16usize).map(|point| point >> bit & 1 == 1).collect())
+            .collect();
+
+        let mut z_checks = coordinates.clone();
+        for (i, first) in coordinates.iter().enumerate() {
+            for second in &coordinates[i + 1..] {
+                z_checks.push(first.iter().zip(second).map(|(&a, &b)| a && b).collect());
+            }
+        }
+
+        let hx = BinaryMatrix::from_rows(15, coordinates);
+        let hz = BinaryMatrix::from_rows(15, z_checks);
+        let css = CssCode::from_parity_checks(hx, hz)
+            .expect("the quantum Reed–Muller code is a valid CSS code")
+            .with_distance(3);
+        Self { css }
+    }
+
+    pub fn num_qubits(&self) -> usize {
+        15
+    }
+
+    pub fn as_css(&self) -> &CssCode {
+        &self.css
+    }
+
+    /// T† on every qubit, which implements the logical T.
+    ///
+    /// |0̄⟩ is spread over words of weight 0 and 8 and |1̄⟩ over words of weight 7 and 15,
+    /// so T† on every qubit leaves |0̄⟩ alone and gives |1̄⟩ the phase ω⁻⁷ = ω⁻¹⁵ = ω.
+    pub fn transversal_t(&self) -> Vec<DiagonalGate> {
+        (0..self.num_qubits()).map(DiagonalGate::t_dagger).collect()
+    }
+
+    /// Qubit holding the unencoded input at the start of `encoding_circuit`; every other
+    /// qubit starts in |0⟩
+    pub fn data_qubit(&self) -> usize {
+        self.encoder().0
+    }
+
+    /// A CSS encoder: CNOTs copy the data qubit onto a logical X representative, then each
+    /// X check is added in superposition from a Hadamard on one of its qubits
+    pub fn encoding_circuit(&self) -> Vec<CliffordGate> {
+        self.encoder().1
+    }
+
+    /// Syndrome extraction with one ancilla per check, the ancilla of X check i being qubit
+    /// 15 + i and that of Z check j qubit 19 + j. Measuring the ancillas in the Z basis gives
+    /// the syndrome in the order of `syndrome_measurement`.
+    pub fn syndrome_extraction_circuit(&self) -> Vec<CliffordGate> {
+        let n = self.num_qubits();
+        let hx = self.css.hx();
+        let mut circuit = Vec::new();
+        for (check, row) in hx.rows().iter().enumerate() {
+            let ancilla = n + check;
+            circuit.push(CliffordGate::H(ancilla));
+            for qubit in (0..n).filter(|&q| row[q]) {
+                circuit.push(CliffordGate::Cnot { control: ancilla, target: qubit });
+            }
+            circuit.push(CliffordGate::H(ancilla));
+        }
+        for (check, row) in self.css.hz().rows().iter().enumerate() {
+            let ancilla = n + hx.num_rows() + check;
+            for qubit in (0..n).filter(|&q| row[q]) {
+                circuit.push(CliffordGate::Cnot { control: qubit, target: ancilla });
+            }
+        }
+        circuit
+    }
+
+    /// The data qubit and the encoding circuit
+    fn encoder(&self) -> (usize, Vec<CliffordGate>) {
+        let mut checks = self.css.hx().clone();
+        let pivots = checks.row_reduce();
+
+        // Clear the pivot qubits from X̄ so that they are free to start the checks
+        let mut logical = self.css.logical_x(0).x_bits().to_vec();
+        for (row, &pivot) in pivots.iter().enumerate() {
+            if logical[pivot] {
+                add_into(&mut logical, checks.row(row));
+            }
+        }
+        let data = logical.iter().position(|&bit| bit).expect("X̄ is not a product of X checks");
+
+        let mut circuit = Vec::new();
+        for qubit in (0..self.num_qubits()).filter(|&q| q != data && logical[q]) {
+            circuit.push(CliffordGate::Cnot { control: data, target: qubit });
+        }
+        for (row, &pivot) in pivots.iter().enumerate() {
+            circuit.push(CliffordGate::H(pivot));
+            for qubit in (0..self.num_qubits()).filter(|&q| q != pivot && checks.get(row, q)) {
+                circuit.push(CliffordGate::Cnot { control: pivot, target: qubit });
+            }
+        }
+        (data, circuit)
+    }
+}
+
+impl Default for ReedMullerCode {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CorrectionCode for ReedMullerCode {
+    fn encode(&self, data: &Qubit) -> Vec<Qubit> {
+        self.css.encode(data)
+    }
+
+    fn syndrome_measurement(&self, encoded_qubits: &mut Vec<Qubit>) -> Vec<bool> {
+        self.css.syndrome_measurement(encoded_qubits)
+    }
+
+    fn correct(&mut self, encoded_qubits: &mut Vec<Qubit>, syndromes: Vec<bool>) {
+        self.css.correct(encoded_qubits, syndromes)
+    }
+
+    fn decode(&self, encoded_qubits: &mut Vec<Qubit>) -> Qubit {
+        self.css.decode(encoded_qubits)
+    }
+
+    fn get_average_correction_time(&self) -> f64 {
+        self.css.get_average_correction_time()
+    }
+
+    fn logical_errors(&self, encoded_qubits: &[Qubit]) -> Vec<Pauli> {
+        self.css.logical_errors(encoded_qubits)
+    }
+
+    fn num_qubits(&self) -> usize {
+        15
+    }
+
+    fn num_logical_qubits(&self) -> usize {
+        1
+    }
+
+    fn stabilizer_generators(&self) -> Vec<PauliString> {
+        self.css.stabilizer_generators()
+    }
+
+    fn logical_operators(&self) -> Vec<(PauliString, PauliString)> {
+        self.css.logical_operators()
+    }
+
+    fn parameters(&self) -> CodeParameters {
+        self.css.parameters()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::correction_codes::stabilizer::symplectic_vector as symplectic;
+    use crate::gates::conjugate_through;
+    use crate::gf2::EchelonBasis;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn transversal_t_dagger_is_logical_t() {
+        let code = ReedMullerCode::new();
+        assert_eq!(code.as_css().logical_diagonal_phase(&code.transversal_t()), Some(1));
+    }
+
+    #[test]
+    fn encoding_circuit_maps_the_input_onto_the_code() {
+        let code = ReedMullerCode::new();
+        let (n, data, circuit) = (code.num_qubits(), code.data_qubit(), code.encoding_circuit());
+        let mut stabilizers = EchelonBasis::new();
+        for generator in code.stabilizer_generators() {
+            stabilizers.insert(&symplectic(&generator));
+        }
+
+        // Z on each ancilla becomes a stabilizer, and together they generate the group
+        let mut images = EchelonBasis::new();
+        for ancilla in (0..n).filter(|&q| q != data) {
+            let z = PauliString::from_support(n, &[ancilla], Pauli::Z);
+            let image = symplectic(&conjugate_through(&circuit, &z));
+            assert!(stabilizers.contains(&image), "Z on ancilla {}", ancilla);
+            assert!(images.insert(&image), "Z on ancilla {}", ancilla);
+        }
+        assert_eq!(images.rank(), n - 1);
+
+        // X and Z on the data qubit become X̄ and Z̄, up to stabilizers
+        let logicals = [(Pauli::X, code.as_css().logical_x(0)), (Pauli::Z, code.as_css().logical_z(0))];
+        for (pauli, logical) in logicals {
+            let image = conjugate_through(&circuit, &PauliString::from_support(n, &[data], pauli));
+            assert!(stabilizers.contains(&symplectic(&image.product(logical))), "{:?}", pauli);
+        }
+    }
+
+    #[test]
+    fn syndrome_extraction_circuit_reads_the_syndrome() {
+        let code = ReedMullerCode::new();
+        let n = code.num_qubits();
+        let circuit = code.syndrome_extraction_circuit();
+        let width = n + code.as_css().hx().num_rows() + code.as_css().hz().num_rows();
+        let mut rng = StdRng::seed_from_u64(34);
+        for _ in 0..500 {
+            let mut error = PauliString::identity(n);
+            for qubit in 0..n {
+                error.set(qubit, [Pauli::I, Pauli::X, Pauli::Y, Pauli::Z][rng.gen_range(0..4)]);
+            }
+            let mut encoded = code.encode(&Qubit::new());
+            error.apply_to(&mut encoded);
+            let expected = code.syndrome_measurement(&mut encoded);
+
+            // An ancilla measured in the Z basis flips when the error reaches it as X or Y
+            let mut padded = PauliString::identity(width);
+            for qubit in 0..n {
+                padded.set(qubit, error.get(qubit));
+            }
+            let pushed = conjugate_through(&circuit, &padded);
+            let outcomes: Vec<bool> = (n..width).map(|ancilla| pushed.get(ancilla).x_bit()).collect();
+            assert_eq!(outcomes, expected, "{}", error);
+        }
+    }
+}
@@ -0,0 +1,521 @@
+use crate::correction_codes::{
+    average_duration, encode_frame, logical_pauli, CodeConstructionError, CodeParameters, CorrectionCode,
+};
+use crate::gates::CliffordGate;
+use crate::gf2::EchelonBasis;
+use crate::pauli::{Pauli, PauliString};
+use crate::qubit::Qubit;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// A general stabilizer code given by its generators as Pauli strings.
+///
+/// The generators are brought to Gottesman's standard form, which yields the logical
+/// operators and an encoding circuit without any hand-written code. Physical qubits are
+/// tracked as a Pauli frame, as in `CssCode`.
+///
+/// `correct` looks the full syndrome up and undoes any single-qubit X, Y or Z error.
+pub struct StabilizerCode {
+    generators: Vec<PauliString>,
+    logical_x: Vec<PauliString>,
+    logical_z: Vec<PauliString>,
+    data_qubits: Vec<usize>,
+    encoding_circuit: Vec<CliffordGate>,
+    /// Known distance, set by constructors that can derive it
+    distance: Option<usize>,
+    corrections: HashMap<Vec<bool>, (usize, Pauli)>,
+    correction_time: Vec<Duration>,
+}
+
+impl StabilizerCode {
+    /// Parses the generators, e.g. `&["XZZXI", "IXZZX", "XIXZZ", "ZXIXZ"]`, and checks
+    /// that they commute and are independent
+    pub fn from_generators(generators: &[&str]) -> Result<Self, CodeConstructionError> {
+        let parsed = generators
+            .iter()
+            .map(|generator| {
+                generator
+                    .parse::<PauliString>()
+                    .map_err(|_| CodeConstructionError::InvalidPauliString(generator.to_string()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Self::from_pauli_strings(parsed)
+    }
+
+    pub fn from_pauli_strings(generators: Vec<PauliString>) -> Result<Self, CodeConstructionError> {
+        let n = generators.first().ok_or(CodeConstructionError::NoGenerators)?.len();
+        for (index, generator) in generators.iter().enumerate() {
+            if generator.len() != n {
+                return Err(CodeConstructionError::LengthMismatch {
+                    generator: index,
+                    expected: n,
+                    found: generator.len(),
+                });
+            }
+        }
+        for (first, a) in generators.iter().enumerate() {
+            for (second, b) in generators.iter().enumerate().skip(first + 1) {
+                if !a.commutes_with(b) {
+                    return Err(CodeConstructionError::NonCommutingGenerators { first, second });
+                }
+            }
+        }
+        let mut span = EchelonBasis::new();
+        for (index, generator) in generators.iter().enumerate() {
+            if !span.insert(&symplectic_vector(generator)) {
+                return Err(CodeConstructionError::DependentGenerators { generator: index });
+            }
+        }
+        if generators.len() >= n {
+            return Err(CodeConstructionError::NoLogicalQubits);
+        }
+
+        let form = StandardForm::new(&generators);
+        let corrections = single_qubit_lookup(&generators);
+
+        Ok(Self {
+            logical_x: form.logical_x(),
+            logical_z: form.logical_z(),
+            data_qubits: form.data_qubits(),
+            encoding_circuit: form.encoding_circuit(),
+            distance: None,
+            generators,
+            corrections,
+            correction_time: Vec::new(),
+        })
+    }
+
+    /// The [[5,1,3]] perfect code, the smallest code correcting any single-qubit error
+    pub fn five_qubit() -> Self {
+        Self::from_generators(&["XZZXI", "IXZZX", "XIXZZ", "ZXIXZ"])
+            .expect("the five-qubit code is a valid stabilizer code")
+            .with_distance(3)
+    }
+
+    /// Records a distance known from the construction
+    pub(crate) fn with_distance(mut self, distance: usize) -> Self {
+        self.distance = Some(distance);
+        self
+    }
+
+    pub fn num_qubits(&self) -> usize {
+        self.generators[0].len()
+    }
+
+    pub fn num_logical_qubits(&self) -> usize {
+        self.logical_x.len()
+    }
+
+    /// [[n, k, d]], with the distance only when the constructor knows it
+    pub fn parameters(&self) -> CodeParameters {
+        CodeParameters {
+            n: self.num_qubits(),
+            k: self.num_logical_qubits(),
+            d: self.distance,
+        }
+    }
+
+    pub fn generators(&self) -> &[PauliString] {
+        &self.generators
+    }
+
+    pub fn logical_x(&self, logical_qubit: usize) -> &PauliString {
+        &self.logical_x[logical_qubit]
+    }
+
+    pub fn logical_z(&self, logical_qubit: usize) -> &PauliString {
+        &self.logical_z[logical_qubit]
+    }
+
+    /// Qubits holding the unencoded logical states at the start of `encoding_circuit`;
+    /// every other qubit starts in |0⟩
+    pub fn data_qubits(&self) -> &[usize] {
+        &self.data_qubits
+    }
+
+    /// A Clifford circuit mapping the input on `data_qubits` to the code space, up to
+    /// Pauli corrections of the signs
+    pub fn encoding_circuit(&self) -> &[CliffordGate] {
+        &self.encoding_circuit
+    }
+}
+
+impl CorrectionCode for StabilizerCode {
+    fn encode(&self, data: &Qubit) -> Vec<Qubit> {
+        encode_frame(self.num_qubits(), data, &self.logical_x[0], &self.logical_z[0])
+    }
+
+    fn syndrome_measurement(&self, encoded_qubits: &mut Vec<Qubit>) -> Vec<bool> {
+        let frame = PauliString::from_frame(encoded_qubits);
+        self.generators
+            .iter()
+            .map(|generator| !frame.commutes_with(generator))
+            .collect()
+    }
+
+    fn correct(&mut self, encoded_qubits: &mut Vec<Qubit>, syndromes: Vec<bool>) {
+        let start = Instant::now();
+
+        if let Some(&(qubit, pauli)) = self.corrections.get(&syndromes) {
+            pauli.apply(&mut encoded_qubits[qubit]);
+        }
+
+        let duration = start.elapsed();
+        self.correction_time.push(duration);
+    }
+
+    fn decode(&self, encoded_qubits: &mut Vec<Qubit>) -> Qubit {
+        let frame = PauliString::from_frame(encoded_qubits);
+
+        let mut decoded_qubit = Qubit::frame_reference();
+        logical_pauli(&frame, &self.logical_x[0], &self.logical_z[0]).apply(&mut decoded_qubit);
+
+        decoded_qubit
+    }
+
+    fn get_average_correction_time(&self) -> f64 {
+        average_duration(&self.correction_time)
+    }
+
+    fn logical_errors(&self, encoded_qubits: &[Qubit]) -> Vec<Pauli> {
+        let frame = PauliString::from_frame(encoded_qubits);
+        self.logical_x
+            .iter()
+            .zip(&self.logical_z)
+            .map(|(x, z)| logical_pauli(&frame, x, z))
+            .collect()
+    }
+
+    fn num_qubits(&self) -> usize {
+        StabilizerCode::num_qubits(self)
+    }
+
+    fn num_logical_qubits(&self) -> usize {
+        StabilizerCode::num_logical_qubits(self)
+    }
+
+    fn stabilizer_generators(&self) -> Vec<PauliString> {
+        self.generators.clone()
+    }
+
+    fn logical_operators(&self) -> Vec<(PauliString, PauliString)> {
+        self.logical_x.iter().cloned().zip(self.logical_z.iter().cloned()).collect()
+    }
+
+    fn parameters(&self) -> CodeParameters {
+        StabilizerCode::parameters(self)
+    }
+}
+
+/// The operator as a single 2n-bit vector (x | z)
+pub(super) fn symplectic_vector(operator: &PauliString) -> Vec<bool> {
+    let mut vector = operator.x_bits().to_vec();
+    vector.extend_from_slice(operator.z_bits());
+    vector
+}
+
+/// Maps the syndrome of every single-qubit X, Y and Z error to that error, keeping the
+/// first one found when several share a syndrome
+pub(super) fn single_qubit_lookup(generators: &[PauliString]) -> HashMap<Vec<bool>, (usize, Pauli)> {
+    let n = generators[0].len();
+    let mut lookup = HashMap::new();
+    for qubit in 0..n {
+        for pauli in [Pauli::X, Pauli::Z, Pauli::Y] {
+            let error = PauliString::from_support(n, &[qubit], pauli);
+            let syndrome: Vec<bool> = generators.iter().map(|g| !error.commutes_with(g)).collect();
+            if syndrome.iter().any(|&bit| bit) {
+                lookup.entry(syndrome).or_insert((qubit, pauli));
+            }
+        }
+    }
+    lookup
+}
+
+/// Generators in Gottesman's standard form, with qubits reordered so that
+///
+/// ```text
+///   ⎡ I A1 A2 │ B 0 C ⎤  r rows
+///   ⎣ 0 0  0  │ D I E ⎦  n-k-r rows
+/// ```
+///
+/// where column blocks have widths r, n-k-r and k.
+struct StandardForm {
+    x: Vec<Vec<bool>>,
+    z: Vec<Vec<bool>>,
+    /// Original index of the qubit in each column
+    order: Vec<usize>,
+    rank_x: usize,
+}
+
+impl StandardForm {
+    fn new(generators: &[PauliString]) -> Self {
+        let mut form = Self {
+            x: generators.iter().map(|g| g.x_bits().to_vec()).collect(),
+            z: generators.iter().map(|g| g.z_bits().to_vec()).collect(),
+            order: (0..generators[0].len()).collect(),
+            rank_x: 0,
+        };
+        let rows = generators.len();
+        let n = form.order.len();
+
+        // Gaussian elimination of the X block, moving pivots into the leading columns
+        while let Some((row, col)) = form.find_pivot(true, form.rank_x, rows, form.rank_x, n) {
+            let pivot = form.rank_x;
+            form.swap_rows(row, pivot);
+            form.swap_columns(col, pivot);
+            form.eliminate(true, pivot);
+            form.rank_x += 1;
+        }
+
+        // The remaining rows are Z-only; eliminate them on the next block of columns, also
+        // clearing that block from the top rows (which leaves their X part untouched)
+        for pivot in form.rank_x..rows {
+            let (row, col) = form
+                .find_pivot(false, pivot, rows, pivot, n)
+                .expect("independent generators have full rank");
+            form.swap_rows(row, pivot);
+            form.swap_columns(col, pivot);
+            form.eliminate(false, pivot);
+        }
+
+        form
+    }
+
+    /// First (row, column) with a 1 in the chosen block, scanning column by column
+    fn find_pivot(
+        &self,
+        x_block: bool,
+        row_start: usize,
+        row_end: usize,
+        col_start: usize,
+        col_end: usize,
+    ) -> Option<(usize, usize)> {
+        let block = if x_block { &self.x } else { &self.z };
+        (col_start..col_end).find_map(|col| {
+            (row_start..row_end)
+                .find(|&row| block[row][col])
+                .map(|row| (row, col))
+        })
+    }
+
+    fn swap_rows(&mut self, a: usize, b: usize) {
+        self.x.swap(a, b);
+        self.z.swap(a, b);
+    }
+
+    fn swap_columns(&mut self, a: usize, b: usize) {
+        for row in self.x.iter_mut().chain(self.z.iter_mut()) {
+            row.swap(a, b);
+        }
+        self.order.swap(a, b);
+    }
+
+    /// Clears column `pivot` of the chosen block in every row except row `pivot`
+    fn eliminate(&mut self, x_block: bool, pivot: usize) {
+        let pivot_x = self.x[pivot].clone();
+        let pivot_z = self.z[pivot].clone();
+        for row in 0..self.x.len() {
+            let hit = if x_block { self.x[row][pivot] } else { self.z[row][pivot] };
+            if row != pivot && hit {
+                for (bit, &p) in self.x[row].iter_mut().zip(&pivot_x) {
+                    *bit ^= p;
+                }
+                for (bit, &p) in self.z[row].iter_mut().zip(&pivot_z) {
+                    *bit ^= p;
+                }
+            }
+        }
+    }
+
+    fn num_qubits(&self) -> usize {
+        self.order.len()
+    }
+
+    fn num_generators(&self) -> usize {
+        self.x.len()
+    }
+
+    /// Builds an operator from bits indexed by column, relabelled to the original qubits
+    fn operator(&self, x: &[bool], z: &[bool]) -> PauliString {
+        let n = self.num_qubits();
+        let mut operator = PauliString::identity(n);
+        for col in 0..n {
+            operator.set(self.order[col], Pauli::from_bits(x[col], z[col]));
+        }
+        operator
+    }
+
+    /// X̄ⱼ = (0 Eᵀ I │ Cᵀ 0 0)
+    fn logical_x(&self) -> Vec<PauliString> {
+        let (n, m, r) = (self.num_qubits(), self.num_generators(), self.rank_x);
+        (m..n)
+            .map(|logical_col| {
+                let mut x = vec![false; n];
+                let mut z = vec![false; n];
+                for (row, bit) in x.iter_mut().enumerate().take(m).skip(r) {
+                    *bit = self.z[row][logical_col];
+                }
+                x[logical_col] = true;
+                for (row, bit) in z.iter_mut().enumerate().take(r) {
+                    *bit = self.z[row][logical_col];
+                }
+                self.operator(&x, &z)
+            })
+            .collect()
+    }
+
+    /// Z̄ⱼ = (0 0 0 │ A2ᵀ 0 I)
+    fn logical_z(&self) -> Vec<PauliString> {
+        let (n, m, r) = (self.num_qubits(), self.num_generators(), self.rank_x);
+        (m..n)
+            .map(|logical_col| {
+                let x = vec![false; n];
+                let mut z = vec![false; n];
+                for (row, bit) in z.iter_mut().enumerate().take(r) {
+                    *bit = self.x[row][logical_col];
+                }
+                z[logical_col] = true;
+                self.operator(&x, &z)
+            })
+            .collect()
+    }
+
+    fn data_qubits(&self) -> Vec<usize> {
+        self.order[self.num_generators()..].to_vec()
+    }
+
+    /// Gottesman's encoder: controlled logical X's from the data qubits onto the Z-only
+    /// block, then each of the first r generators applied controlled on a |+⟩ qubit
+    fn encoding_circuit(&self) -> Vec<CliffordGate> {
+        let (n, m, r) = (self.num_qubits(), self.num_generators(), self.rank_x);
+        let q = |col: usize| self.order[col];
+        let mut circuit = Vec::new();
+
+        for logical_col in m..n {
+            for row in r..m {
+                if self.z[row][logical_col] {
+                    circuit.push(CliffordGate::Cnot { control: q(logical_col), target: q(row) });
+                }
+            }
+        }
+
+        for row in 0..r {
+            // A Y on the control needs a phase of i on its |1⟩ branch, and so does each Y
+            // target, which is built from CNOT then CZ and so applies iY
+            let y_targets = (0..n)
+                .filter(|&col| col != row && self.x[row][col] && self.z[row][col])
+                .count();
+            circuit.push(CliffordGate::H(q(row)));
+            if (self.z[row][row] as usize + y_targets) % 2 == 1 {
+                circuit.push(CliffordGate::S(q(row)));
+            }
+            for col in (0..n).filter(|&col| col != row) {
+                if self.x[row][col] {
+                    circuit.push(CliffordGate::Cnot { control: q(row), target: q(col) });
+                }
+                if self.z[row][col] {
+                    circuit.push(CliffordGate::Cz(q(row), q(col)));
+                }
+            }
+        }
+
+        circuit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gates::conjugate_through;
+
+    /// Every logical commutes with every generator, and X̄ᵢ anticommutes with Z̄ⱼ exactly
+    /// when i = j
+    fn assert_symplectic_logicals(code: &StabilizerCode) {
+        let k = code.num_logical_qubits();
+        for i in 0..k {
+            let (x, z) = (code.logical_x(i), code.logical_z(i));
+            assert!(code.generators().iter().all(|g| g.commutes_with(x) && g.commutes_with(z)));
+            for j in 0..k {
+                assert_eq!(x.commutes_with(code.logical_z(j)), i != j, "X̄{} and Z̄{}", i, j);
+                assert!(x.commutes_with(code.logical_x(j)) && z.commutes_with(code.logical_z(j)));
+            }
+        }
+    }
+
+    /// The encoder maps Z on each ancilla into the stabilizer group, independently, and X and
+    /// Z on each data qubit to its logical operators up to stabilizers, all up to sign
+    fn assert_encoder(code: &StabilizerCode) {
+        let n = code.num_qubits();
+        let mut stabilizers = EchelonBasis::new();
+        for generator in code.generators() {
+            stabilizers.insert(&symplectic_vector(generator));
+        }
+        let circuit = code.encoding_circuit();
+        let mut images = EchelonBasis::new();
+        for ancilla in (0..n).filter(|qubit| !code.data_qubits().contains(qubit)) {
+            let z = PauliString::from_support(n, &[ancilla], Pauli::Z);
+            let image = symplectic_vector(&conjugate_through(circuit, &z));
+            assert!(stabilizers.contains(&image), "Z on ancilla {}", ancilla);
+            assert!(images.insert(&image), "Z on ancilla {}", ancilla);
+        }
+        assert_eq!(images.rank(), code.generators().len());
+        for (logical, &data) in code.data_qubits().iter().enumerate() {
+            let logicals = [(Pauli::X, code.logical_x(logical)), (Pauli::Z, code.logical_z(logical))];
+            for (pauli, expected) in logicals {
+                let image = conjugate_through(circuit, &PauliString::from_support(n, &[data], pauli));
+                let residue = symplectic_vector(&image.product(expected));
+                assert!(stabilizers.contains(&residue), "{:?} on data qubit {}", pauli, data);
+            }
+        }
+    }
+
+    #[test]
+    fn standard_form_logicals_of_the_five_qubit_code() {
+        let code = StabilizerCode::five_qubit();
+        assert_eq!(code.num_logical_qubits(), 1);
+        assert_eq!(code.parameters(), CodeParameters { n: 5, k: 1, d: Some(3) });
+        assert_symplectic_logicals(&code);
+    }
+
+    #[test]
+    fn encoding_circuits_map_the_input_onto_the_code() {
+        assert_encoder(&StabilizerCode::five_qubit());
+        for generators in [&["XZZXI", "IXZZX"][..], &["YYYY", "XXXX"][..], &["XZZXI", "IXZZX", "XIXZZ"][..]] {
+            let code = StabilizerCode::from_generators(generators).expect("valid generators");
+            assert_encoder(&code);
+        }
+    }
+
+    #[test]
+    fn standard_form_logicals_with_y_generators_and_several_logical_qubits() {
+        for (generators, k) in [
+            (&["XXXX", "ZZZZ"][..], 2),
+            (&["YYYY", "XXXX"][..], 2),
+            (&["XZZXI", "IXZZX"][..], 3),
+            (&["ZZIII", "IZZII", "IIZZI", "IIIZZ"][..], 1),
+        ] {
+            let code = StabilizerCode::from_generators(generators).expect("valid generators");
+            assert_eq!(code.num_logical_qubits(), k, "{:?}", generators);
+            assert_eq!(code.data_qubits().len(), k);
+            assert_symplectic_logicals(&code);
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_generators() {
+        let error = |generators: &[&str]| StabilizerCode::from_generators(generators).err();
+        assert_eq!(error(&["XZ", "ZX"]), Some(CodeConstructionError::NoLogicalQubits));
+        assert_eq!(
+            error(&["XX", "ZI"]),
+            Some(CodeConstructionError::NonCommutingGenerators { first: 0, second: 1 })
+        );
+        assert_eq!(
+            error(&["XXI", "IXX", "XIX"]),
+            Some(CodeConstructionError::DependentGenerators { generator: 2 })
+        );
+        assert_eq!(
+            error(&["XXA"]),
+            Some(CodeConstructionError::InvalidPauliString("XXA".to_string()))
+        );
+    }
+}
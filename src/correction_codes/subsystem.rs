@@ -0,0 +1,372 @@
+use super::stabilizer::{single_qubit_lookup, symplectic_vector};
+use crate::correction_codes::{
+    average_duration, encode_frame, logical_pauli, CodeConstructionError, CodeParameters, CorrectionCode,
+    StabilizerCode,
+};
+use crate::gates::CliffordGate;
+use crate::gf2::{BinaryMatrix, EchelonBasis};
+use crate::pauli::{Pauli, PauliString};
+use crate::qubit::Qubit;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// A subsystem code given by the generators of its gauge group.
+///
+/// The gauge generators need not commute. The stabilizers are the centre of the gauge group,
+/// each recorded as a product of gauge generators, so their values are inferred from gauge
+/// outcomes rather than measured directly. Bare logical operators commute with the whole
+/// gauge group; the remaining degrees of freedom are gauge qubits that carry no information.
+///
+/// `syndrome_measurement` returns one outcome per gauge generator. Outcomes of
+/// non-commuting gauge operators are random, so each round draws them uniformly among the
+/// assignments whose products reproduce the stabilizer values. `correct` infers the
+/// stabilizers and undoes any single-qubit X, Y or Z error.
+pub struct SubsystemCode {
+    gauge_generators: Vec<PauliString>,
+    stabilizers: Vec<PauliString>,
+    /// Gauge generators whose product is each stabilizer
+    stabilizer_products: Vec<Vec<usize>>,
+    /// Basis of the gauge outcome patterns that leave every stabilizer value unchanged
+    gauge_freedom: BinaryMatrix,
+    num_gauge_qubits: usize,
+    logical_x: Vec<PauliString>,
+    logical_z: Vec<PauliString>,
+    /// Known distance, set by constructors that can derive it
+    distance: Option<usize>,
+    corrections: HashMap<Vec<bool>, (usize, Pauli)>,
+    correction_time: Vec<Duration>,
+}
+
+/// How one gauge generator is measured with ancilla qubits on hardware.
+///
+/// Every ancilla starts in |0⟩, `circuit` runs, and each ancilla is then measured in the Z
+/// basis. The outcome of `syndrome_qubit` is the gauge outcome. Flag qubits end in |0⟩ unless
+/// a fault on the ancillas has spread to more than one data qubit, which a decoder can use to
+/// tell such faults apart from single data errors.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GaugeMeasurement {
+    /// Index of the gauge generator
+    pub gauge: usize,
+    pub syndrome_qubit: usize,
+    pub flag_qubits: Vec<usize>,
+    pub circuit: Vec<CliffordGate>,
+}
+
+impl SubsystemCode {
+    /// Parses the gauge generators, e.g. `&["XXI", "IZZ"]`
+    pub fn from_generators(generators: &[&str]) -> Result<Self, CodeConstructionError> {
+        let parsed = generators
+            .iter()
+            .map(|generator| {
+                generator
+                    .parse::<PauliString>()
+                    .map_err(|_| CodeConstructionError::InvalidPauliString(generator.to_string()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Self::from_pauli_strings(parsed)
+    }
+
+    pub fn from_pauli_strings(generators: Vec<PauliString>) -> Result<Self, CodeConstructionError> {
+        let n = generators.first().ok_or(CodeConstructionError::NoGenerators)?.len();
+        for (index, generator) in generators.iter().enumerate() {
+            if generator.len() != n {
+                return Err(CodeConstructionError::LengthMismatch {
+                    generator: index,
+                    expected: n,
+                    found: generator.len(),
+                });
+            }
+        }
+        let m = generators.len();
+
+        // A product of gauge generators lies in the centre exactly when it commutes with every
+        // generator, i.e. when its combination vector is in the kernel of the commutation matrix
+        let commutation = BinaryMatrix::from_rows(
+            m,
+            generators
+                .iter()
+                .map(|a| generators.iter().map(|b| !a.commutes_with(b)).collect())
+                .collect(),
+        );
+        let central_combinations = commutation.kernel();
+
+        let mut stabilizer_span = EchelonBasis::new();
+        let mut stabilizers = Vec::new();
+        let mut stabilizer_products = Vec::new();
+        for combination in central_combinations.rows() {
+            let factors: Vec<usize> = (0..m).filter(|&i| combination[i]).collect();
+            let mut product = PauliString::identity(n);
+            for &factor in &factors {
+                product.multiply(&generators[factor]);
+            }
+            if stabilizer_span.insert(&symplectic_vector(&product)) {
+                stabilizers.push(product);
+                stabilizer_products.push(factors);
+            }
+        }
+
+        // Products equal to the identity also constrain the outcomes, so every central
+        // combination is kept here and not only the independent stabilizers
+        let gauge_freedom = central_combinations.kernel();
+
+        let mut gauge_span = EchelonBasis::new();
+        for generator in &generators {
+            gauge_span.insert(&symplectic_vector(generator));
+        }
+        let num_gauge_qubits = (gauge_span.rank() - stabilizers.len()) / 2;
+
+        let (logical_x, logical_z) = bare_logicals(&generators, &stabilizers);
+        if logical_x.is_empty() {
+            return Err(CodeConstructionError::NoLogicalQubits);
+        }
+
+        Ok(Self {
+            corrections: single_qubit_lookup(&stabilizers),
+            gauge_generators: generators,
+            stabilizers,
+            stabilizer_products,
+            gauge_freedom,
+            num_gauge_qubits,
+            logical_x,
+            logical_z,
+            distance: None,
+            correction_time: Vec::new(),
+        })
+    }
+
+    /// Records a distance known from the construction
+    pub(crate) fn with_distance(mut self, distance: usize) -> Self {
+        self.distance = Some(distance);
+        self
+    }
+
+    pub fn num_qubits(&self) -> usize {
+        self.gauge_generators[0].len()
+    }
+
+    pub fn num_logical_qubits(&self) -> usize {
+        self.logical_x.len()
+    }
+
+    /// [[n, k, d]], with the distance only when the constructor knows it
+    pub fn parameters(&self) -> CodeParameters {
+        CodeParameters {
+            n: self.num_qubits(),
+            k: self.num_logical_qubits(),
+            d: self.distance,
+        }
+    }
+
+    pub fn num_gauge_qubits(&self) -> usize {
+        self.num_gauge_qubits
+    }
+
+    pub fn gauge_generators(&self) -> &[PauliString] {
+        &self.gauge_generators
+    }
+
+    /// Independent generators of the stabilizer group, the centre of the gauge group
+    pub fn stabilizers(&self) -> &[PauliString] {
+        &self.stabilizers
+    }
+
+    /// Indices of the gauge generators whose product is stabilizer `stabilizer`
+    pub fn stabilizer_product(&self, stabilizer: usize) -> &[usize] {
+        &self.stabilizer_products[stabilizer]
+    }
+
+    /// Bare logical X, which commutes with every gauge operator
+    pub fn logical_x(&self, logical_qubit: usize) -> &PauliString {
+        &self.logical_x[logical_qubit]
+    }
+
+    /// Bare logical Z, which commutes with every gauge operator
+    pub fn logical_z(&self, logical_qubit: usize) -> &PauliString {
+        &self.logical_z[logical_qubit]
+    }
+
+    /// Outcomes of measuring every gauge generator on a Pauli frame, randomised over the
+    /// patterns that are consistent with the stabilizer values
+    pub fn measure_gauge(&self, encoded_qubits: &[Qubit]) -> Vec<bool> {
+        let frame = PauliString::from_frame(encoded_qubits);
+        let mut outcomes: Vec<bool> = self
+            .gauge_generators
+            .iter()
+            .map(|generator| !frame.commutes_with(generator))
+            .collect();
+        for pattern in self.gauge_freedom.rows() {
+            if rand::random::<bool>() {
+                for (outcome, &flip) in outcomes.iter_mut().zip(pattern) {
+                    *outcome ^= flip;
+                }
+            }
+        }
+        outcomes
+    }
+
+    /// Stabilizer values implied by one outcome per gauge generator
+    pub fn infer_stabilizers(&self, gauge_outcomes: &[bool]) -> Vec<bool> {
+        self.stabilizer_products
+            .iter()
+            .map(|factors| factors.iter().fold(false, |parity, &g| parity ^ gauge_outcomes[g]))
+            .collect()
+    }
+
+    /// The stabilizer code obtained by promoting the chosen gauge generators to stabilizers.
+    ///
+    /// The chosen generators must commute with each other; generators that are products of
+    /// the stabilizers or of each other are dropped. When every gauge qubit is fixed, the
+    /// logical operators of the new code are dressed logicals of this one, so a known
+    /// distance carries over. Gauge qubits left unfixed become logical qubits, and the
+    /// distance is then left unknown.
+    pub fn gauge_fix(&self, fixed: &[usize]) -> Result<StabilizerCode, CodeConstructionError> {
+        for (position, &first) in fixed.iter().enumerate() {
+            for &second in &fixed[position + 1..] {
+                if !self.gauge_generators[first].commutes_with(&self.gauge_generators[second]) {
+                    return Err(CodeConstructionError::NonCommutingGenerators { first, second });
+                }
+            }
+        }
+
+        let mut span = EchelonBasis::new();
+        let generators = self
+            .stabilizers
+            .iter()
+            .chain(fixed.iter().map(|&g| &self.gauge_generators[g]))
+            .filter(|generator| span.insert(&symplectic_vector(generator)))
+            .cloned()
+            .collect();
+        let code = StabilizerCode::from_pauli_strings(generators)?;
+        Ok(match self.distance {
+            Some(distance) if code.num_logical_qubits() == self.num_logical_qubits() => {
+                code.with_distance(distance)
+            }
+            _ => code,
+        })
+    }
+}
+
+impl CorrectionCode for SubsystemCode {
+    fn encode(&self, data: &Qubit) -> Vec<Qubit> {
+        encode_frame(self.num_qubits(), data, &self.logical_x[0], &self.logical_z[0])
+    }
+
+    fn syndrome_measurement(&self, encoded_qubits: &mut Vec<Qubit>) -> Vec<bool> {
+        self.measure_gauge(encoded_qubits)
+    }
+
+    fn correct(&mut self, encoded_qubits: &mut Vec<Qubit>, syndromes: Vec<bool>) {
+        let start = Instant::now();
+
+        let stabilizer_values = self.infer_stabilizers(&syndromes);
+        if let Some(&(qubit, pauli)) = self.corrections.get(&stabilizer_values) {
+            pauli.apply(&mut encoded_qubits[qubit]);
+        }
+
+        let duration = start.elapsed();
+        self.correction_time.push(duration);
+    }
+
+    fn decode(&self, encoded_qubits: &mut Vec<Qubit>) -> Qubit {
+        let frame = PauliString::from_frame(encoded_qubits);
+
+        let mut decoded_qubit = Qubit::frame_reference();
+        logical_pauli(&frame, &self.logical_x[0], &self.logical_z[0]).apply(&mut decoded_qubit);
+
+        decoded_qubit
+    }
+
+    fn get_average_correction_time(&self) -> f64 {
+        average_duration(&self.correction_time)
+    }
+
+    fn logical_errors(&self, encoded_qubits: &[Qubit]) -> Vec<Pauli> {
+        let frame = PauliString::from_frame(encoded_qubits);
+        self.logical_x
+            .iter()
+            .zip(&self.logical_z)
+            .map(|(x, z)| logical_pauli(&frame, x, z))
+            .collect()
+    }
+
+    fn num_qubits(&self) -> usize {
+        SubsystemCode::num_qubits(self)
+    }
+
+    fn num_logical_qubits(&self) -> usize {
+        SubsystemCode::num_logical_qubits(self)
+    }
+
+    fn stabilizer_generators(&self) -> Vec<PauliString> {
+        self.stabilizers.clone()
+    }
+
+    /// The bare logical operators
+    fn logical_operators(&self) -> Vec<(PauliString, PauliString)> {
+        self.logical_x.iter().cloned().zip(self.logical_z.iter().cloned()).collect()
+    }
+
+    /// One outcome per gauge generator
+    fn syndrome_length(&self) -> usize {
+        self.gauge_generators.len()
+    }
+
+    fn parameters(&self) -> CodeParameters {
+        SubsystemCode::parameters(self)
+    }
+}
+
+/// Pairs of bare logical operators: the centralizer of the gauge group modulo the
+/// stabilizers, brought to symplectic form so that X̄ᵢ anticommutes with Z̄ⱼ only when i = j
+pub(super) fn bare_logicals(
+    gauge_generators: &[PauliString],
+    stabilizers: &[PauliString],
+) -> (Vec<PauliString>, Vec<PauliString>) {
+    let n = gauge_generators[0].len();
+
+    // Rows (z | x) turn the symplectic product into an ordinary dot product with (x | z)
+    let swapped = BinaryMatrix::from_rows(
+        2 * n,
+        gauge_generators
+            .iter()
+            .map(|generator| {
+                let mut row = generator.z_bits().to_vec();
+                row.extend_from_slice(generator.x_bits());
+                row
+            })
+            .collect(),
+    );
+
+    let mut span = EchelonBasis::new();
+    for stabilizer in stabilizers {
+        span.insert(&symplectic_vector(stabilizer));
+    }
+    let mut pool: Vec<PauliString> = swapped
+        .kernel()
+        .rows()
+        .iter()
+        .filter(|vector| span.insert(vector))
+        .map(|vector| PauliString::from_bits(vector[..n].to_vec(), vector[n..].to_vec()))
+        .collect();
+
+    let mut logical_x = Vec::new();
+    let mut logical_z = Vec::new();
+    while !pool.is_empty() {
+        let x = pool.remove(0);
+        let Some(partner) = pool.iter().position(|candidate| !candidate.commutes_with(&x)) else {
+            continue;
+        };
+        let z = pool.remove(partner);
+        for operator in pool.iter_mut() {
+            if !operator.commutes_with(&z) {
+                operator.multiply(&x);
+            }
+            if !operator.commutes_with(&x) {
+                operator.multiply(&z);
+            }
+        }
+        logical_x.push(x);
+        logical_z.push(z);
+    }
+    (logical_x, logical_z)
+}
@@ -0,0 +1,315 @@
+use crate::correction_codes::{average_duration, encode_frame, logical_pauli, CodeParameters, CorrectionCode};
+use crate::matching::{min_weight_pairing, Pairing};
+use crate::pauli::{Pauli, PauliString};
+use crate::qubit::Qubit;
+use std::time::{Duration, Instant};
+
+/// Kitaev's toric code on an L×L lattice with periodic boundaries.
+///
+/// Qubits live on the 2L² edges. Edge `horizontal_edge(r, c)` joins vertex (r, c) to
+/// (r, c+1) and `vertical_edge(r, c)` joins vertex (r, c) to (r+1, c). Every vertex carries
+/// an X-type star stabilizer and every plaquette a Z-type stabilizer; the torus encodes
+/// two logical qubits.
+///
+/// Physical qubits are tracked as a Pauli frame: `encode` returns frame reference qubits,
+/// errors and corrections act on their frames, and `decode` returns a frame reference
+/// qubit carrying the logical Pauli of logical qubit 0.
+pub struct ToricCode {
+    size: usize,
+    vertex_stabilizers: Vec<PauliString>,
+    plaquette_stabilizers: Vec<PauliString>,
+    logical_x: [PauliString; 2],
+    logical_z: [PauliString; 2],
+    correction_time: Vec<Duration>,
+}
+
+impl ToricCode {
+    pub fn new(size: usize) -> Self {
+        assert!(size >= 2, "the toric code needs a lattice of at least 2×2");
+        let n = 2 * size * size;
+        let mut code = Self {
+            size,
+            vertex_stabilizers: Vec::with_capacity(size * size),
+            plaquette_stabilizers: Vec::with_capacity(size * size),
+            logical_x: [PauliString::identity(n), PauliString::identity(n)],
+            logical_z: [PauliString::identity(n), PauliString::identity(n)],
+            correction_time: Vec::new(),
+        };
+
+        for r in 0..size {
+            for c in 0..size {
+                let star = [
+                    code.horizontal_edge(r, c),
+                    code.horizontal_edge(r, c + size - 1),
+                    code.vertical_edge(r, c),
+                    code.vertical_edge(r + size - 1, c),
+                ];
+                code.vertex_stabilizers.push(PauliString::from_support(n, &star, Pauli::X));
+
+                let plaquette = [
+                    code.horizontal_edge(r, c),
+                    code.horizontal_edge(r + 1, c),
+                    code.vertical_edge(r, c),
+                    code.vertical_edge(r, c + 1),
+                ];
+                code.plaquette_stabilizers.push(PauliString::from_support(n, &plaquette, Pauli::Z));
+            }
+        }
+
+        // Logical qubit 0: Z along a row of horizontal edges, X across it on the dual lattice.
+        // Logical qubit 1: the same picture rotated onto the vertical edges.
+        let row: Vec<usize> = (0..size).map(|c| code.horizontal_edge(0, c)).collect();
+        let dual_column: Vec<usize> = (0..size).map(|r| code.horizontal_edge(r, 0)).collect();
+        let column: Vec<usize> = (0..size).map(|r| code.vertical_edge(r, 0)).collect();
+        let dual_row: Vec<usize> = (0..size).map(|c| code.vertical_edge(0, c)).collect();
+        code.logical_z = [
+            PauliString::from_support(n, &row, Pauli::Z),
+            PauliString::from_support(n, &column, Pauli::Z),
+        ];
+        code.logical_x = [
+            PauliString::from_support(n, &dual_column, Pauli::X),
+            PauliString::from_support(n, &dual_row, Pauli::X),
+        ];
+
+        code
+    }
+
+    /// Linear size L of the lattice, which is also the code distance
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn num_qubits(&self) -> usize {
+        2 * self.size * self.size
+    }
+
+    /// Index of the edge from vertex (r, c) to (r, c+1); coordinates wrap around
+    pub fn horizontal_edge(&self, r: usize, c: usize) -> usize {
+        (r % self.size) * self.size + c % self.size
+    }
+
+    /// Index of the edge from vertex (r, c) to (r+1, c); coordinates wrap around
+    pub fn vertical_edge(&self, r: usize, c: usize) -> usize {
+        self.size * self.size + self.horizontal_edge(r, c)
+    }
+
+    pub fn vertex_stabilizers(&self) -> &[PauliString] {
+        &self.vertex_stabilizers
+    }
+
+    pub fn plaquette_stabilizers(&self) -> &[PauliString] {
+        &self.plaquette_stabilizers
+    }
+
+    pub fn logical_x(&self, logical_qubit: usize) -> &PauliString {
+        &self.logical_x[logical_qubit]
+    }
+
+    pub fn logical_z(&self, logical_qubit: usize) -> &PauliString {
+        &self.logical_z[logical_qubit]
+    }
+
+    /// Signed shortest displacement from `from` to `to` around a cycle of length L
+    fn torus_offset(&self, from: usize, to: usize) -> isize {
+        let size = self.size as isize;
+        let forward = (to as isize - from as isize).rem_euclid(size);
+        if forward <= size / 2 {
+            forward
+        } else {
+            forward - size
+        }
+    }
+
+    fn torus_distance(&self, a: (usize, usize), b: (usize, usize)) -> usize {
+        self.torus_offset(a.0, b.0).unsigned_abs() + self.torus_offset(a.1, b.1).unsigned_abs()
+    }
+
+    /// Pairs defects by torus distance and returns the edges of the connecting paths.
+    ///
+    /// `crossed_edge(r, c, dr, dc)` names the edge crossed when stepping from site (r, c)
+    /// by one unit in direction (dr, dc).
+    fn match_defects<F>(&self, defects: &[(usize, usize)], crossed_edge: F) -> Vec<usize>
+    where
+        F: Fn(usize, usize, isize, isize) -> usize,
+    {
+        let distances: Vec<Vec<f64>> = defects
+            .iter()
+            .map(|&a| defects.iter().map(|&b| self.torus_distance(a, b) as f64).collect())
+            .collect();
+        // The torus has no boundary, so every defect is paired with another
+        let pairings = min_weight_pairing(&distances, &vec![f64::INFINITY; defects.len()]);
+
+        let mut edges = Vec::new();
+        for pairing in pairings {
+            let Pairing::Pair(i, j) = pairing else {
+                continue;
+            };
+            let (mut r, mut c) = defects[i];
+            let (target_r, target_c) = defects[j];
+            let size = self.size;
+            let dr = self.torus_offset(r, target_r);
+            for _ in 0..dr.unsigned_abs() {
+                let step = dr.signum();
+                edges.push(crossed_edge(r, c, step, 0));
+                r = (r as isize + step).rem_euclid(size as isize) as usize;
+            }
+            let dc = self.torus_offset(c, target_c);
+            for _ in 0..dc.unsigned_abs() {
+                let step = dc.signum();
+                edges.push(crossed_edge(r, c, 0, step));
+                c = (c as isize + step).rem_euclid(size as isize) as usize;
+            }
+        }
+
+        edges
+    }
+}
+
+impl CorrectionCode for ToricCode {
+    fn encode(&self, data: &Qubit) -> Vec<Qubit> {
+        // Carry the input's Pauli frame onto logical qubit 0
+        encode_frame(self.num_qubits(), data, &self.logical_x[0], &self.logical_z[0])
+    }
+
+    fn syndrome_measurement(&self, encoded_qubits: &mut Vec<Qubit>) -> Vec<bool> {
+        let frame = PauliString::from_frame(encoded_qubits);
+
+        // Vertex (star) outcomes first, then plaquettes
+        self.vertex_stabilizers
+            .iter()
+            .chain(self.plaquette_stabilizers.iter())
+            .map(|stabilizer| !frame.commutes_with(stabilizer))
+            .collect()
+    }
+
+    fn correct(&mut self, encoded_qubits: &mut Vec<Qubit>, syndromes: Vec<bool>) {
+        let start = Instant::now();
+        let size = self.size;
+        let sites = size * size;
+
+        let defects_of = |offset: usize| -> Vec<(usize, usize)> {
+            (0..sites)
+                .filter(|&i| syndromes.get(offset + i).copied().unwrap_or(false))
+                .map(|i| (i / size, i % size))
+                .collect()
+        };
+        let vertex_defects = defects_of(0);
+        let plaquette_defects = defects_of(sites);
+
+        // Z errors light up stars: stepping between vertices crosses the edge between them
+        let z_edges = self.match_defects(&vertex_defects, |r, c, dr, dc| match (dr, dc) {
+            (1, 0) => self.vertical_edge(r, c),
+            (-1, 0) => self.vertical_edge(r + size - 1, c),
+            (0, 1) => self.horizontal_edge(r, c),
+            _ => self.horizontal_edge(r, c + size - 1),
+        });
+
+        // X errors light up plaquettes: stepping between plaquettes crosses their shared edge
+        let x_edges = self.match_defects(&plaquette_defects, |r, c, dr, dc| match (dr, dc) {
+            (1, 0) => self.horizontal_edge(r + 1, c),
+            (-1, 0) => self.horizontal_edge(r, c),
+            (0, 1) => self.vertical_edge(r, c + 1),
+            _ => self.vertical_edge(r, c),
+        });
+
+        for edge in z_edges {
+            Pauli::Z.apply(&mut encoded_qubits[edge]);
+        }
+        for edge in x_edges {
+            Pauli::X.apply(&mut encoded_qubits[edge]);
+        }
+
+        let duration = start.elapsed();
+        self.correction_time.push(duration);
+    }
+
+    fn decode(&self, encoded_qubits: &mut Vec<Qubit>) -> Qubit {
+        let frame = PauliString::from_frame(encoded_qubits);
+
+        let mut decoded_qubit = Qubit::frame_reference();
+        logical_pauli(&frame, &self.logical_x[0], &self.logical_z[0]).apply(&mut decoded_qubit);
+
+        decoded_qubit
+    }
+
+    fn get_average_correction_time(&self) -> f64 {
+        average_duration(&self.correction_time)
+    }
+
+    fn logical_errors(&self, encoded_qubits: &[Qubit]) -> Vec<Pauli> {
+        let frame = PauliString::from_frame(encoded_qubits);
+        (0..2)
+            .map(|i| logical_pauli(&frame, &self.logical_x[i], &self.logical_z[i]))
+            .collect()
+    }
+
+    fn num_qubits(&self) -> usize {
+        ToricCode::num_qubits(self)
+    }
+
+    fn num_logical_qubits(&self) -> usize {
+        2
+    }
+
+    fn stabilizer_generators(&self) -> Vec<PauliString> {
+        self.vertex_stabilizers.iter().chain(&self.plaquette_stabilizers).cloned().collect()
+    }
+
+    fn logical_operators(&self) -> Vec<(PauliString, PauliString)> {
+        self.logical_x.iter().cloned().zip(self.logical_z.iter().cloned()).collect()
+    }
+
+    fn parameters(&self) -> CodeParameters {
+        CodeParameters {
+            n: ToricCode::num_qubits(self),
+            k: 2,
+            d: Some(self.size),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gf2::BinaryMatrix;
+
+    #[test]
+    fn stabilizers_commute_and_leave_two_logical_qubits() {
+        for size in [2, 3, 4] {
+            let code = ToricCode::new(size);
+            let generators = code.stabilizer_generators();
+            for a in &generators {
+                assert!(generators.iter().all(|b| a.commutes_with(b)));
+                for (x, z) in code.logical_operators() {
+                    assert!(a.commutes_with(&x) && a.commutes_with(&z));
+                }
+            }
+            // One star and one plaquette are the products of all the others
+            let rows = generators.iter().map(|g| [g.x_bits(), g.z_bits()].concat()).collect();
+            let rank = BinaryMatrix::from_rows(2 * code.num_qubits(), rows).rank();
+            assert_eq!(code.num_qubits() - rank, 2, "L = {}", size);
+            for i in 0..2 {
+                for j in 0..2 {
+                    assert_eq!(code.logical_x(i).commutes_with(code.logical_z(j)), i != j);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn corrects_every_single_qubit_error() {
+        for size in [3, 4, 5] {
+            let mut code = ToricCode::new(size);
+            for qubit in 0..code.num_qubits() {
+                for pauli in [Pauli::X, Pauli::Y, Pauli::Z] {
+                    let mut encoded = code.encode(&Qubit::new());
+                    pauli.apply(&mut encoded[qubit]);
+                    let syndromes = code.syndrome_measurement(&mut encoded);
+                    code.correct(&mut encoded, syndromes);
+                    assert!(code.syndrome_measurement(&mut encoded).iter().all(|&fired| !fired));
+                    assert_eq!(code.logical_errors(&encoded), [Pauli::I; 2], "{:?} on {}", pauli, qubit);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,270 @@
+use crate::correction_codes::{average_duration, encode_frame, logical_pauli, CodeParameters, CorrectionCode};
+use crate::error_models::BiasedNoise;
+use crate::matching::{log_likelihood_weight, DecodingGraph};
+use crate::pauli::{Pauli, PauliString};
+use crate::qubit::Qubit;
+use std::time::{Duration, Instant};
+
+/// The XZZX surface code on a d×d grid of qubits, for odd d.
+///
+/// Qubit (r, c) has index `r * d + c`. The plaquette whose top-left corner is (r, c) measures
+/// X on (r, c) and (r+1, c+1) and Z on (r, c+1) and (r+1, c), truncated to weight two along
+/// the edges of the grid. It is the rotated surface code with a Hadamard on every other
+/// qubit, which makes every plaquette alike: a Z error only trips the two plaquettes on its
+/// main diagonal and an X error the two on its anti-diagonal. Under pure dephasing the code
+/// therefore splits into repetition codes along the diagonals.
+///
+/// The plaquettes fall into two checkerboard sublattices that never share an error edge,
+/// and `correct` matches each one separately. Z edges and X edges are weighted by the
+/// log-likelihood of the errors they stand for, so the decoder follows the noise bias set
+/// with `with_noise`; a Y error counts as both.
+pub struct XzzxCode {
+    distance: usize,
+    stabilizers: Vec<PauliString>,
+    /// Top-left corner of each plaquette, in syndrome order
+    plaquettes: Vec<(isize, isize)>,
+    logical_x: PauliString,
+    logical_z: PauliString,
+    sublattices: [Sublattice; 2],
+    correction_time: Vec<Duration>,
+}
+
+/// One checkerboard half of the plaquettes, as a decoding graph
+struct Sublattice {
+    graph: DecodingGraph,
+    /// Graph node of each plaquette in this sublattice, by stabilizer index
+    nodes: Vec<Option<usize>>,
+    /// Qubit and Pauli each edge stands for
+    corrections: Vec<(usize, Pauli)>,
+}
+
+impl XzzxCode {
+    /// The code with a decoder tuned to depolarizing noise
+    pub fn new(distance: usize) -> Self {
+        assert!(distance >= 3 && distance % 2 == 1, "the XZZX code needs an odd distance of at least 3");
+        let d = distance as isize;
+        let n = distance * distance;
+
+        let mut plaquettes = Vec::with_capacity(n - 1);
+        for r in -1..d {
+            for c in -1..d {
+                let interior_row = (0..d - 1).contains(&r);
+                let interior_col = (0..d - 1).contains(&c);
+                let even = (r + c).rem_euclid(2) == 0;
+                let kept = (interior_row && interior_col)
+                    || (interior_col && (r == -1 || r == d - 1) && even)
+                    || (interior_row && (c == -1 || c == d - 1) && !even);
+                if kept {
+                    plaquettes.push((r, c));
+                }
+            }
+        }
+
+        let mut code = Self {
+            distance,
+            stabilizers: Vec::with_capacity(n - 1),
+            plaquettes,
+            logical_x: PauliString::identity(n),
+            logical_z: PauliString::identity(n),
+            sublattices: [Sublattice::empty(), Sublattice::empty()],
+            correction_time: Vec::new(),
+        };
+
+        for &(r, c) in &code.plaquettes {
+            let mut stabilizer = PauliString::identity(n);
+            for (dr, dc, pauli) in [(0, 0, Pauli::X), (0, 1, Pauli::Z), (1, 0, Pauli::Z), (1, 1, Pauli::X)] {
+                if let Some(qubit) = code.qubit(r + dr, c + dc) {
+                    stabilizer.set(qubit, pauli);
+                }
+            }
+            code.stabilizers.push(stabilizer);
+        }
+
+        // The CSS rotated code has Z̄ along the top row and X̄ down the left column; the
+        // Hadamards on odd sites swap X and Z on every other qubit of each
+        for i in 0..distance {
+            let odd = i % 2 == 1;
+            code.logical_z.set(i, if odd { Pauli::X } else { Pauli::Z });
+            code.logical_x.set(i * distance, if odd { Pauli::Z } else { Pauli::X });
+        }
+
+        code.build_sublattices(1.0, 1.0);
+        code
+    }
+
+    /// Reweights the decoder for the given noise, so that strongly biased noise is decoded
+    /// mostly along the diagonals
+    pub fn with_noise(mut self, noise: &BiasedNoise) -> Self {
+        let (p_x, p_y, p_z) = noise.pauli_probabilities();
+        self.build_sublattices(log_likelihood_weight(p_z + p_y), log_likelihood_weight(p_x + p_y));
+        self
+    }
+
+    pub fn distance(&self) -> usize {
+        self.distance
+    }
+
+    pub fn num_qubits(&self) -> usize {
+        self.distance * self.distance
+    }
+
+    pub fn stabilizers(&self) -> &[PauliString] {
+        &self.stabilizers
+    }
+
+    pub fn logical_x(&self) -> &PauliString {
+        &self.logical_x
+    }
+
+    pub fn logical_z(&self) -> &PauliString {
+        &self.logical_z
+    }
+
+    /// Index of qubit (r, c), or `None` off the grid
+    fn qubit(&self, r: isize, c: isize) -> Option<usize> {
+        let d = self.distance as isize;
+        ((0..d).contains(&r) && (0..d).contains(&c)).then(|| (r * d + c) as usize)
+    }
+
+    /// Builds both decoding graphs, with infinite weights leaving the edges out
+    fn build_sublattices(&mut self, z_weight: f64, x_weight: f64) {
+        let plaquette_index = |r: isize, c: isize| self.plaquettes.iter().position(|&p| p == (r, c));
+
+        let mut sublattices = [Sublattice::empty(), Sublattice::empty()];
+        for (parity, sublattice) in sublattices.iter_mut().enumerate() {
+            let mut num_nodes = 0;
+            sublattice.nodes = self
+                .plaquettes
+                .iter()
+                .map(|&(r, c)| {
+                    ((r + c).rem_euclid(2) as usize == parity).then(|| {
+                        num_nodes += 1;
+                        num_nodes - 1
+                    })
+                })
+                .collect();
+            // One extra node stands for the whole open boundary of this sublattice
+            sublattice.graph = DecodingGraph::new(num_nodes + 1);
+            sublattice.graph.set_boundary(num_nodes);
+        }
+
+        let d = self.distance as isize;
+        for r in 0..d {
+            for c in 0..d {
+                let qubit = (r * d + c) as usize;
+                // A Z error trips the plaquettes above-left and below-right of the qubit, an X
+                // error those above-right and below-left
+                let pairs = [
+                    ((r - 1, c - 1), (r, c), Pauli::Z, z_weight),
+                    ((r - 1, c), (r, c - 1), Pauli::X, x_weight),
+                ];
+                for (first, second, pauli, weight) in pairs {
+                    if !weight.is_finite() {
+                        continue;
+                    }
+                    let sublattice = &mut sublattices[(first.0 + first.1).rem_euclid(2) as usize];
+                    let boundary = sublattice.graph.num_nodes() - 1;
+                    let node = |plaquette: (isize, isize)| {
+                        plaquette_index(plaquette.0, plaquette.1)
+                            .and_then(|index| sublattice.nodes[index])
+                            .unwrap_or(boundary)
+                    };
+                    let (a, b) = (node(first), node(second));
+                    if a == boundary && b == boundary {
+                        continue;
+                    }
+                    sublattice.graph.add_edge(a, b, weight);
+                    sublattice.corrections.push((qubit, pauli));
+                }
+            }
+        }
+
+        self.sublattices = sublattices;
+    }
+}
+
+impl Sublattice {
+    fn empty() -> Self {
+        Self {
+            graph: DecodingGraph::new(0),
+            nodes: Vec::new(),
+            corrections: Vec::new(),
+        }
+    }
+}
+
+impl CorrectionCode for XzzxCode {
+    fn encode(&self, data: &Qubit) -> Vec<Qubit> {
+        encode_frame(self.num_qubits(), data, &self.logical_x, &self.logical_z)
+    }
+
+    fn syndrome_measurement(&self, encoded_qubits: &mut Vec<Qubit>) -> Vec<bool> {
+        let frame = PauliString::from_frame(encoded_qubits);
+        self.stabilizers
+            .iter()
+            .map(|stabilizer| !frame.commutes_with(stabilizer))
+            .collect()
+    }
+
+    fn correct(&mut self, encoded_qubits: &mut Vec<Qubit>, syndromes: Vec<bool>) {
+        let start = Instant::now();
+
+        for sublattice in &self.sublattices {
+            let defects: Vec<usize> = sublattice
+                .nodes
+                .iter()
+                .zip(&syndromes)
+                .filter_map(|(&node, &flagged)| if flagged { node } else { None })
+                .collect();
+            for edge in sublattice.graph.match_defects(&defects) {
+                let (qubit, pauli) = sublattice.corrections[edge];
+                pauli.apply(&mut encoded_qubits[qubit]);
+            }
+        }
+
+        let duration = start.elapsed();
+        self.correction_time.push(duration);
+    }
+
+    fn decode(&self, encoded_qubits: &mut Vec<Qubit>) -> Qubit {
+        let frame = PauliString::from_frame(encoded_qubits);
+
+        let mut decoded_qubit = Qubit::frame_reference();
+        logical_pauli(&frame, &self.logical_x, &self.logical_z).apply(&mut decoded_qubit);
+
+        decoded_qubit
+    }
+
+    fn get_average_correction_time(&self) -> f64 {
+        average_duration(&self.correction_time)
+    }
+
+    fn logical_errors(&self, encoded_qubits: &[Qubit]) -> Vec<Pauli> {
+        let frame = PauliString::from_frame(encoded_qubits);
+        vec![logical_pauli(&frame, &self.logical_x, &self.logical_z)]
+    }
+
+    fn num_qubits(&self) -> usize {
+        XzzxCode::num_qubits(self)
+    }
+
+    fn num_logical_qubits(&self) -> usize {
+        1
+    }
+
+    fn stabilizer_generators(&self) -> Vec<PauliString> {
+        self.stabilizers.clone()
+    }
+
+    fn logical_operators(&self) -> Vec<(PauliString, PauliString)> {
+        vec![(self.logical_x.clone(), self.logical_z.clone())]
+    }
+
+    fn parameters(&self) -> CodeParameters {
+        CodeParameters {
+            n: XzzxCode::num_qubits(self),
+            k: 1,
+            d: Some(self.distance),
+        }
+    }
+}
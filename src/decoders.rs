@@ -0,0 +1,167 @@
+use crate::correction_codes::{average_duration, CodeParameters, CorrectionCode};
+use crate::hardware::SoftSyndrome;
+use crate::pauli::{Pauli, PauliString};
+use crate::qubit::Qubit;
+use std::time::{Duration, Instant};
+
+mod bp_osd;
+mod code_graph;
+mod lookup;
+mod matching;
+mod maximum_likelihood;
+mod sliding_window;
+mod union_find;
+
+pub use bp_osd::{BpMethod, BpOsd, BpOsdDecoder, BpOutcome, OsdMethod};
+pub use code_graph::Prediction;
+pub use lookup::LookupTableDecoder;
+pub use matching::{CorrelatedMatchingDecoder, DemMatchingDecoder, MatchingDecoder};
+pub use maximum_likelihood::{CosetSum, MaximumLikelihoodDecoder};
+pub use sliding_window::{BacklogAnalysis, SlidingWindowDecoder, WindowDecoderFactory, WindowedDecoding};
+pub use union_find::{DemUnionFindDecoder, UnionFindDecoder};
+
+/// Finds a correction from a syndrome, independently of the code that produced it.
+///
+/// A syndrome has one bit per stabilizer generator, in the order of the code's
+/// `stabilizer_generators`, set when the error anticommutes with that generator. The
+/// correction is a Pauli operator on the whole block; it should have the same syndrome as
+/// the error, so that applying it returns the block to the code space.
+pub trait Decoder {
+    fn decode(&mut self, syndrome: &[bool]) -> PauliString;
+
+    /// Decodes repeated rounds of syndrome readout, each outcome with the probability that
+    /// it was read out wrongly, followed by a perfectly read `final_syndrome` of the same
+    /// error. Decoders that cannot use the rounds decode the final syndrome alone.
+    fn decode_soft(&mut self, _rounds: &[SoftSyndrome], final_syndrome: &[bool]) -> PauliString {
+        self.decode(final_syndrome)
+    }
+}
+
+/// Finds which error mechanisms of a detector error model fired, from the detectors they
+/// flipped. A decoder is built for one model, and returns indices into its mechanisms; the
+/// observables those mechanisms flip are its prediction of the logical error.
+pub trait DetectorDecoder {
+    fn decode_detectors(&mut self, flipped: &[usize]) -> Vec<usize>;
+}
+
+impl<D: Decoder + ?Sized> Decoder for Box<D> {
+    fn decode(&mut self, syndrome: &[bool]) -> PauliString {
+        (**self).decode(syndrome)
+    }
+
+    fn decode_soft(&mut self, rounds: &[SoftSyndrome], final_syndrome: &[bool]) -> PauliString {
+        (**self).decode_soft(rounds, final_syndrome)
+    }
+}
+
+/// A code paired with a decoder other than its own.
+///
+/// Encoding, readout and logical errors are the code's. The syndrome is measured from the
+/// code's stabilizer generators directly, in the layout every `Decoder` expects, and the
+/// correction is whatever the decoder returns for it. For a subsystem code these are the
+/// stabilizer values its gauge outcomes multiply out to.
+pub struct DecodedCode<C, D> {
+    code: C,
+    decoder: D,
+    stabilizers: Vec<PauliString>,
+    correction_time: Vec<Duration>,
+}
+
+impl<C: CorrectionCode, D: Decoder> DecodedCode<C, D> {
+    pub fn new(code: C, decoder: D) -> Self {
+        let stabilizers = code.stabilizer_generators();
+        Self {
+            code,
+            decoder,
+            stabilizers,
+            correction_time: Vec::new(),
+        }
+    }
+
+    pub fn code(&self) -> &C {
+        &self.code
+    }
+
+    pub fn decoder(&self) -> &D {
+        &self.decoder
+    }
+
+    pub fn decoder_mut(&mut self) -> &mut D {
+        &mut self.decoder
+    }
+}
+
+impl<C: CorrectionCode, D: Decoder> CorrectionCode for DecodedCode<C, D> {
+    fn encode(&self, data: &Qubit) -> Vec<Qubit> {
+        self.code.encode(data)
+    }
+
+    fn syndrome_measurement(&self, encoded_qubits: &mut Vec<Qubit>) -> Vec<bool> {
+        let frame = PauliString::from_frame(encoded_qubits);
+        self.stabilizers
+            .iter()
+            .map(|stabilizer| !frame.commutes_with(stabilizer))
+            .collect()
+    }
+
+    fn correct(&mut self, encoded_qubits: &mut Vec<Qubit>, syndromes: Vec<bool>) {
+        let start = Instant::now();
+
+        let correction = self.decoder.decode(&syndromes);
+        correction.apply_to(encoded_qubits);
+
+        let duration = start.elapsed();
+        self.correction_time.push(duration);
+    }
+
+    fn correct_soft(
+        &mut self,
+        encoded_qubits: &mut Vec<Qubit>,
+        rounds: &[SoftSyndrome],
+        final_syndromes: Vec<bool>,
+    ) {
+        let start = Instant::now();
+
+        let correction = self.decoder.decode_soft(rounds, &final_syndromes);
+        correction.apply_to(encoded_qubits);
+
+        let duration = start.elapsed();
+        self.correction_time.push(duration);
+    }
+
+    fn decode(&self, encoded_qubits: &mut Vec<Qubit>) -> Qubit {
+        self.code.decode(encoded_qubits)
+    }
+
+    fn get_average_correction_time(&self) -> f64 {
+        average_duration(&self.correction_time)
+    }
+
+    fn logical_errors(&self, encoded_qubits: &[Qubit]) -> Vec<Pauli> {
+        self.code.logical_errors(encoded_qubits)
+    }
+
+    fn num_qubits(&self) -> usize {
+        self.code.num_qubits()
+    }
+
+    fn num_logical_qubits(&self) -> usize {
+        self.code.num_logical_qubits()
+    }
+
+    fn stabilizer_generators(&self) -> Vec<PauliString> {
+        self.stabilizers.clone()
+    }
+
+    fn logical_operators(&self) -> Vec<(PauliString, PauliString)> {
+        self.code.logical_operators()
+    }
+
+    fn syndrome_length(&self) -> usize {
+        self.stabilizers.len()
+    }
+
+    fn parameters(&self) -> CodeParameters {
+        self.code.parameters()
+    }
+}
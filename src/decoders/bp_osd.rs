@@ -0,0 +1,406 @@
+use crate::circuits::DetectorErrorModel;
+use crate::decoders::{Decoder, DetectorDecoder};
+use crate::error_models::PauliChannel;
+use crate::gf2::BinaryMatrix;
+use crate::pauli::PauliString;
+
+/// Largest magnitude a log-likelihood ratio is allowed, which keeps tanh away from ±1
+const MAX_LLR: f64 = 30.0;
+
+/// How a check node combines the messages of its other bits
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BpMethod {
+    /// The exact sum-product rule, 2 atanh Π tanh(m / 2)
+    ProductSum,
+    /// The min-sum approximation, sign product times smallest magnitude, scaled by
+    /// `scaling_factor` (between 0 and 1) to make up for its overconfidence
+    MinSum { scaling_factor: f64 },
+}
+
+/// Post-processing for syndromes that belief propagation fails to satisfy
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OsdMethod {
+    /// BP's hard decision is returned as it is
+    None,
+    /// Ordered statistics decoding of order zero: the unique solution supported on the
+    /// information set of bits BP found most likely to be flipped
+    Osd0,
+    /// OSD-0 followed by the combination sweep, which also tries flipping each bit outside
+    /// the information set, and each pair among the `order` most likely of them
+    CombinationSweep { order: usize },
+}
+
+/// Belief propagation on the Tanner graph of a classical check matrix, with ordered statistics
+/// post-processing (BP+OSD).
+///
+/// Bits flip independently with known probabilities, and the decoder looks for a likely
+/// error with syndrome H·e = s. Belief propagation passes log-likelihood ratios between bits
+/// and checks in flooding order until its hard decision satisfies the syndrome. On quantum
+/// LDPC codes it often fails to, because degenerate errors leave it split between equally
+/// good answers, and ordered statistics decoding then turns its soft output into a valid
+/// correction by Gaussian elimination over the bits it finds most suspicious.
+#[derive(Clone, Debug)]
+pub struct BpOsd {
+    checks: BinaryMatrix,
+    /// Prior log-likelihood ratio ln((1 − p) / p) of each bit
+    priors: Vec<f64>,
+    /// Check and bit of each edge of the Tanner graph
+    edges: Vec<(usize, usize)>,
+    check_edges: Vec<Vec<usize>>,
+    bit_edges: Vec<Vec<usize>>,
+    method: BpMethod,
+    max_iterations: usize,
+    osd: OsdMethod,
+}
+
+/// What belief propagation alone made of a syndrome
+#[derive(Clone, Debug, PartialEq)]
+pub struct BpOutcome {
+    /// The hard decision, bits whose posterior log-likelihood ratio is negative
+    pub error: Vec<bool>,
+    pub posteriors: Vec<f64>,
+    /// Whether the hard decision has the syndrome
+    pub converged: bool,
+    pub iterations: usize,
+}
+
+impl BpOsd {
+    /// The decoder for `checks` when bit j flips with probability `error_probabilities[j]`.
+    /// It defaults to min-sum with a scaling factor of 0.625, at most 50 iterations, and a
+    /// combination sweep of order 10.
+    pub fn new(checks: BinaryMatrix, error_probabilities: &[f64]) -> Self {
+        assert_eq!(checks.num_cols(), error_probabilities.len(), "one probability is needed per bit");
+        let priors = error_probabilities
+            .iter()
+            .map(|&p| ((1.0 - p) / p).ln().clamp(-MAX_LLR, MAX_LLR))
+            .collect();
+        let mut edges = Vec::new();
+        let mut check_edges = vec![Vec::new(); checks.num_rows()];
+        let mut bit_edges = vec![Vec::new(); checks.num_cols()];
+        for (check, row) in checks.rows().iter().enumerate() {
+            for (bit, _) in row.iter().enumerate().filter(|&(_, &set)| set) {
+                check_edges[check].push(edges.len());
+                bit_edges[bit].push(edges.len());
+                edges.push((check, bit));
+            }
+        }
+        Self {
+            checks,
+            priors,
+            edges,
+            check_edges,
+            bit_edges,
+            method: BpMethod::MinSum { scaling_factor: 0.625 },
+            max_iterations: 50,
+            osd: OsdMethod::CombinationSweep { order: 10 },
+        }
+    }
+
+    /// The decoder for the check matrix of a detector error model, with a row per detector
+    /// and a column per mechanism, which handles mechanisms flipping any number of detectors
+    pub fn for_model(model: &DetectorErrorModel) -> Self {
+        let mut checks = BinaryMatrix::zeros(model.num_detectors(), model.mechanisms().len());
+        for (column, mechanism) in model.mechanisms().iter().enumerate() {
+            for &detector in &mechanism.detectors {
+                checks.set(detector, column, true);
+            }
+        }
+        let probabilities: Vec<f64> =
+            model.mechanisms().iter().map(|mechanism| mechanism.probability).collect();
+        Self::new(checks, &probabilities)
+    }
+
+    pub fn with_method(mut self, method: BpMethod) -> Self {
+        self.method = method;
+        self
+    }
+
+    pub fn with_max_iterations(mut self, max_iterations: usize) -> Self {
+        self.max_iterations = max_iterations;
+        self
+    }
+
+    pub fn with_osd(mut self, osd: OsdMethod) -> Self {
+        self.osd = osd;
+        self
+    }
+
+    pub fn checks(&self) -> &BinaryMatrix {
+        &self.checks
+    }
+
+    /// A likely error with this syndrome: BP's answer if it converged, and otherwise the
+    /// post-processed one. Without post-processing the answer may miss the syndrome.
+    pub fn decode(&self, syndrome: &[bool]) -> Vec<bool> {
+        let outcome = self.propagate(syndrome);
+        if outcome.converged || self.osd == OsdMethod::None {
+            return outcome.error;
+        }
+        self.ordered_statistics(syndrome, &outcome.posteriors).unwrap_or(outcome.error)
+    }
+
+    /// Belief propagation in flooding order, stopping as soon as the hard decision has the
+    /// syndrome. Messages are log-likelihood ratios, positive when a bit is more likely
+    /// unflipped, and a check whose syndrome bit is set flips the sign of what it sends.
+    pub fn propagate(&self, syndrome: &[bool]) -> BpOutcome {
+        assert_eq!(syndrome.len(), self.checks.num_rows(), "syndrome has the wrong length");
+        let mut to_checks: Vec<f64> = self.edges.iter().map(|&(_, bit)| self.priors[bit]).collect();
+        let mut to_bits = vec![0.0; self.edges.len()];
+        let mut posteriors = self.priors.clone();
+        let mut error = vec![false; self.priors.len()];
+
+        for iteration in 1..=self.max_iterations {
+            for (check, edges) in self.check_edges.iter().enumerate() {
+                let sign = if syndrome[check] { -1.0 } else { 1.0 };
+                let incoming: Vec<f64> = edges.iter().map(|&edge| to_checks[edge]).collect();
+                let outgoing = match self.method {
+                    BpMethod::ProductSum => product_sum(&incoming),
+                    BpMethod::MinSum { scaling_factor } => min_sum(&incoming, scaling_factor),
+                };
+                for (&edge, message) in edges.iter().zip(outgoing) {
+                    to_bits[edge] = sign * message;
+                }
+            }
+
+            for (bit, edges) in self.bit_edges.iter().enumerate() {
+                posteriors[bit] = self.priors[bit] + edges.iter().map(|&edge| to_bits[edge]).sum::<f64>();
+                for &edge in edges {
+                    to_checks[edge] = (posteriors[bit] - to_bits[edge]).clamp(-MAX_LLR, MAX_LLR);
+                }
+                error[bit] = posteriors[bit] < 0.0;
+            }
+
+            if self.checks.mul_vec(&error) == syndrome {
+                return BpOutcome { error, posteriors, converged: true, iterations: iteration };
+            }
+        }
+        BpOutcome { error, posteriors, converged: false, iterations: self.max_iterations }
+    }
+
+    /// Ordered statistics decoding. Bits are ranked from most to least likely flipped by
+    /// their posteriors, and row reduction of the ranked check matrix picks the information
+    /// set: the first linearly independent columns. Every choice of the other bits fixes the
+    /// information set's bits; OSD-0 leaves them all clear, and the combination sweep also
+    /// tries flipping one or two of them. The candidate of least weight under the priors
+    /// wins. Returns `None` if no error has the syndrome.
+    fn ordered_statistics(&self, syndrome: &[bool], posteriors: &[f64]) -> Option<Vec<bool>> {
+        let n = self.priors.len();
+        let mut ranking: Vec<usize> = (0..n).collect();
+        ranking.sort_by(|&a, &b| posteriors[a].total_cmp(&posteriors[b]));
+
+        let rows = self
+            .checks
+            .rows()
+            .iter()
+            .zip(syndrome)
+            .map(|(row, &bit)| ranking.iter().map(|&col| row[col]).chain([bit]).collect())
+            .collect();
+        let mut reduced = BinaryMatrix::from_rows(n + 1, rows);
+        let pivots = reduced.row_reduce();
+        if pivots.last() == Some(&n) {
+            return None;
+        }
+        let mut is_pivot = vec![false; n];
+        for &pivot in &pivots {
+            is_pivot[pivot] = true;
+        }
+        let others: Vec<usize> = (0..n).filter(|&col| !is_pivot[col]).collect();
+
+        let mut flips: Vec<Vec<usize>> = vec![Vec::new()];
+        if let OsdMethod::CombinationSweep { order } = self.osd {
+            flips.extend(others.iter().map(|&col| vec![col]));
+            let sweep = &others[..order.min(others.len())];
+            for (i, &a) in sweep.iter().enumerate() {
+                flips.extend(sweep[i + 1..].iter().map(|&b| vec![a, b]));
+            }
+        }
+
+        let mut best: Option<(f64, Vec<bool>)> = None;
+        for flipped in flips {
+            let mut candidate = vec![false; n];
+            for &col in &flipped {
+                candidate[ranking[col]] = true;
+            }
+            for (row, &pivot) in pivots.iter().enumerate() {
+                let row = reduced.row(row);
+                candidate[ranking[pivot]] = flipped.iter().fold(row[n], |bit, &col| bit ^ row[col]);
+            }
+            let cost: f64 = (0..n).filter(|&bit| candidate[bit]).map(|bit| self.priors[bit]).sum();
+            if best.as_ref().is_none_or(|(best_cost, _)| cost < *best_cost) {
+                best = Some((cost, candidate));
+            }
+        }
+        best.map(|(_, candidate)| candidate)
+    }
+}
+
+/// Sum-product check messages, each from the product of the other bits' tanh(m / 2)
+fn product_sum(incoming: &[f64]) -> Vec<f64> {
+    let tanhs: Vec<f64> = incoming.iter().map(|&message| (message / 2.0).tanh()).collect();
+    // Products of the factors after each position, so that each message skips its own
+    let mut suffix = vec![1.0; tanhs.len() + 1];
+    for i in (0..tanhs.len()).rev() {
+        suffix[i] = suffix[i + 1] * tanhs[i];
+    }
+    let mut prefix = 1.0;
+    let mut outgoing = Vec::with_capacity(tanhs.len());
+    for (i, &t) in tanhs.iter().enumerate() {
+        let product: f64 = prefix * suffix[i + 1];
+        outgoing.push((2.0 * product.atanh()).clamp(-MAX_LLR, MAX_LLR));
+        prefix *= t;
+    }
+    outgoing
+}
+
+/// Min-sum check messages, from the signs and the smallest magnitude among the other bits
+fn min_sum(incoming: &[f64], scaling_factor: f64) -> Vec<f64> {
+    let negative = incoming.iter().filter(|&&message| message < 0.0).count() % 2 == 1;
+    let (mut smallest, mut second, mut position) = (f64::INFINITY, f64::INFINITY, 0);
+    for (i, &message) in incoming.iter().enumerate() {
+        let magnitude = message.abs();
+        if magnitude < smallest {
+            (second, smallest, position) = (smallest, magnitude, i);
+        } else if magnitude < second {
+            second = magnitude;
+        }
+    }
+    incoming
+        .iter()
+        .enumerate()
+        .map(|(i, &message)| {
+            let magnitude = if i == position { second } else { smallest };
+            let sign = if negative != (message < 0.0) { -1.0 } else { 1.0 };
+            sign * scaling_factor * magnitude.min(MAX_LLR)
+        })
+        .collect()
+}
+
+impl DetectorDecoder for BpOsd {
+    fn decode_detectors(&mut self, flipped: &[usize]) -> Vec<usize> {
+        let mut syndrome = vec![false; self.checks.num_rows()];
+        for &detector in flipped {
+            syndrome[detector] = true;
+        }
+        let error = self.decode(&syndrome);
+        (0..error.len()).filter(|&mechanism| error[mechanism]).collect()
+    }
+}
+
+/// BP+OSD for any stabilizer code, on the binary form of its syndrome equations.
+///
+/// An error with X bits x and Z bits z trips the stabilizer with X bits a and Z bits b when
+/// a·z + b·x = 1, so the syndrome is linear in the 2n bits (x | z). Each is taken to flip
+/// independently, the X bits with probability p_x + p_y and the Z bits with p_z + p_y, which
+/// gives a classical decoding problem. Its Tanner graph splits into independent blocks, one
+/// for the X checks and one for the Z checks of a CSS code, and each block is decoded on
+/// its own.
+#[derive(Clone, Debug)]
+pub struct BpOsdDecoder {
+    num_qubits: usize,
+    blocks: Vec<Block>,
+}
+
+/// A connected piece of the Tanner graph, with the checks and the bits of (x | z) it covers
+#[derive(Clone, Debug)]
+struct Block {
+    checks: Vec<usize>,
+    bits: Vec<usize>,
+    decoder: BpOsd,
+}
+
+impl BpOsdDecoder {
+    /// The decoder for the code with these stabilizer generators, under `channel` on every
+    /// qubit, with the defaults of `BpOsd`
+    pub fn new(stabilizers: &[PauliString], channel: &PauliChannel) -> Self {
+        let n = stabilizers[0].len();
+        let (p_x, p_y, p_z) = channel.pauli_probabilities();
+        // Row i of the check matrix is (b_i | a_i), so that it multiplies (x | z)
+        let rows: Vec<Vec<bool>> = stabilizers
+            .iter()
+            .map(|stabilizer| stabilizer.z_bits().iter().chain(stabilizer.x_bits()).copied().collect())
+            .collect();
+
+        let mut parent: Vec<usize> = (0..2 * n).collect();
+        for row in &rows {
+            let mut support = (0..2 * n).filter(|&bit| row[bit]);
+            if let Some(first) = support.next() {
+                for bit in support {
+                    let (a, b) = (find_root(&mut parent, first), find_root(&mut parent, bit));
+                    parent[b] = a;
+                }
+            }
+        }
+
+        let mut blocks = Vec::new();
+        for root in 0..2 * n {
+            if find_root(&mut parent, root) != root {
+                continue;
+            }
+            let checks: Vec<usize> = (0..rows.len())
+                .filter(|&i| (0..2 * n).any(|bit| rows[i][bit] && find_root(&mut parent, bit) == root))
+                .collect();
+            if checks.is_empty() {
+                // A bit no stabilizer sees is never part of a correction
+                continue;
+            }
+            let bits: Vec<usize> = (0..2 * n).filter(|&bit| find_root(&mut parent, bit) == root).collect();
+            let matrix = BinaryMatrix::from_rows(
+                bits.len(),
+                checks.iter().map(|&i| bits.iter().map(|&bit| rows[i][bit]).collect()).collect(),
+            );
+            let probabilities: Vec<f64> =
+                bits.iter().map(|&bit| if bit < n { p_x + p_y } else { p_z + p_y }).collect();
+            blocks.push(Block { checks, bits, decoder: BpOsd::new(matrix, &probabilities) });
+        }
+        Self { num_qubits: n, blocks }
+    }
+
+    pub fn with_method(self, method: BpMethod) -> Self {
+        self.map_decoders(|decoder| decoder.with_method(method))
+    }
+
+    pub fn with_max_iterations(self, max_iterations: usize) -> Self {
+        self.map_decoders(|decoder| decoder.with_max_iterations(max_iterations))
+    }
+
+    pub fn with_osd(self, osd: OsdMethod) -> Self {
+        self.map_decoders(|decoder| decoder.with_osd(osd))
+    }
+
+    pub fn num_qubits(&self) -> usize {
+        self.num_qubits
+    }
+
+    fn map_decoders(mut self, update: impl Fn(BpOsd) -> BpOsd) -> Self {
+        for block in &mut self.blocks {
+            block.decoder = update(block.decoder.clone());
+        }
+        self
+    }
+}
+
+impl Decoder for BpOsdDecoder {
+    fn decode(&mut self, syndrome: &[bool]) -> PauliString {
+        let n = self.num_qubits;
+        let mut bits = vec![false; 2 * n];
+        for block in &self.blocks {
+            let block_syndrome: Vec<bool> = block.checks.iter().map(|&check| syndrome[check]).collect();
+            if !block_syndrome.contains(&true) {
+                continue;
+            }
+            for (&bit, flipped) in block.bits.iter().zip(block.decoder.decode(&block_syndrome)) {
+                bits[bit] = flipped;
+            }
+        }
+        let z = bits.split_off(n);
+        PauliString::from_bits(bits, z)
+    }
+}
+
+fn find_root(parent: &mut [usize], mut node: usize) -> usize {
+    while parent[node] != node {
+        parent[node] = parent[parent[node]];
+        node = parent[node];
+    }
+    node
+}
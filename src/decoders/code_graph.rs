@@ -0,0 +1,172 @@
+use crate::error_models::PauliChannel;
+use crate::hardware::SoftSyndrome;
+use crate::matching::DecodingGraph;
+use crate::pauli::{Pauli, PauliString};
+
+/// What a graph decoder concluded from a syndrome
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Prediction {
+    pub correction: PauliString,
+    /// The logical Pauli the correction applies to each logical qubit, relative to the
+    /// identity; the error is predicted to have applied the same
+    pub logical_flips: Vec<Pauli>,
+}
+
+/// The graph the graph decoders work on: a node per check and a boundary node, and an X and
+/// a Z edge per qubit that record the logical operators their error flips, bit 2l for X̄_l
+/// and bit 2l + 1 for Z̄_l
+#[derive(Clone, Debug)]
+pub(super) struct CodeGraph {
+    num_qubits: usize,
+    graph: DecodingGraph,
+    /// Qubit and Pauli each edge stands for
+    corrections: Vec<(usize, Pauli)>,
+    num_logical_qubits: usize,
+}
+
+impl CodeGraph {
+    /// Panics if some single-qubit error trips more than two checks
+    pub(super) fn new(
+        stabilizers: &[PauliString],
+        logicals: &[(PauliString, PauliString)],
+        channel: &PauliChannel,
+    ) -> Self {
+        let n = stabilizers[0].len();
+        assert!(logicals.len() <= 32, "graph decoders track up to 32 logical qubits");
+        let (p_x, p_y, p_z) = channel.pauli_probabilities();
+        let boundary = stabilizers.len();
+        let mut graph = DecodingGraph::new(stabilizers.len() + 1);
+        graph.set_boundary(boundary);
+
+        let mut corrections = Vec::new();
+        for qubit in 0..n {
+            for (pauli, probability) in [(Pauli::X, p_x + p_y), (Pauli::Z, p_z + p_y)] {
+                let error = PauliString::from_support(n, &[qubit], pauli);
+                let checks: Vec<usize> =
+                    (0..stabilizers.len()).filter(|&i| !error.commutes_with(&stabilizers[i])).collect();
+                let (a, b) = match checks[..] {
+                    [] => continue,
+                    [a] => (a, boundary),
+                    [a, b] => (a, b),
+                    _ => panic!("qubit {} has a {:?} error tripping {} checks", qubit, pauli, checks.len()),
+                };
+                graph.add_fault(a, b, probability, logical_flip_mask(&error, logicals));
+                corrections.push((qubit, pauli));
+            }
+        }
+
+        Self {
+            num_qubits: n,
+            graph,
+            corrections,
+            num_logical_qubits: logicals.len(),
+        }
+    }
+
+    pub(super) fn num_qubits(&self) -> usize {
+        self.num_qubits
+    }
+
+    pub(super) fn graph(&self) -> &DecodingGraph {
+        &self.graph
+    }
+
+    /// Qubit and Pauli an edge stands for
+    pub(super) fn correction(&self, edge: usize) -> (usize, Pauli) {
+        self.corrections[edge]
+    }
+
+    /// The nodes of the checks that fired
+    pub(super) fn defects(syndrome: &[bool]) -> Vec<usize> {
+        (0..syndrome.len()).filter(|&i| syndrome[i]).collect()
+    }
+
+    /// The edges of the errors on any of these qubits
+    pub(super) fn qubit_edges(&self, qubits: &[usize]) -> Vec<usize> {
+        (0..self.corrections.len()).filter(|&edge| qubits.contains(&self.corrections[edge].0)).collect()
+    }
+
+    /// The space-time graph of `rounds` of noisy syndrome readout followed by a perfect
+    /// final round: a layer of check nodes per round and one for the final syndrome, each
+    /// with its own boundary node. Layer t has a copy of every error edge for the errors
+    /// that struck before round t, laid out so that edge e of layer t is edge
+    /// t · num_edges + e. These copies are followed by time-like edges joining each check to
+    /// itself in the next layer, for its outcome in round t being read out wrongly with
+    /// probability `rounds[t].flip_probabilities[check]`. An outcome that cannot be wrong
+    /// gets no time-like edge. Time-like edges flip no logical operator and add nothing to a
+    /// prediction.
+    pub(super) fn with_readout_errors(&self, rounds: &[SoftSyndrome]) -> DecodingGraph {
+        let nodes = self.graph.num_nodes();
+        let checks = nodes - 1;
+        let mut graph = DecodingGraph::new(nodes * (rounds.len() + 1));
+        for layer in 0..=rounds.len() {
+            graph.set_boundary(layer * nodes + checks);
+        }
+        for layer in 0..rounds.len() {
+            for edge in 0..self.graph.num_edges() {
+                let (a, b, weight) = self.graph.edge(edge);
+                graph.add_edge(layer * nodes + a, layer * nodes + b, weight);
+            }
+        }
+        for (layer, round) in rounds.iter().enumerate() {
+            for (check, &probability) in round.flip_probabilities.iter().enumerate() {
+                if probability > 0.0 {
+                    graph.add_fault(layer * nodes + check, (layer + 1) * nodes + check, probability, 0);
+                }
+            }
+        }
+        graph
+    }
+
+    /// The defects of the space-time graph of `with_readout_errors`: the checks whose
+    /// outcome changed from one round to the next, with the final syndrome as the last round
+    pub(super) fn readout_defects(&self, rounds: &[SoftSyndrome], final_syndrome: &[bool]) -> Vec<usize> {
+        let nodes = self.graph.num_nodes();
+        let mut previous = vec![false; final_syndrome.len()];
+        let mut defects = Vec::new();
+        let layers = rounds.iter().map(|round| &round.outcomes[..]).chain([final_syndrome]);
+        for (layer, outcomes) in layers.enumerate() {
+            for check in (0..outcomes.len()).filter(|&check| outcomes[check] != previous[check]) {
+                defects.push(layer * nodes + check);
+            }
+            previous.copy_from_slice(outcomes);
+        }
+        defects
+    }
+
+    /// As `prediction`, for the edges of the space-time graph of `with_readout_errors`: each
+    /// copy of an error edge stands for its error, and the time-like edges add nothing
+    pub(super) fn readout_prediction(&self, rounds: &[SoftSyndrome], edges: &[usize]) -> Prediction {
+        let num_edges = self.graph.num_edges();
+        let mut counts = vec![0usize; num_edges];
+        for &edge in edges.iter().filter(|&&edge| edge < rounds.len() * num_edges) {
+            counts[edge % num_edges] += 1;
+        }
+        let edges: Vec<usize> = (0..num_edges).filter(|&edge| counts[edge] % 2 == 1).collect();
+        self.prediction(&edges)
+    }
+
+    /// The correction made of the errors of these edges, and its logical effect
+    pub(super) fn prediction(&self, edges: &[usize]) -> Prediction {
+        let edges: Vec<usize> = edges.iter().copied().filter(|&edge| edge < self.corrections.len()).collect();
+        let mut correction = PauliString::identity(self.num_qubits);
+        for &edge in &edges {
+            let (qubit, pauli) = self.corrections[edge];
+            correction.set(qubit, correction.get(qubit).multiply(pauli));
+        }
+        let mask = self.graph.observables_of(&edges);
+        let logical_flips = (0..self.num_logical_qubits)
+            .map(|l| Pauli::from_bits(mask >> (2 * l) & 1 == 1, mask >> (2 * l + 1) & 1 == 1))
+            .collect();
+        Prediction { correction, logical_flips }
+    }
+}
+
+/// Bit 2l is set when `error` applies X̄_l, which it does when it anticommutes with Z̄_l, and
+/// bit 2l + 1 when it applies Z̄_l
+fn logical_flip_mask(error: &PauliString, logicals: &[(PauliString, PauliString)]) -> u64 {
+    logicals.iter().enumerate().fold(0, |mask, (l, (logical_x, logical_z))| {
+        mask | u64::from(!error.commutes_with(logical_z)) << (2 * l)
+            | u64::from(!error.commutes_with(logical_x)) << (2 * l + 1)
+    })
+}
@@ -0,0 +1,246 @@
+use crate::decoders::Decoder;
+use crate::error_models::PauliChannel;
+use crate::gf2::EchelonBasis;
+use crate::pauli::{Pauli, PauliString};
+use std::collections::HashMap;
+
+/// A precomputed table from every syndrome to the best correction for it.
+///
+/// Errors are enumerated in order of weight, and each syndrome keeps the cheapest error that
+/// shows it: the lightest one, or the most likely one under a Pauli channel. Enumeration
+/// stops once every syndrome the errors can produce has an entry that no heavier error can
+/// beat. Tables have one entry per syndrome, so they suit codes with up to about 20 qubits.
+#[derive(Clone, Debug)]
+pub struct LookupTableDecoder {
+    num_qubits: usize,
+    /// Syndromes and corrections packed into words, bit i for check or qubit i
+    table: HashMap<u64, PackedCorrection>,
+}
+
+/// X and Z bits of a correction
+#[derive(Clone, Copy, Debug)]
+struct PackedCorrection {
+    x: u64,
+    z: u64,
+}
+
+/// A single-qubit Pauli that errors are built from, with its syndrome and cost
+#[derive(Clone, Copy)]
+struct Fault {
+    pauli: Pauli,
+    syndrome: u64,
+    cost: f64,
+}
+
+/// A table entry while it is being built
+struct Candidate {
+    correction: PackedCorrection,
+    cost: f64,
+}
+
+impl LookupTableDecoder {
+    /// Minimum-weight corrections for the code with these stabilizer generators. Among errors
+    /// of equal weight the first in the enumeration wins, with lower qubits and X, then Z,
+    /// then Y first.
+    pub fn minimum_weight(stabilizers: &[PauliString]) -> Self {
+        Self::build(stabilizers, [(Pauli::X, 1.0), (Pauli::Z, 1.0), (Pauli::Y, 1.0)])
+    }
+
+    /// The most likely correction for each syndrome when every qubit suffers `channel`
+    /// independently. An error with Pauli P_j on qubit j has probability
+    /// (1 − p)^n Π_j p_(P_j) / (1 − p), so the best error minimises Σ_j ln((1 − p) / p_(P_j)).
+    /// Paulis the channel never applies are never used.
+    pub fn most_likely(stabilizers: &[PauliString], channel: &PauliChannel) -> Self {
+        let (p_x, p_y, p_z) = channel.pauli_probabilities();
+        let p_none = 1.0 - channel.probability();
+        let cost = |p: f64| if p > 0.0 { (p_none / p).ln() } else { f64::INFINITY };
+        Self::build(stabilizers, [(Pauli::X, cost(p_x)), (Pauli::Z, cost(p_z)), (Pauli::Y, cost(p_y))])
+    }
+
+    fn build(stabilizers: &[PauliString], costs: [(Pauli, f64); 3]) -> Self {
+        let n = stabilizers[0].len();
+        assert!(n <= 64 && stabilizers.len() < 64, "lookup tables are limited to 64 qubits and 63 checks");
+
+        let faults: Vec<Vec<Fault>> = (0..n)
+            .map(|qubit| {
+                costs
+                    .iter()
+                    .filter(|(_, cost)| cost.is_finite())
+                    .map(|&(pauli, cost)| {
+                        let error = PauliString::from_support(n, &[qubit], pauli);
+                        let syndrome = stabilizers
+                            .iter()
+                            .enumerate()
+                            .filter(|(_, stabilizer)| !error.commutes_with(stabilizer))
+                            .fold(0, |bits, (i, _)| bits | 1 << i);
+                        Fault { pauli, syndrome, cost }
+                    })
+                    .collect()
+            })
+            .collect();
+
+        // The errors reach every syndrome in the span of the single-fault syndromes
+        let mut span = EchelonBasis::new();
+        for fault in faults.iter().flatten() {
+            span.insert(&(0..stabilizers.len()).map(|i| fault.syndrome >> i & 1 == 1).collect::<Vec<_>>());
+        }
+        let reachable = 1u64 << span.rank();
+        let cheapest_fault = faults.iter().flatten().map(|fault| fault.cost).fold(f64::INFINITY, f64::min);
+
+        let mut candidates = HashMap::new();
+        for weight in 0..=n {
+            let highest_cost = candidates.values().map(|c: &Candidate| c.cost).fold(0.0, f64::max);
+            if candidates.len() as u64 == reachable && weight as f64 * cheapest_fault > highest_cost {
+                break;
+            }
+            let identity = PackedCorrection { x: 0, z: 0 };
+            extend(&faults, 0, weight, 0, 0.0, identity, &mut candidates);
+        }
+
+        let table = candidates.into_iter().map(|(syndrome, entry)| (syndrome, entry.correction)).collect();
+        Self { num_qubits: n, table }
+    }
+
+    pub fn num_qubits(&self) -> usize {
+        self.num_qubits
+    }
+
+    /// Number of syndromes in the table
+    pub fn len(&self) -> usize {
+        self.table.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.table.is_empty()
+    }
+
+    /// The table's correction for `syndrome`, if the syndrome can occur
+    pub fn correction(&self, syndrome: &[bool]) -> Option<PauliString> {
+        let key = syndrome.iter().enumerate().fold(0u64, |bits, (i, &bit)| bits | u64::from(bit) << i);
+        self.table.get(&key).map(|correction| {
+            let x = (0..self.num_qubits).map(|q| correction.x >> q & 1 == 1).collect();
+            let z = (0..self.num_qubits).map(|q| correction.z >> q & 1 == 1).collect();
+            PauliString::from_bits(x, z)
+        })
+    }
+}
+
+impl Decoder for LookupTableDecoder {
+    /// The table's correction, or the identity for a syndrome that no error produces
+    fn decode(&mut self, syndrome: &[bool]) -> PauliString {
+        self.correction(syndrome).unwrap_or_else(|| PauliString::identity(self.num_qubits))
+    }
+}
+
+/// Visits every error made of `remaining` more faults on qubits from `first_qubit` on,
+/// keeping each syndrome's cheapest error
+fn extend(
+    faults: &[Vec<Fault>],
+    first_qubit: usize,
+    remaining: usize,
+    syndrome: u64,
+    cost: f64,
+    correction: PackedCorrection,
+    candidates: &mut HashMap<u64, Candidate>,
+) {
+    if remaining == 0 {
+        let entry = candidates.entry(syndrome).or_insert(Candidate { correction, cost });
+        if cost < entry.cost {
+            *entry = Candidate { correction, cost };
+        }
+        return;
+    }
+    let last_qubit = faults.len() - remaining;
+    for (qubit, qubit_faults) in faults.iter().enumerate().take(last_qubit + 1).skip(first_qubit) {
+        for fault in qubit_faults {
+            let extended = PackedCorrection {
+                x: correction.x | u64::from(fault.pauli.x_bit()) << qubit,
+                z: correction.z | u64::from(fault.pauli.z_bit()) << qubit,
+            };
+            let extended_syndrome = syndrome ^ fault.syndrome;
+            let extended_cost = cost + fault.cost;
+            extend(faults, qubit + 1, remaining - 1, extended_syndrome, extended_cost, extended, candidates);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::correction_codes::{CorrectionCode, CssCode, StabilizerCode};
+
+    /// Every n-qubit Pauli with its syndrome
+    fn all_errors(stabilizers: &[PauliString]) -> Vec<(PauliString, Vec<bool>)> {
+        let n = stabilizers[0].len();
+        (0..1usize << (2 * n))
+            .map(|index| {
+                let x = (0..n).map(|q| index >> (2 * q) & 1 == 1).collect();
+                let z = (0..n).map(|q| index >> (2 * q + 1) & 1 == 1).collect();
+                let error = PauliString::from_bits(x, z);
+                let syndrome = stabilizers.iter().map(|s| !error.commutes_with(s)).collect();
+                (error, syndrome)
+            })
+            .collect()
+    }
+
+    fn small_codes() -> Vec<Vec<PauliString>> {
+        let repetition = vec![
+            PauliString::from_support(3, &[0, 1], Pauli::Z),
+            PauliString::from_support(3, &[1, 2], Pauli::Z),
+        ];
+        vec![repetition, StabilizerCode::five_qubit().generators().to_vec(), CssCode::steane().stabilizer_generators()]
+    }
+
+    /// Checks that the table covers exactly the syndromes some error shows, and that each
+    /// entry shows its syndrome at the lowest cost of any error that does
+    fn assert_cheapest(decoder: &LookupTableDecoder, stabilizers: &[PauliString], cost: impl Fn(&PauliString) -> f64) {
+        let mut cheapest: HashMap<Vec<bool>, f64> = HashMap::new();
+        for (error, syndrome) in all_errors(stabilizers) {
+            let entry = cheapest.entry(syndrome).or_insert(f64::INFINITY);
+            *entry = entry.min(cost(&error));
+        }
+        assert_eq!(decoder.len(), cheapest.len());
+        for (syndrome, best) in cheapest {
+            let correction = decoder.correction(&syndrome).unwrap();
+            let shown: Vec<bool> = stabilizers.iter().map(|s| !correction.commutes_with(s)).collect();
+            assert_eq!(shown, syndrome);
+            assert!((cost(&correction) - best).abs() < 1e-9, "{:?}: {} > {}", syndrome, cost(&correction), best);
+        }
+    }
+
+    #[test]
+    fn minimum_weight_matches_brute_force() {
+        for stabilizers in small_codes() {
+            let decoder = LookupTableDecoder::minimum_weight(&stabilizers);
+            assert_cheapest(&decoder, &stabilizers, |error| error.weight() as f64);
+        }
+    }
+
+    #[test]
+    fn most_likely_matches_brute_force() {
+        let channel = PauliChannel::new(0.01, 0.002, 0.05);
+        let (p_x, p_y, p_z) = channel.pauli_probabilities();
+        let p_none = 1.0 - channel.probability();
+        let cost = |error: &PauliString| {
+            (0..error.len())
+                .map(|q| match error.get(q) {
+                    Pauli::I => 0.0,
+                    Pauli::X => (p_none / p_x).ln(),
+                    Pauli::Y => (p_none / p_y).ln(),
+                    Pauli::Z => (p_none / p_z).ln(),
+                })
+                .sum::<f64>()
+        };
+        for stabilizers in small_codes() {
+            let decoder = LookupTableDecoder::most_likely(&stabilizers, &channel);
+            assert_cheapest(&decoder, &stabilizers, cost);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "63 checks")]
+    fn rejects_64_checks() {
+        let check = PauliString::from_support(1, &[0], Pauli::Z);
+        LookupTableDecoder::minimum_weight(&vec![check; 64]);
+    }
+}
@@ -0,0 +1,266 @@
+use super::code_graph::{CodeGraph, Prediction};
+use crate::circuits::{DetectorErrorModel, DetectorErrorModelError};
+use crate::decoders::{Decoder, DetectorDecoder};
+use crate::error_models::PauliChannel;
+use crate::hardware::SoftSyndrome;
+use crate::matching::{log_likelihood_weight, DecodingGraph};
+use crate::pauli::{Pauli, PauliString};
+
+/// Minimum-weight perfect matching on the decoding graph of a code whose single-qubit X and Z
+/// errors each trip at most two checks, as in repetition, surface, toric and XZZX codes.
+///
+/// Every check is a node, with one extra boundary node for errors that trip a single check.
+/// Each qubit contributes an X edge and a Z edge, weighted by the log-likelihood of the X and
+/// Z components of the channel, so a Y error counts as both. Edges also record the logical
+/// operators their error flips, which gives the decoder's prediction of the logical error
+/// along with the correction.
+#[derive(Clone, Debug)]
+pub struct MatchingDecoder {
+    graph: CodeGraph,
+}
+
+impl MatchingDecoder {
+    /// The decoder for the code with these stabilizer generators and logical operators, under
+    /// `channel` on every qubit. Panics if some single-qubit error trips more than two checks.
+    pub fn new(
+        stabilizers: &[PauliString],
+        logicals: &[(PauliString, PauliString)],
+        channel: &PauliChannel,
+    ) -> Self {
+        Self {
+            graph: CodeGraph::new(stabilizers, logicals, channel),
+        }
+    }
+
+    pub fn num_qubits(&self) -> usize {
+        self.graph.num_qubits()
+    }
+
+    /// The graph being matched, with the boundary as its last node. Its edges carry each
+    /// error's probability and logical flips, bit 2l for X̄_l and bit 2l + 1 for Z̄_l, so
+    /// `DecodingGraph::repeated` turns it into the graph of a memory experiment.
+    pub fn graph(&self) -> &DecodingGraph {
+        self.graph.graph()
+    }
+
+    /// The correction for `syndrome` and the logical error it predicts
+    pub fn predict(&self, syndrome: &[bool]) -> Prediction {
+        let edges = self.graph.graph().match_defects(&CodeGraph::defects(syndrome));
+        self.graph.prediction(&edges)
+    }
+
+    /// The correction for `rounds` of syndrome readout, each outcome wrong with its flip
+    /// probability, followed by the perfectly read `final_syndrome`, and the logical error it
+    /// predicts. Matching runs on the space-time graph of the rounds, where a change in a
+    /// check's outcome can also be explained by a readout error at the cost of its
+    /// log-likelihood, so unreliable outcomes are cheap to join across rounds.
+    pub fn predict_soft(&self, rounds: &[SoftSyndrome], final_syndrome: &[bool]) -> Prediction {
+        let graph = self.graph.with_readout_errors(rounds);
+        let edges = graph.match_defects(&self.graph.readout_defects(rounds, final_syndrome));
+        self.graph.readout_prediction(rounds, &edges)
+    }
+}
+
+impl Decoder for MatchingDecoder {
+    fn decode(&mut self, syndrome: &[bool]) -> PauliString {
+        self.predict(syndrome).correction
+    }
+
+    fn decode_soft(&mut self, rounds: &[SoftSyndrome], final_syndrome: &[bool]) -> PauliString {
+        self.predict_soft(rounds, final_syndrome).correction
+    }
+}
+
+/// Matching that accounts for Y errors tripping both X and Z checks.
+///
+/// `MatchingDecoder` weights each X and Z edge by the marginal probability of its component,
+/// so under depolarizing noise it treats the X and Z parts of an error as independent and
+/// pays twice for a Y. This decoder matches once, then reweights every edge by the
+/// probability of its component given whether the other component of the same qubit was
+/// matched: p_Y / (p_Y + p_Z) for the X edge of a qubit whose Z edge was used, and
+/// p_X / (1 - p_Y - p_Z) otherwise, and likewise for Z edges. Matching again on these weights
+/// makes a Y cheaper than an X and a Z elsewhere. The reweighting repeats until the matching
+/// stops changing or `max_iterations` rounds are done.
+#[derive(Clone, Debug)]
+pub struct CorrelatedMatchingDecoder {
+    graph: CodeGraph,
+    /// The edge of the other Pauli on the same qubit, for each edge that has one
+    partners: Vec<Option<usize>>,
+    /// Weight of each edge when its partner is matched and when it is not
+    conditional_weights: Vec<(f64, f64)>,
+    /// The graph with the weights of the current round
+    reweighted: DecodingGraph,
+    max_iterations: usize,
+}
+
+impl CorrelatedMatchingDecoder {
+    /// The decoder for the code with these stabilizer generators and logical operators, under
+    /// `channel` on every qubit, with up to two rounds of reweighting. Panics if some
+    /// single-qubit error trips more than two checks.
+    pub fn new(
+        stabilizers: &[PauliString],
+        logicals: &[(PauliString, PauliString)],
+        channel: &PauliChannel,
+    ) -> Self {
+        let graph = CodeGraph::new(stabilizers, logicals, channel);
+        let num_edges = graph.graph().num_edges();
+        let mut edge_of = vec![[None; 2]; graph.num_qubits()];
+        for edge in 0..num_edges {
+            let (qubit, pauli) = graph.correction(edge);
+            edge_of[qubit][usize::from(pauli == Pauli::Z)] = Some(edge);
+        }
+        let partners = (0..num_edges)
+            .map(|edge| {
+                let (qubit, pauli) = graph.correction(edge);
+                edge_of[qubit][usize::from(pauli == Pauli::X)]
+            })
+            .collect();
+
+        let (p_x, p_y, p_z) = channel.pauli_probabilities();
+        let conditional_weights = (0..num_edges)
+            .map(|edge| {
+                let (own, other) = match graph.correction(edge).1 {
+                    Pauli::X => (p_x, p_z),
+                    _ => (p_z, p_x),
+                };
+                let conditional = |p: f64, total: f64| if p > 0.0 { p / total } else { 0.0 };
+                (
+                    log_likelihood_weight(conditional(p_y, p_y + other)),
+                    log_likelihood_weight(conditional(own, 1.0 - p_y - other)),
+                )
+            })
+            .collect();
+
+        Self {
+            reweighted: graph.graph().clone(),
+            graph,
+            partners,
+            conditional_weights,
+            max_iterations: 2,
+        }
+    }
+
+    /// Rounds of reweighting after the first matching; zero makes this `MatchingDecoder`
+    pub fn with_max_iterations(mut self, max_iterations: usize) -> Self {
+        self.max_iterations = max_iterations;
+        self
+    }
+
+    pub fn num_qubits(&self) -> usize {
+        self.graph.num_qubits()
+    }
+
+    /// The correction for `syndrome` and the logical error it predicts
+    pub fn predict(&mut self, syndrome: &[bool]) -> Prediction {
+        let defects = CodeGraph::defects(syndrome);
+        let mut edges = self.graph.graph().match_defects(&defects);
+        for _ in 0..self.max_iterations {
+            let mut matched = vec![false; self.partners.len()];
+            for &edge in &edges {
+                matched[edge] = true;
+            }
+            for (edge, partner) in self.partners.iter().enumerate() {
+                if let Some(partner) = *partner {
+                    let (given, not_given) = self.conditional_weights[edge];
+                    self.reweighted.set_weight(edge, if matched[partner] { given } else { not_given });
+                }
+            }
+            let rematched = self.reweighted.match_defects(&defects);
+            if rematched == edges {
+                break;
+            }
+            edges = rematched;
+        }
+        self.graph.prediction(&edges)
+    }
+}
+
+impl Decoder for CorrelatedMatchingDecoder {
+    fn decode(&mut self, syndrome: &[bool]) -> PauliString {
+        self.predict(syndrome).correction
+    }
+}
+
+/// Minimum-weight perfect matching on the decoding graph of a detector error model, such as
+/// one extracted from a noisy circuit
+#[derive(Clone, Debug)]
+pub struct DemMatchingDecoder {
+    graph: DecodingGraph,
+    /// Mechanism of the model each edge stands for
+    sources: Vec<usize>,
+}
+
+impl DemMatchingDecoder {
+    /// Fails if some mechanism cannot be split into edges of the graph
+    pub fn new(model: &DetectorErrorModel) -> Result<Self, DetectorErrorModelError> {
+        let (graph, sources) = model.decoding_graph_with_sources()?;
+        Ok(Self { graph, sources })
+    }
+
+    pub fn graph(&self) -> &DecodingGraph {
+        &self.graph
+    }
+}
+
+impl DetectorDecoder for DemMatchingDecoder {
+    fn decode_detectors(&mut self, flipped: &[usize]) -> Vec<usize> {
+        self.graph.match_defects(flipped).into_iter().map(|edge| self.sources[edge]).collect()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::correction_codes::{CorrectionCode, ToricCode, XzzxCode};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn syndrome(stabilizers: &[PauliString], error: &PauliString) -> Vec<bool> {
+        stabilizers.iter().map(|s| !error.commutes_with(s)).collect()
+    }
+
+    #[test]
+    fn correlated_matching_corrects_a_y_chain_that_matching_does_not() {
+        let code = XzzxCode::new(5);
+        let (stabilizers, logicals) = (code.stabilizer_generators(), code.logical_operators());
+        let channel = PauliChannel::new(0.05, 0.05, 0.05);
+        let error = PauliString::from_support(code.num_qubits(), &[0, 1, 2], Pauli::Y);
+        let syndrome = syndrome(&stabilizers, &error);
+        let is_trivial = |correction: &PauliString| {
+            let residual = correction.product(&error);
+            logicals.iter().all(|(x, z)| residual.commutes_with(x) && residual.commutes_with(z))
+        };
+
+        // Matching pays for the X and Z parts of the three Ys apart, and explains them with
+        // lighter chains that complete a logical; once a part is matched the other is cheap
+        let plain = MatchingDecoder::new(&stabilizers, &logicals, &channel).predict(&syndrome);
+        assert!(!is_trivial(&plain.correction));
+        let correlated = CorrelatedMatchingDecoder::new(&stabilizers, &logicals, &channel).predict(&syndrome);
+        assert!(is_trivial(&correlated.correction));
+    }
+
+    #[test]
+    fn no_reweighting_is_plain_matching() {
+        let toric = ToricCode::new(5);
+        let xzzx = XzzxCode::new(5);
+        let channel = PauliChannel::new(0.02, 0.05, 0.03);
+        let mut rng = StdRng::seed_from_u64(49);
+        for code in [&toric as &dyn CorrectionCode, &xzzx] {
+            let (stabilizers, logicals) = (code.stabilizer_generators(), code.logical_operators());
+            let plain = MatchingDecoder::new(&stabilizers, &logicals, &channel);
+            let mut unweighted =
+                CorrelatedMatchingDecoder::new(&stabilizers, &logicals, &channel).with_max_iterations(0);
+            for _ in 0..200 {
+                let mut error = PauliString::identity(code.num_qubits());
+                for qubit in 0..code.num_qubits() {
+                    if rng.gen_bool(0.1) {
+                        error.set(qubit, [Pauli::X, Pauli::Y, Pauli::Z][rng.gen_range(0..3)]);
+                    }
+                }
+                let syndrome = syndrome(&stabilizers, &error);
+                assert_eq!(unweighted.predict(&syndrome), plain.predict(&syndrome), "{}", error);
+            }
+        }
+    }
+}
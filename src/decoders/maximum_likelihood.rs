@@ -0,0 +1,455 @@
+use crate::decoders::Decoder;
+use crate::error_models::PauliChannel;
+use crate::gf2::{BinaryMatrix, EchelonBasis};
+use crate::pauli::PauliString;
+
+/// Largest number of group generators the brute-force sum enumerates the products of
+const MAX_BRUTE_FORCE_GENERATORS: usize = 30;
+
+/// Largest number of logical qubits, whose 4^k cosets are each contracted separately
+const MAX_TENSOR_NETWORK_LOGICAL_QUBITS: usize = 4;
+
+/// How the probability of each coset of the stabilizer group is summed up
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CosetSum {
+    /// Every product of the generators is enumerated, in Gray-code order
+    BruteForce,
+    /// The sum over generator subsets is contracted as a tensor network, eliminating one
+    /// generator at a time
+    TensorNetwork,
+}
+
+/// The optimal, degenerate decoder: the correction is the most likely logical class of
+/// errors with the syndrome, not the most likely single error.
+///
+/// Errors that differ by a stabilizer act identically, so what a decoder has to get right is
+/// the coset E·L·S of the stabilizer group S, for a fixed error E with the syndrome and each
+/// logical operator L. The decoder sums the probability of every error in each coset and
+/// returns E·L for the most likely one, which no decoder can beat on average. Minimum-weight
+/// decoders pick the coset of the single most likely error instead, and fall short of this
+/// whenever a coset with many likely errors outweighs it.
+#[derive(Clone, Debug)]
+pub struct MaximumLikelihoodDecoder {
+    num_qubits: usize,
+    /// Row i, multiplied by the X bits and then the Z bits of an error, gives syndrome bit i
+    syndrome_map: BinaryMatrix,
+    /// An independent subset of the stabilizer generators
+    generators: Vec<PauliString>,
+    /// X̄_0, Z̄_0, X̄_1, Z̄_1, …; logical class t applies those whose bit is set in t
+    logical_generators: Vec<PauliString>,
+    /// Probability of I, X, Z and Y on each qubit, indexed by x + 2z
+    pauli_probabilities: [f64; 4],
+    method: CosetSum,
+    /// Generators of the tensor network in the order they are summed out
+    elimination_order: Vec<usize>,
+}
+
+/// A tensor with one binary index per generator in `generators`, bit i of a position into
+/// `entries` being the value of `generators[i]`
+struct Tensor {
+    generators: Vec<usize>,
+    entries: Vec<f64>,
+}
+
+impl MaximumLikelihoodDecoder {
+    /// Brute force for the code with these stabilizer generators and logical operators, under
+    /// `channel` on every qubit. Each decoding enumerates 2^(n + k) Paulis, so this suits
+    /// codes of up to about 20 qubits.
+    pub fn brute_force(
+        stabilizers: &[PauliString],
+        logicals: &[(PauliString, PauliString)],
+        channel: &PauliChannel,
+    ) -> Self {
+        let decoder = Self::new(stabilizers, logicals, channel, CosetSum::BruteForce);
+        assert!(
+            decoder.generators.len() + decoder.logical_generators.len() <= MAX_BRUTE_FORCE_GENERATORS,
+            "the code is too large to decode by brute force"
+        );
+        decoder
+    }
+
+    /// Tensor-network contraction for the code with these stabilizer generators and logical
+    /// operators, under `channel` on every qubit. The network has a tensor per qubit and an
+    /// index per generator, and it is contracted exactly, so the cost grows exponentially in
+    /// the largest set of generators that ever has to be held together. For planar codes
+    /// such as the XZZX code that is about a row of the lattice, which keeps codes of
+    /// distance up to about 9 fast.
+    pub fn tensor_network(
+        stabilizers: &[PauliString],
+        logicals: &[(PauliString, PauliString)],
+        channel: &PauliChannel,
+    ) -> Self {
+        assert!(
+            logicals.len() <= MAX_TENSOR_NETWORK_LOGICAL_QUBITS,
+            "the tensor network decoder handles up to {} logical qubits",
+            MAX_TENSOR_NETWORK_LOGICAL_QUBITS
+        );
+        Self::new(stabilizers, logicals, channel, CosetSum::TensorNetwork)
+    }
+
+    fn new(
+        stabilizers: &[PauliString],
+        logicals: &[(PauliString, PauliString)],
+        channel: &PauliChannel,
+        method: CosetSum,
+    ) -> Self {
+        let n = stabilizers[0].len();
+        let syndrome_map = BinaryMatrix::from_rows(
+            2 * n,
+            stabilizers
+                .iter()
+                .map(|stabilizer| stabilizer.z_bits().iter().chain(stabilizer.x_bits()).copied().collect())
+                .collect(),
+        );
+
+        let mut basis = EchelonBasis::new();
+        let generators: Vec<PauliString> = stabilizers
+            .iter()
+            .filter(|stabilizer| {
+                let vector: Vec<bool> =
+                    stabilizer.x_bits().iter().chain(stabilizer.z_bits()).copied().collect();
+                basis.insert(&vector)
+            })
+            .cloned()
+            .collect();
+        let logical_generators =
+            logicals.iter().flat_map(|(x, z)| [x.clone(), z.clone()]).collect();
+
+        let (p_x, p_y, p_z) = channel.pauli_probabilities();
+        let mut decoder = Self {
+            num_qubits: n,
+            syndrome_map,
+            generators,
+            logical_generators,
+            pauli_probabilities: [1.0 - channel.probability(), p_x, p_z, p_y],
+            method,
+            elimination_order: Vec::new(),
+        };
+        if method == CosetSum::TensorNetwork {
+            decoder.elimination_order = decoder.greedy_elimination_order();
+        }
+        decoder
+    }
+
+    pub fn num_qubits(&self) -> usize {
+        self.num_qubits
+    }
+
+    /// The probability of each logical class given the syndrome: entry t is the chance that
+    /// the error lies in the coset of E·L_t, where E is `pure_error(syndrome)` and L_t applies
+    /// X̄_l when bit 2l of t is set and Z̄_l when bit 2l + 1 is. `None` if no error has the
+    /// syndrome.
+    pub fn coset_probabilities(&self, syndrome: &[bool]) -> Option<Vec<f64>> {
+        self.classify(syndrome).map(|(_, probabilities)| probabilities)
+    }
+
+    /// Some error with this syndrome, fixed for each syndrome, or `None` if there is none
+    pub fn pure_error(&self, syndrome: &[bool]) -> Option<PauliString> {
+        let mut bits = self.syndrome_map.solve(syndrome)?;
+        let z = bits.split_off(self.num_qubits);
+        Some(PauliString::from_bits(bits, z))
+    }
+
+    /// The pure error of the syndrome and the probability of each logical class relative to it
+    fn classify(&self, syndrome: &[bool]) -> Option<(PauliString, Vec<f64>)> {
+        let pure_error = self.pure_error(syndrome)?;
+        let log_probabilities = match self.method {
+            CosetSum::BruteForce => self.brute_force_sums(&pure_error),
+            CosetSum::TensorNetwork => (0..1 << self.logical_generators.len())
+                .map(|class| self.contract(&self.with_logical(&pure_error, class)))
+                .collect(),
+        };
+        let largest = log_probabilities.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        if largest == f64::NEG_INFINITY {
+            return None;
+        }
+        let weights: Vec<f64> = log_probabilities.iter().map(|&log| (log - largest).exp()).collect();
+        let total: f64 = weights.iter().sum();
+        Some((pure_error, weights.iter().map(|weight| weight / total).collect()))
+    }
+
+    fn with_logical(&self, error: &PauliString, class: usize) -> PauliString {
+        let mut error = error.clone();
+        for (i, logical) in self.logical_generators.iter().enumerate() {
+            if class >> i & 1 == 1 {
+                error.multiply(logical);
+            }
+        }
+        error
+    }
+
+    fn probability_of(&self, qubit_x: bool, qubit_z: bool) -> f64 {
+        self.pauli_probabilities[usize::from(qubit_x) | usize::from(qubit_z) << 1]
+    }
+
+    /// Walks through the group generated by the stabilizers and logical operators in Gray-code
+    /// order, one multiplication per step, adding each error's probability to its class
+    fn brute_force_sums(&self, pure_error: &PauliString) -> Vec<f64> {
+        let group: Vec<&PauliString> = self.generators.iter().chain(&self.logical_generators).collect();
+        let mut sums = vec![0.0; 1 << self.logical_generators.len()];
+        let mut error = pure_error.clone();
+        let mut class = 0;
+        for step in 0u64..1 << group.len() {
+            if step > 0 {
+                let flipped = step.trailing_zeros() as usize;
+                error.multiply(group[flipped]);
+                if flipped >= self.generators.len() {
+                    class ^= 1 << (flipped - self.generators.len());
+                }
+            }
+            sums[class] += error
+                .x_bits()
+                .iter()
+                .zip(error.z_bits())
+                .map(|(&x, &z)| self.probability_of(x, z))
+                .product::<f64>();
+        }
+        sums.iter().map(|sum| sum.ln()).collect()
+    }
+
+    /// The generators each qubit's tensor depends on
+    fn qubit_generators(&self) -> Vec<Vec<usize>> {
+        (0..self.num_qubits)
+            .map(|qubit| {
+                (0..self.generators.len())
+                    .filter(|&j| self.generators[j].x_bits()[qubit] || self.generators[j].z_bits()[qubit])
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Repeatedly sums out the generator that leaves the smallest tensor behind
+    fn greedy_elimination_order(&self) -> Vec<usize> {
+        let mut scopes = self.qubit_generators();
+        let mut remaining: Vec<usize> = (0..self.generators.len()).collect();
+        let mut order = Vec::new();
+        while !remaining.is_empty() {
+            let merged_scope = |generator: usize| {
+                let mut scope: Vec<usize> = scopes
+                    .iter()
+                    .filter(|scope| scope.contains(&generator))
+                    .flatten()
+                    .copied()
+                    .filter(|&j| j != generator)
+                    .collect();
+                scope.sort_unstable();
+                scope.dedup();
+                scope
+            };
+            let position = (0..remaining.len())
+                .min_by_key(|&i| merged_scope(remaining[i]).len())
+                .expect("generators remain");
+            let generator = remaining.swap_remove(position);
+            let scope = merged_scope(generator);
+            scopes.retain(|scope| !scope.contains(&generator));
+            scopes.push(scope);
+            order.push(generator);
+        }
+        order
+    }
+
+    /// Log of the total probability of the coset of `error`: the sum over every subset of the
+    /// generators of the probability of `error` times their product. That probability is a
+    /// product over qubits, each factor depending only on the generators acting on the
+    /// qubit, so it is a tensor network that can be summed one generator at a time.
+    fn contract(&self, error: &PauliString) -> f64 {
+        let mut tensors: Vec<Tensor> = self
+            .qubit_generators()
+            .into_iter()
+            .enumerate()
+            .map(|(qubit, generators)| {
+                let entries = (0..1usize << generators.len())
+                    .map(|assignment| {
+                        let (mut x, mut z) = (error.x_bits()[qubit], error.z_bits()[qubit]);
+                        for (i, &j) in generators.iter().enumerate() {
+                            if assignment >> i & 1 == 1 {
+                                x ^= self.generators[j].x_bits()[qubit];
+                                z ^= self.generators[j].z_bits()[qubit];
+                            }
+                        }
+                        self.probability_of(x, z)
+                    })
+                    .collect();
+                Tensor { generators, entries }
+            })
+            .collect();
+
+        // Tensors are rescaled to a largest entry of 1 as they are made, to avoid underflow
+        let mut log_scale = 0.0;
+        for &generator in &self.elimination_order {
+            let (involved, rest): (Vec<Tensor>, Vec<Tensor>) =
+                tensors.into_iter().partition(|tensor| tensor.generators.contains(&generator));
+            tensors = rest;
+            let mut scope: Vec<usize> = involved
+                .iter()
+                .flat_map(|tensor| tensor.generators.iter().copied())
+                .filter(|&j| j != generator)
+                .collect();
+            scope.sort_unstable();
+            scope.dedup();
+
+            // Where each index of each involved tensor sits in the scope, with the generator
+            // being summed out placed last
+            let positions: Vec<Vec<usize>> = involved
+                .iter()
+                .map(|tensor| {
+                    tensor
+                        .generators
+                        .iter()
+                        .map(|j| scope.iter().position(|k| k == j).unwrap_or(scope.len()))
+                        .collect()
+                })
+                .collect();
+            let mut entries: Vec<f64> = (0..1usize << scope.len())
+                .map(|assignment| {
+                    (0..2usize)
+                        .map(|value| {
+                            let full = assignment | value << scope.len();
+                            involved
+                                .iter()
+                                .zip(&positions)
+                                .map(|(tensor, positions)| {
+                                    let index = positions
+                                        .iter()
+                                        .enumerate()
+                                        .fold(0, |index, (i, &position)| index | (full >> position & 1) << i);
+                                    tensor.entries[index]
+                                })
+                                .product::<f64>()
+                        })
+                        .sum()
+                })
+                .collect();
+
+            let largest = entries.iter().copied().fold(0.0, f64::max);
+            if largest == 0.0 {
+                return f64::NEG_INFINITY;
+            }
+            for entry in entries.iter_mut() {
+                *entry /= largest;
+            }
+            log_scale += largest.ln();
+            tensors.push(Tensor { generators: scope, entries });
+        }
+
+        // Only scalars are left, from qubits no generator acts on and from the last sums
+        log_scale + tensors.iter().map(|tensor| tensor.entries[0].ln()).sum::<f64>()
+    }
+}
+
+impl Decoder for MaximumLikelihoodDecoder {
+    fn decode(&mut self, syndrome: &[bool]) -> PauliString {
+        let Some((pure_error, probabilities)) = self.classify(syndrome) else {
+            return PauliString::identity(self.num_qubits);
+        };
+        let best = (0..probabilities.len())
+            .max_by(|&a, &b| probabilities[a].total_cmp(&probabilities[b]))
+            .unwrap_or(0);
+        self.with_logical(&pure_error, best)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::correction_codes::{CorrectionCode, CssCode, StabilizerCode, XzzxCode};
+    use crate::decoders::LookupTableDecoder;
+    use std::collections::HashMap;
+
+    fn channel() -> PauliChannel {
+        PauliChannel::new(0.02, 0.01, 0.06)
+    }
+
+    /// Stabilizer generators and logical operators of a code
+    type CodeOperators = (Vec<PauliString>, Vec<(PauliString, PauliString)>);
+
+    fn small_codes() -> Vec<CodeOperators> {
+        [
+            Box::new(StabilizerCode::five_qubit()) as Box<dyn CorrectionCode>,
+            Box::new(CssCode::steane()),
+            Box::new(XzzxCode::new(3)),
+        ]
+        .iter()
+        .map(|code| (code.stabilizer_generators(), code.logical_operators()))
+        .collect()
+    }
+
+    /// Every n-qubit Pauli with its probability under `channel`
+    fn all_errors(n: usize, channel: &PauliChannel) -> impl Iterator<Item = (PauliString, f64)> + '_ {
+        let (p_x, p_y, p_z) = channel.pauli_probabilities();
+        let probabilities = [1.0 - channel.probability(), p_x, p_z, p_y];
+        (0..1usize << (2 * n)).map(move |index| {
+            let x: Vec<bool> = (0..n).map(|q| index >> (2 * q) & 1 == 1).collect();
+            let z: Vec<bool> = (0..n).map(|q| index >> (2 * q + 1) & 1 == 1).collect();
+            let probability = (0..n).map(|q| probabilities[usize::from(x[q]) | usize::from(z[q]) << 1]).product();
+            (PauliString::from_bits(x, z), probability)
+        })
+    }
+
+    fn syndrome(error: &PauliString, stabilizers: &[PauliString]) -> Vec<bool> {
+        stabilizers.iter().map(|stabilizer| !error.commutes_with(stabilizer)).collect()
+    }
+
+    /// The logical class of `error` relative to `reference`, numbered as in
+    /// `coset_probabilities`
+    fn class(error: &PauliString, reference: &PauliString, logicals: &[(PauliString, PauliString)]) -> usize {
+        let relative = error.product(reference);
+        logicals.iter().enumerate().fold(0, |class, (l, (x, z))| {
+            let applies_x = !relative.commutes_with(z);
+            let applies_z = !relative.commutes_with(x);
+            class | usize::from(applies_x) << (2 * l) | usize::from(applies_z) << (2 * l + 1)
+        })
+    }
+
+    #[test]
+    fn coset_probabilities_match_a_sum_over_every_error() {
+        for (stabilizers, logicals) in small_codes() {
+            let n = stabilizers[0].len();
+            let decoders = [
+                MaximumLikelihoodDecoder::brute_force(&stabilizers, &logicals, &channel()),
+                MaximumLikelihoodDecoder::tensor_network(&stabilizers, &logicals, &channel()),
+            ];
+            let mut sums: HashMap<Vec<bool>, Vec<f64>> = HashMap::new();
+            for (error, probability) in all_errors(n, &channel()) {
+                let syndrome = syndrome(&error, &stabilizers);
+                let pure_error = decoders[0].pure_error(&syndrome).unwrap();
+                let class = class(&error, &pure_error, &logicals);
+                sums.entry(syndrome).or_insert_with(|| vec![0.0; 1 << (2 * logicals.len())])[class] += probability;
+            }
+            for (syndrome, sums) in sums {
+                let total: f64 = sums.iter().sum();
+                for decoder in &decoders {
+                    let probabilities = decoder.coset_probabilities(&syndrome).unwrap();
+                    for (probability, sum) in probabilities.iter().zip(&sums) {
+                        assert!((probability - sum / total).abs() < 1e-9, "{:?}: {:?}", syndrome, probabilities);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn maximum_likelihood_beats_the_most_likely_error() {
+        for (stabilizers, logicals) in small_codes() {
+            let n = stabilizers[0].len();
+            let mut maximum_likelihood = MaximumLikelihoodDecoder::brute_force(&stabilizers, &logicals, &channel());
+            let mut lookup = LookupTableDecoder::most_likely(&stabilizers, &channel());
+            let mut corrections: HashMap<Vec<bool>, [PauliString; 2]> = HashMap::new();
+            let (mut maximum_likelihood_success, mut lookup_success) = (0.0, 0.0);
+            for (error, probability) in all_errors(n, &channel()) {
+                let syndrome = syndrome(&error, &stabilizers);
+                let [ml, table] = corrections
+                    .entry(syndrome.clone())
+                    .or_insert_with(|| [maximum_likelihood.decode(&syndrome), lookup.decode(&syndrome)]);
+                assert_eq!(self::syndrome(ml, &stabilizers), syndrome);
+                if class(&error, ml, &logicals) == 0 {
+                    maximum_likelihood_success += probability;
+                }
+                if class(&error, table, &logicals) == 0 {
+                    lookup_success += probability;
+                }
+            }
+            assert!(maximum_likelihood_success >= lookup_success - 1e-12);
+        }
+    }
+}
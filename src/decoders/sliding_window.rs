@@ -0,0 +1,309 @@
+use crate::circuits::{DetectorErrorModel, DetectorErrorModelError};
+use crate::decoders::DetectorDecoder;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Builds the decoder of one window from the window's detector error model
+pub type WindowDecoderFactory<'a> =
+    dyn Fn(&DetectorErrorModel) -> Result<Box<dyn DetectorDecoder>, DetectorErrorModelError> + 'a;
+
+/// Streaming decoding of a long memory experiment, a window of rounds at a time.
+///
+/// Detectors are grouped into rounds of `detectors_per_round`, in index order, as in
+/// `NoisyCircuit::memory_experiment`. Each window covers `commit_rounds` rounds followed by
+/// `buffer_rounds` more, and holds the mechanisms whose first detector lies in it, with
+/// detectors past its end left out, so that they act as a boundary. The window's decoder
+/// explains the flipped detectors, and only the mechanisms starting in the commit region
+/// are kept: their observables are added to the prediction and all their detectors are
+/// flipped, which carries their effect into the next window, starting right after the
+/// commit region. The buffer lets errors near the end of the commit region be seen in
+/// full before they are committed to. The last window commits everything it decodes.
+///
+/// Windows with the same model up to a shift of detectors, as in the bulk of a memory
+/// experiment, share one decoder, so any `DetectorDecoder` can be plugged in.
+pub struct SlidingWindowDecoder {
+    model: DetectorErrorModel,
+    detectors_per_round: usize,
+    commit_rounds: usize,
+    windows: Vec<Window>,
+    decoders: Vec<Box<dyn DetectorDecoder>>,
+}
+
+/// Detectors, observables and probability bits of every mechanism of a window's model, which
+/// identify windows that can share a decoder
+type WindowSignature = Vec<(Vec<usize>, Vec<usize>, u64)>;
+
+/// The rounds a window covers and the mechanisms it decodes
+struct Window {
+    start: usize,
+    commit_end: usize,
+    end: usize,
+    /// Mechanism of the whole model behind each mechanism of the window's model
+    mechanisms: Vec<usize>,
+    decoder: usize,
+}
+
+/// What streaming decoding made of one experiment
+#[derive(Clone, Debug, PartialEq)]
+pub struct WindowedDecoding {
+    /// The observables predicted to have flipped, bit i for observable i
+    pub observables: u64,
+    /// Time taken to decode each window
+    pub window_latencies: Vec<Duration>,
+    /// Round after the last one of each window, when its data is complete
+    pub window_ends: Vec<usize>,
+    pub commit_rounds: usize,
+}
+
+/// How a streaming decoder keeps up with syndrome data that arrives one round per cycle
+#[derive(Clone, Debug, PartialEq)]
+pub struct BacklogAnalysis {
+    pub cycle_time: Duration,
+    /// Time in which each window has to be decoded for the decoder to keep pace with the
+    /// experiment: its commit rounds times the cycle time
+    pub budget_per_window: Duration,
+    /// Time from the last round of each window being measured to its decoding finishing,
+    /// with windows decoded one after another as soon as their data is in
+    pub reaction_times: Vec<Duration>,
+    /// Whether windows take no longer than their budget on average. If not, the backlog of
+    /// undecoded rounds, and with it the reaction time, grows without bound.
+    pub keeps_up: bool,
+}
+
+impl SlidingWindowDecoder {
+    /// Windows over `model` with the given commit and buffer sizes in rounds, each decoded
+    /// by a decoder that `make_decoder` builds for its model. Fails if the model has more than
+    /// 64 observables, as predictions are kept one bit per observable.
+    pub fn new(
+        model: &DetectorErrorModel,
+        detectors_per_round: usize,
+        commit_rounds: usize,
+        buffer_rounds: usize,
+        make_decoder: &WindowDecoderFactory,
+    ) -> Result<Self, DetectorErrorModelError> {
+        assert!(detectors_per_round > 0 && commit_rounds > 0, "rounds and commit regions cannot be empty");
+        if model.num_observables() > 64 {
+            return Err(DetectorErrorModelError::TooManyObservables { found: model.num_observables() });
+        }
+        let num_rounds = model.num_detectors().div_ceil(detectors_per_round);
+        let round_of = |detector: usize| detector / detectors_per_round;
+
+        // Mechanisms by the round of their first detector; those flipping none are left out
+        let mut starting_in = vec![Vec::new(); num_rounds];
+        for (index, mechanism) in model.mechanisms().iter().enumerate() {
+            if let Some(&first) = mechanism.detectors.first() {
+                starting_in[round_of(first)].push(index);
+            }
+        }
+
+        let mut windows = Vec::new();
+        let mut decoders = Vec::new();
+        let mut decoder_of: HashMap<WindowSignature, usize> = HashMap::new();
+        let mut start = 0;
+        while start < num_rounds {
+            let end = (start + commit_rounds + buffer_rounds).min(num_rounds);
+            let commit_end = if end == num_rounds { end } else { start + commit_rounds };
+            let offset = start * detectors_per_round;
+            let last_detector = (end * detectors_per_round).min(model.num_detectors());
+
+            let mechanisms: Vec<usize> = starting_in[start..end].concat();
+            let mut window_model = DetectorErrorModel::new(last_detector - offset, model.num_observables());
+            for &index in &mechanisms {
+                let mechanism = &model.mechanisms()[index];
+                let detectors = mechanism
+                    .detectors
+                    .iter()
+                    .filter(|&&detector| detector < last_detector)
+                    .map(|&detector| detector - offset)
+                    .collect();
+                window_model.add_error(mechanism.probability, detectors, mechanism.observables.clone());
+            }
+
+            let signature: WindowSignature = window_model
+                .mechanisms()
+                .iter()
+                .map(|m| (m.detectors.clone(), m.observables.clone(), m.probability.to_bits()))
+                .collect();
+            let decoder = match decoder_of.get(&signature) {
+                Some(&decoder) => decoder,
+                None => {
+                    decoders.push(make_decoder(&window_model)?);
+                    decoder_of.insert(signature, decoders.len() - 1);
+                    decoders.len() - 1
+                }
+            };
+            windows.push(Window { start, commit_end, end, mechanisms, decoder });
+            if end == num_rounds {
+                break;
+            }
+            start += commit_rounds;
+        }
+
+        Ok(Self {
+            model: model.clone(),
+            detectors_per_round,
+            commit_rounds,
+            windows,
+            decoders,
+        })
+    }
+
+    pub fn num_windows(&self) -> usize {
+        self.windows.len()
+    }
+
+    /// Number of distinct window models, each with its own decoder
+    pub fn num_decoders(&self) -> usize {
+        self.decoders.len()
+    }
+
+    /// Decodes the experiment whose detectors in `flipped` fired, window by window in the
+    /// order their data would arrive, timing each window
+    pub fn decode(&mut self, flipped: &[usize]) -> WindowedDecoding {
+        let mut syndrome = vec![false; self.model.num_detectors()];
+        for &detector in flipped {
+            syndrome[detector] ^= true;
+        }
+        let mut observables = 0;
+        let mut window_latencies = Vec::with_capacity(self.windows.len());
+
+        for window in &self.windows {
+            let started = Instant::now();
+            let offset = window.start * self.detectors_per_round;
+            let last_detector = (window.end * self.detectors_per_round).min(syndrome.len());
+            let local: Vec<usize> =
+                (offset..last_detector).filter(|&detector| syndrome[detector]).map(|d| d - offset).collect();
+            for chosen in self.decoders[window.decoder].decode_detectors(&local) {
+                let mechanism = &self.model.mechanisms()[window.mechanisms[chosen]];
+                if mechanism.detectors[0] >= window.commit_end * self.detectors_per_round {
+                    continue;
+                }
+                for &detector in &mechanism.detectors {
+                    syndrome[detector] ^= true;
+                }
+                for &observable in &mechanism.observables {
+                    observables ^= 1 << observable;
+                }
+            }
+            window_latencies.push(started.elapsed());
+        }
+
+        WindowedDecoding {
+            observables,
+            window_latencies,
+            window_ends: self.windows.iter().map(|window| window.end).collect(),
+            commit_rounds: self.commit_rounds,
+        }
+    }
+}
+
+impl WindowedDecoding {
+    pub fn mean_latency(&self) -> Duration {
+        self.window_latencies.iter().sum::<Duration>() / self.window_latencies.len().max(1) as u32
+    }
+
+    pub fn max_latency(&self) -> Duration {
+        self.window_latencies.iter().copied().max().unwrap_or_default()
+    }
+
+    /// The backlog when one round is measured every `cycle_time`. A window's data is
+    /// complete at the end of its last round, and its decoding starts then or when the
+    /// window before it is done, whichever is later.
+    pub fn backlog(&self, cycle_time: Duration) -> BacklogAnalysis {
+        let mut finished = Duration::ZERO;
+        let mut reaction_times = Vec::with_capacity(self.window_latencies.len());
+        for (&latency, &end) in self.window_latencies.iter().zip(&self.window_ends) {
+            let available = cycle_time * end as u32;
+            finished = finished.max(available) + latency;
+            reaction_times.push(finished - available);
+        }
+        let budget_per_window = cycle_time * self.commit_rounds as u32;
+        BacklogAnalysis {
+            cycle_time,
+            budget_per_window,
+            reaction_times,
+            keeps_up: self.mean_latency() <= budget_per_window,
+        }
+    }
+}
+
+impl BacklogAnalysis {
+    pub fn max_reaction_time(&self) -> Duration {
+        self.reaction_times.iter().copied().max().unwrap_or_default()
+    }
+
+    /// Reaction time of the last window, the delay before the final logical result is known
+    pub fn final_reaction_time(&self) -> Duration {
+        self.reaction_times.last().copied().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuits::{CircuitNoise, NoisyCircuit};
+    use crate::correction_codes::{CorrectionCode, XzzxCode};
+    use crate::decoders::DemMatchingDecoder;
+
+    fn matching(window: &DetectorErrorModel) -> Result<Box<dyn DetectorDecoder>, DetectorErrorModelError> {
+        Ok(Box::new(DemMatchingDecoder::new(window)?))
+    }
+
+    #[test]
+    fn a_window_over_every_round_decodes_globally() {
+        let code = XzzxCode::new(3);
+        let stabilizers = code.stabilizer_generators();
+        let circuit =
+            NoisyCircuit::memory_experiment(&stabilizers, code.logical_z(), 6, &CircuitNoise::uniform(0.01));
+        let model = circuit.detector_error_model();
+        let num_rounds = model.num_detectors().div_ceil(stabilizers.len());
+
+        let mut global = DemMatchingDecoder::new(&model).expect("the model has a matching graph");
+        let mut windowed = SlidingWindowDecoder::new(&model, stabilizers.len(), 2, num_rounds, &matching)
+            .expect("the model has a matching graph");
+        assert_eq!(windowed.num_windows(), 1);
+        for _ in 0..100 {
+            let (detectors, _) = model.sample();
+            let expected = global
+                .decode_detectors(&detectors)
+                .iter()
+                .flat_map(|&mechanism| &model.mechanisms()[mechanism].observables)
+                .fold(0, |mask, &observable| mask ^ 1 << observable);
+            assert_eq!(windowed.decode(&detectors).observables, expected, "{:?}", detectors);
+        }
+    }
+
+    #[test]
+    fn backlog_follows_the_window_latencies() {
+        let ms = Duration::from_millis;
+        let decoding = WindowedDecoding {
+            observables: 0,
+            window_latencies: vec![ms(3), ms(1), ms(5)],
+            window_ends: vec![4, 6, 8],
+            commit_rounds: 2,
+        };
+        assert_eq!(decoding.mean_latency(), ms(3));
+        assert_eq!(decoding.max_latency(), ms(5));
+
+        // Each window's data arrives after the window before it is decoded
+        let slow_cycles = decoding.backlog(ms(2));
+        assert_eq!(slow_cycles.budget_per_window, ms(4));
+        assert_eq!(slow_cycles.reaction_times, vec![ms(3), ms(1), ms(5)]);
+        assert!(slow_cycles.keeps_up);
+
+        // The first window is still being decoded when the second one's data is in
+        let fast_cycles = decoding.backlog(ms(1));
+        assert_eq!(fast_cycles.budget_per_window, ms(2));
+        assert_eq!(fast_cycles.reaction_times, vec![ms(3), ms(2), ms(5)]);
+        assert_eq!((fast_cycles.max_reaction_time(), fast_cycles.final_reaction_time()), (ms(5), ms(5)));
+        assert!(!fast_cycles.keeps_up);
+    }
+
+    #[test]
+    fn rejects_more_observables_than_a_prediction_holds() {
+        let mut model = DetectorErrorModel::new(2, 0);
+        model.add_error(0.1, vec![0, 1], vec![64]);
+        let error = SlidingWindowDecoder::new(&model, 1, 1, 1, &matching).err();
+        assert_eq!(error, Some(DetectorErrorModelError::TooManyObservables { found: 65 }));
+    }
+}
@@ -0,0 +1,185 @@
+use super::code_graph::{CodeGraph, Prediction};
+use crate::circuits::{DetectorErrorModel, DetectorErrorModelError};
+use crate::decoders::{Decoder, DetectorDecoder};
+use crate::error_models::PauliChannel;
+use crate::hardware::SoftSyndrome;
+use crate::matching::DecodingGraph;
+use crate::pauli::PauliString;
+
+/// The union-find decoder on the same decoding graph as `MatchingDecoder`.
+///
+/// Clusters grow around the defects until each can be neutralised, and a peeling decoder
+/// corrects within them, in time almost linear in the number of defects. Erased qubits,
+/// whose positions are known but whose errors are not, seed clusters of their own, which
+/// makes the decoder optimal against pure erasures.
+#[derive(Clone, Debug)]
+pub struct UnionFindDecoder {
+    graph: CodeGraph,
+}
+
+impl UnionFindDecoder {
+    /// The decoder for the code with these stabilizer generators and logical operators, with
+    /// edges grown at rates following `channel`. Panics if some single-qubit error trips more
+    /// than two checks.
+    pub fn new(
+        stabilizers: &[PauliString],
+        logicals: &[(PauliString, PauliString)],
+        channel: &PauliChannel,
+    ) -> Self {
+        Self {
+            graph: CodeGraph::new(stabilizers, logicals, channel),
+        }
+    }
+
+    pub fn num_qubits(&self) -> usize {
+        self.graph.num_qubits()
+    }
+
+    /// The graph clusters grow on, with the boundary as its last node
+    pub fn graph(&self) -> &DecodingGraph {
+        self.graph.graph()
+    }
+
+    /// The correction for `syndrome` and the logical error it predicts
+    pub fn predict(&self, syndrome: &[bool]) -> Prediction {
+        self.predict_with_erasures(syndrome, &[])
+    }
+
+    /// As `predict`, knowing that the qubits in `erased` may have suffered any error
+    pub fn predict_with_erasures(&self, syndrome: &[bool], erased: &[usize]) -> Prediction {
+        let erased_edges = self.graph.qubit_edges(erased);
+        let edges = self.graph.graph().union_find(&CodeGraph::defects(syndrome), &erased_edges);
+        self.graph.prediction(&edges)
+    }
+
+    /// As `predict`, for `rounds` of syndrome readout, each outcome wrong with its flip
+    /// probability, followed by the perfectly read `final_syndrome`: clusters grow on the
+    /// space-time graph of the rounds, where the time-like edge of each outcome is weighted
+    /// by that probability
+    pub fn predict_soft(&self, rounds: &[SoftSyndrome], final_syndrome: &[bool]) -> Prediction {
+        let graph = self.graph.with_readout_errors(rounds);
+        let edges = graph.union_find(&self.graph.readout_defects(rounds, final_syndrome), &[]);
+        self.graph.readout_prediction(rounds, &edges)
+    }
+}
+
+impl Decoder for UnionFindDecoder {
+    fn decode(&mut self, syndrome: &[bool]) -> PauliString {
+        self.predict(syndrome).correction
+    }
+
+    fn decode_soft(&mut self, rounds: &[SoftSyndrome], final_syndrome: &[bool]) -> PauliString {
+        self.predict_soft(rounds, final_syndrome).correction
+    }
+}
+
+/// The union-find decoder on the decoding graph of a detector error model
+#[derive(Clone, Debug)]
+pub struct DemUnionFindDecoder {
+    graph: DecodingGraph,
+    /// Mechanism of the model each edge stands for
+    sources: Vec<usize>,
+}
+
+impl DemUnionFindDecoder {
+    /// Fails if some mechanism cannot be split into edges of the graph
+    pub fn new(model: &DetectorErrorModel) -> Result<Self, DetectorErrorModelError> {
+        let (graph, sources) = model.decoding_graph_with_sources()?;
+        Ok(Self { graph, sources })
+    }
+
+    pub fn graph(&self) -> &DecodingGraph {
+        &self.graph
+    }
+}
+
+impl DetectorDecoder for DemUnionFindDecoder {
+    fn decode_detectors(&mut self, flipped: &[usize]) -> Vec<usize> {
+        self.graph.union_find(flipped, &[]).into_iter().map(|edge| self.sources[edge]).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::correction_codes::{CorrectionCode, ToricCode, XzzxCode};
+    use crate::decoders::MatchingDecoder;
+    use crate::gf2::BinaryMatrix;
+    use crate::pauli::Pauli;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn syndrome(stabilizers: &[PauliString], error: &PauliString) -> Vec<bool> {
+        stabilizers.iter().map(|s| !error.commutes_with(s)).collect()
+    }
+
+    /// Whether a correction leaves the error as a stabilizer rather than a logical
+    fn is_trivial(code: &dyn CorrectionCode, residual: &PauliString) -> bool {
+        let logicals = code.logical_operators();
+        code.stabilizer_generators().iter().all(|s| residual.commutes_with(s))
+            && logicals.iter().all(|(x, z)| residual.commutes_with(x) && residual.commutes_with(z))
+    }
+
+    /// Rank of the stabilizers restricted to `qubits`, as symplectic vectors
+    fn restricted_rank(stabilizers: &[PauliString], qubits: &[usize]) -> usize {
+        let rows = stabilizers
+            .iter()
+            .map(|s| qubits.iter().flat_map(|&q| [s.x_bits()[q], s.z_bits()[q]]).collect())
+            .collect();
+        BinaryMatrix::from_rows(2 * qubits.len(), rows).rank()
+    }
+
+    #[test]
+    fn corrects_every_correctable_erasure() {
+        let code = XzzxCode::new(3);
+        let (stabilizers, logicals) = (code.stabilizer_generators(), code.logical_operators());
+        let decoder = UnionFindDecoder::new(&stabilizers, &logicals, &PauliChannel::new(0.05, 0.05, 0.05));
+        let n = code.num_qubits();
+        let mut rng = StdRng::seed_from_u64(44);
+
+        let mut correctable = 0;
+        for pattern in 1..1usize << n {
+            let erased: Vec<usize> = (0..n).filter(|&q| pattern >> q & 1 == 1).collect();
+            let kept: Vec<usize> = (0..n).filter(|&q| pattern >> q & 1 == 0).collect();
+            // Every Pauli on the erasure that commutes with the stabilizers is one of them
+            let stabilizers_inside = stabilizers.len() - restricted_rank(&stabilizers, &kept);
+            let commuting_inside = 2 * erased.len() - restricted_rank(&stabilizers, &erased);
+            if stabilizers_inside != commuting_inside {
+                continue;
+            }
+            correctable += 1;
+            for _ in 0..20 {
+                let mut error = PauliString::identity(n);
+                for &q in &erased {
+                    error.set(q, [Pauli::I, Pauli::X, Pauli::Y, Pauli::Z][rng.gen_range(0..4)]);
+                }
+                let prediction = decoder.predict_with_erasures(&syndrome(&stabilizers, &error), &erased);
+                assert!(prediction.correction.support().iter().all(|q| erased.contains(q)));
+                let residual = prediction.correction.product(&error);
+                assert!(is_trivial(&code, &residual), "{} erased on {:?}", error, erased);
+            }
+        }
+        assert!(correctable > 100, "only {} correctable erasures", correctable);
+    }
+
+    #[test]
+    fn agrees_with_matching_on_single_qubit_errors() {
+        let toric = ToricCode::new(3);
+        let xzzx = XzzxCode::new(5);
+        let channel = PauliChannel::new(0.02, 0.01, 0.05);
+        for code in [&toric as &dyn CorrectionCode, &xzzx] {
+            let (stabilizers, logicals) = (code.stabilizer_generators(), code.logical_operators());
+            let union_find = UnionFindDecoder::new(&stabilizers, &logicals, &channel);
+            let matching = MatchingDecoder::new(&stabilizers, &logicals, &channel);
+            for qubit in 0..code.num_qubits() {
+                for pauli in [Pauli::X, Pauli::Y, Pauli::Z] {
+                    let error = PauliString::from_support(code.num_qubits(), &[qubit], pauli);
+                    let syndrome = syndrome(&stabilizers, &error);
+                    let (found, expected) = (union_find.predict(&syndrome), matching.predict(&syndrome));
+                    assert_eq!(found.logical_flips, expected.logical_flips, "{}", error);
+                    assert!(is_trivial(code, &found.correction.product(&error)), "{}", error);
+                }
+            }
+        }
+    }
+}
@@ -1,8 +1,14 @@
 use crate::qubit::Qubit;
 use crate::gates::{PauliX, PauliZ, Gate};
+use crate::pauli::Pauli;
 
 pub trait ErrorModel {
     fn apply_error(&self, qubit: &mut Qubit);
+
+    /// The model as independent X, Y and Z errors, for models that are Pauli channels
+    fn pauli_channel(&self) -> Option<PauliChannel> {
+        None
+    }
 }
 
 
@@ -33,6 +39,10 @@ impl ErrorModel for BitFlipNoise {
             PauliX.apply(qubit);
         }
     }
+
+    fn pauli_channel(&self) -> Option<PauliChannel> {
+        Some(PauliChannel::new(self.probability, 0.0, 0.0))
+    }
 }
 
 impl ErrorModel for PhaseFlipNoise {
@@ -41,5 +51,106 @@ impl ErrorModel for PhaseFlipNoise {
             PauliZ.apply(qubit);
         }
     }
+
+    fn pauli_channel(&self) -> Option<PauliChannel> {
+        Some(PauliChannel::new(0.0, 0.0, self.probability))
+    }
+}
+
+
+/// Pauli noise biased towards Z errors.
+///
+/// With total error probability p and bias η = p_Z / (p_X + p_Y), a qubit suffers Z with
+/// probability p·η/(η+1) and X and Y with probability p/(2(η+1)) each. η = 0.5 is
+/// depolarizing noise and `f64::INFINITY` leaves pure dephasing.
+pub struct BiasedNoise {
+    probability: f64,
+    bias: f64,
+}
+
+impl BiasedNoise {
+    pub fn new(probability: f64, bias: f64) -> Self {
+        assert!(bias >= 0.0, "the bias must be non-negative");
+        Self { probability, bias }
+    }
+
+    pub fn probability(&self) -> f64 {
+        self.probability
+    }
+
+    pub fn bias(&self) -> f64 {
+        self.bias
+    }
+
+    /// Probabilities of an X, Y and Z error
+    pub fn pauli_probabilities(&self) -> (f64, f64, f64) {
+        if self.bias.is_infinite() {
+            return (0.0, 0.0, self.probability);
+        }
+        let p_z = self.probability * self.bias / (self.bias + 1.0);
+        let p_x = self.probability / (2.0 * (self.bias + 1.0));
+        (p_x, p_x, p_z)
+    }
+}
+
+impl ErrorModel for BiasedNoise {
+    fn apply_error(&self, qubit: &mut Qubit) {
+        apply_pauli_error(qubit, self.pauli_probabilities());
+    }
+
+    fn pauli_channel(&self) -> Option<PauliChannel> {
+        let (p_x, p_y, p_z) = self.pauli_probabilities();
+        Some(PauliChannel::new(p_x, p_y, p_z))
+    }
 }
 
+/// Independent Pauli noise with arbitrary probabilities of X, Y and Z, such as the logical
+/// noise left on a qubit stored in an oscillator mode
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PauliChannel {
+    p_x: f64,
+    p_y: f64,
+    p_z: f64,
+}
+
+impl PauliChannel {
+    pub fn new(p_x: f64, p_y: f64, p_z: f64) -> Self {
+        assert!(p_x >= 0.0 && p_y >= 0.0 && p_z >= 0.0, "probabilities must be non-negative");
+        assert!(p_x + p_y + p_z <= 1.0 + 1e-12, "the error probabilities add up to more than 1");
+        Self { p_x, p_y, p_z }
+    }
+
+    /// Probabilities of an X, Y and Z error
+    pub fn pauli_probabilities(&self) -> (f64, f64, f64) {
+        (self.p_x, self.p_y, self.p_z)
+    }
+
+    /// Probability of any error at all
+    pub fn probability(&self) -> f64 {
+        self.p_x + self.p_y + self.p_z
+    }
+}
+
+impl ErrorModel for PauliChannel {
+    fn apply_error(&self, qubit: &mut Qubit) {
+        apply_pauli_error(qubit, self.pauli_probabilities());
+    }
+
+    fn pauli_channel(&self) -> Option<PauliChannel> {
+        Some(*self)
+    }
+}
+
+fn apply_pauli_error(qubit: &mut Qubit, (p_x, p_y, p_z): (f64, f64, f64)) {
+    let random = rand::random::<f64>();
+    let pauli = if random < p_z {
+        Pauli::Z
+    } else if random < p_z + p_x {
+        Pauli::X
+    } else if random < p_z + p_x + p_y {
+        Pauli::Y
+    } else {
+        return;
+    };
+    pauli.apply(qubit);
+}
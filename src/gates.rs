@@ -1,4 +1,5 @@
 use crate::qubit::Qubit;
+use crate::pauli::{Pauli, PauliString};
 use num_complex::Complex64;
 
 pub trait Gate {
@@ -46,4 +47,84 @@ impl Gate for Hadamard {
         qubit.set_state(0, normalization * (old_state[0] + old_state[1]));
         qubit.set_state(1, normalization * (old_state[0] - old_state[1]));
     }
-}
\ No newline at end of file
+}
+
+/// A gate of a multi-qubit Clifford circuit, acting on qubit indices.
+///
+/// Circuits are not simulated on `Qubit` states; instead Pauli operators are pushed through
+/// them with `conjugate`, which is all that is needed to check encoders and logical gates.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CliffordGate {
+    H(usize),
+    S(usize),
+    SDag(usize),
+    Cnot { control: usize, target: usize },
+    Cz(usize, usize),
+}
+
+impl CliffordGate {
+    /// Replaces `operator` by G·operator·G†, ignoring the sign
+    pub fn conjugate(&self, operator: &mut PauliString) {
+        match *self {
+            CliffordGate::H(q) => {
+                let p = operator.get(q);
+                operator.set(q, Pauli::from_bits(p.z_bit(), p.x_bit()));
+            }
+            CliffordGate::S(q) | CliffordGate::SDag(q) => {
+                let p = operator.get(q);
+                operator.set(q, Pauli::from_bits(p.x_bit(), p.z_bit() ^ p.x_bit()));
+            }
+            CliffordGate::Cnot { control, target } => {
+                let c = operator.get(control);
+                let t = operator.get(target);
+                operator.set(control, Pauli::from_bits(c.x_bit(), c.z_bit() ^ t.z_bit()));
+                operator.set(target, Pauli::from_bits(t.x_bit() ^ c.x_bit(), t.z_bit()));
+            }
+            CliffordGate::Cz(a, b) => {
+                let pa = operator.get(a);
+                let pb = operator.get(b);
+                operator.set(a, Pauli::from_bits(pa.x_bit(), pa.z_bit() ^ pb.x_bit()));
+                operator.set(b, Pauli::from_bits(pb.x_bit(), pb.z_bit() ^ pa.x_bit()));
+            }
+        }
+    }
+}
+
+/// Pushes `operator` through a whole circuit, gate by gate
+pub fn conjugate_through(circuit: &[CliffordGate], operator: &PauliString) -> PauliString {
+    let mut result = operator.clone();
+    for gate in circuit {
+        gate.conjugate(&mut result);
+    }
+    result
+}
+
+/// The diagonal gate diag(1, ω^k) with ω = e^{iπ/4} on one qubit of a circuit, k counted in
+/// eighths of a turn: k = 1 is T, 2 is S, 4 is Z and 7 is T†.
+///
+/// T is not a Clifford gate, so it cannot be pushed through Pauli operators like
+/// `CliffordGate`. Diagonal circuits are instead followed by the phase they put on each
+/// computational basis state.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DiagonalGate {
+    pub qubit: usize,
+    pub eighths: u8,
+}
+
+impl DiagonalGate {
+    pub fn t(qubit: usize) -> Self {
+        Self { qubit, eighths: 1 }
+    }
+
+    pub fn t_dagger(qubit: usize) -> Self {
+        Self { qubit, eighths: 7 }
+    }
+
+    /// Phase exponent, in eighths of a turn, that a whole circuit puts on basis state |bits⟩
+    pub fn phase_on(circuit: &[DiagonalGate], bits: &[bool]) -> u8 {
+        circuit
+            .iter()
+            .filter(|gate| bits[gate.qubit])
+            .fold(0, |phase, gate| (phase + gate.eighths) % 8)
+    }
+}
@@ -0,0 +1,395 @@
+use std::fmt;
+
+/// A dense matrix over GF(2), stored row by row.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BinaryMatrix {
+    num_cols: usize,
+    rows: Vec<Vec<bool>>,
+}
+
+impl BinaryMatrix {
+    pub fn zeros(num_rows: usize, num_cols: usize) -> Self {
+        Self {
+            num_cols,
+            rows: vec![vec![false; num_cols]; num_rows],
+        }
+    }
+
+    pub fn identity(size: usize) -> Self {
+        let mut matrix = Self::zeros(size, size);
+        for i in 0..size {
+            matrix.rows[i][i] = true;
+        }
+        matrix
+    }
+
+    /// Builds a matrix with `num_cols` columns from its rows
+    pub fn from_rows(num_cols: usize, rows: Vec<Vec<bool>>) -> Self {
+        assert!(
+            rows.iter().all(|row| row.len() == num_cols),
+            "every row must have {} columns",
+            num_cols
+        );
+        Self { num_cols, rows }
+    }
+
+    /// Builds a matrix from rows of 0/1 entries, e.g. `&[&[1, 1, 0], &[0, 1, 1]]`
+    pub fn from_bits(rows: &[&[u8]]) -> Self {
+        let num_cols = rows.first().map_or(0, |row| row.len());
+        Self::from_rows(
+            num_cols,
+            rows.iter()
+                .map(|row| row.iter().map(|&bit| bit % 2 == 1).collect())
+                .collect(),
+        )
+    }
+
+    pub fn num_rows(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn num_cols(&self) -> usize {
+        self.num_cols
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> bool {
+        self.rows[row][col]
+    }
+
+    pub fn set(&mut self, row: usize, col: usize, value: bool) {
+        self.rows[row][col] = value;
+    }
+
+    pub fn row(&self, row: usize) -> &[bool] {
+        &self.rows[row]
+    }
+
+    pub fn rows(&self) -> &[Vec<bool>] {
+        &self.rows
+    }
+
+    pub fn column(&self, col: usize) -> Vec<bool> {
+        self.rows.iter().map(|row| row[col]).collect()
+    }
+
+    pub fn push_row(&mut self, row: Vec<bool>) {
+        assert_eq!(row.len(), self.num_cols, "row has the wrong number of columns");
+        self.rows.push(row);
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.rows.iter().all(|row| row.iter().all(|&bit| !bit))
+    }
+
+    pub fn transpose(&self) -> BinaryMatrix {
+        let mut result = Self::zeros(self.num_cols, self.num_rows());
+        for (i, row) in self.rows.iter().enumerate() {
+            for (j, &bit) in row.iter().enumerate() {
+                result.rows[j][i] = bit;
+            }
+        }
+        result
+    }
+
+    /// Matrix product over GF(2)
+    pub fn multiply(&self, other: &BinaryMatrix) -> BinaryMatrix {
+        assert_eq!(self.num_cols, other.num_rows(), "inner dimensions must agree");
+        let mut result = Self::zeros(self.num_rows(), other.num_cols);
+        for (i, row) in self.rows.iter().enumerate() {
+            for (k, &bit) in row.iter().enumerate() {
+                if bit {
+                    add_into(&mut result.rows[i], &other.rows[k]);
+                }
+            }
+        }
+        result
+    }
+
+    /// Matrix-vector product over GF(2)
+    pub fn mul_vec(&self, vector: &[bool]) -> Vec<bool> {
+        assert_eq!(self.num_cols, vector.len(), "vector has the wrong length");
+        self.rows.iter().map(|row| dot(row, vector)).collect()
+    }
+
+    /// Stacks `other` below this matrix
+    pub fn stack(&self, other: &BinaryMatrix) -> BinaryMatrix {
+        assert_eq!(self.num_cols, other.num_cols, "matrices must have the same width");
+        let mut rows = self.rows.clone();
+        rows.extend(other.rows.iter().cloned());
+        Self::from_rows(self.num_cols, rows)
+    }
+
+    /// Places `other` to the right of this matrix
+    pub fn augment(&self, other: &BinaryMatrix) -> BinaryMatrix {
+        assert_eq!(self.num_rows(), other.num_rows(), "matrices must have the same height");
+        let rows = self
+            .rows
+            .iter()
+            .zip(&other.rows)
+            .map(|(left, right)| left.iter().chain(right).copied().collect())
+            .collect();
+        Self::from_rows(self.num_cols + other.num_cols, rows)
+    }
+
+    /// Kronecker product over GF(2)
+    pub fn kron(&self, other: &BinaryMatrix) -> BinaryMatrix {
+        let mut result = Self::zeros(self.num_rows() * other.num_rows(), self.num_cols * other.num_cols);
+        for (i, row) in self.rows.iter().enumerate() {
+            for (j, &bit) in row.iter().enumerate() {
+                if !bit {
+                    continue;
+                }
+                for (k, other_row) in other.rows.iter().enumerate() {
+                    for (l, &other_bit) in other_row.iter().enumerate() {
+                        result.rows[i * other.num_rows() + k][j * other.num_cols + l] = other_bit;
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    /// Brings the matrix to reduced row echelon form in place and returns the pivot columns
+    pub fn row_reduce(&mut self) -> Vec<usize> {
+        let mut pivots = Vec::new();
+        let mut pivot_row = 0;
+        for col in 0..self.num_cols {
+            if pivot_row == self.num_rows() {
+                break;
+            }
+            let Some(found) = (pivot_row..self.num_rows()).find(|&r| self.rows[r][col]) else {
+                continue;
+            };
+            self.rows.swap(pivot_row, found);
+            let pivot = self.rows[pivot_row].clone();
+            for (r, row) in self.rows.iter_mut().enumerate() {
+                if r != pivot_row && row[col] {
+                    add_into(row, &pivot);
+                }
+            }
+            pivots.push(col);
+            pivot_row += 1;
+        }
+        pivots
+    }
+
+    pub fn rank(&self) -> usize {
+        self.clone().row_reduce().len()
+    }
+
+    /// A basis of the null space {v : M·v = 0}, one vector per row of the result
+    pub fn kernel(&self) -> BinaryMatrix {
+        let mut reduced = self.clone();
+        let pivots = reduced.row_reduce();
+        let mut basis = Vec::new();
+        for free in (0..self.num_cols).filter(|col| !pivots.contains(col)) {
+            let mut vector = vec![false; self.num_cols];
+            vector[free] = true;
+            for (r, &pivot) in pivots.iter().enumerate() {
+                vector[pivot] = reduced.rows[r][free];
+            }
+            basis.push(vector);
+        }
+        Self::from_rows(self.num_cols, basis)
+    }
+
+    /// Whether `vector` is a GF(2) combination of the rows
+    pub fn row_space_contains(&self, vector: &[bool]) -> bool {
+        let mut extended = self.clone();
+        extended.push_row(vector.to_vec());
+        extended.rank() == self.rank()
+    }
+
+    /// Solves M·x = rhs, returning one solution if the system is consistent
+    pub fn solve(&self, rhs: &[bool]) -> Option<Vec<bool>> {
+        assert_eq!(rhs.len(), self.num_rows(), "right-hand side has the wrong length");
+        let mut augmented = self.clone();
+        augmented.num_cols += 1;
+        for (row, &bit) in augmented.rows.iter_mut().zip(rhs) {
+            row.push(bit);
+        }
+        let pivots = augmented.row_reduce();
+        if pivots.last() == Some(&self.num_cols) {
+            return None;
+        }
+        let mut solution = vec![false; self.num_cols];
+        for (r, &pivot) in pivots.iter().enumerate() {
+            solution[pivot] = augmented.rows[r][self.num_cols];
+        }
+        Some(solution)
+    }
+
+    /// Inverse of a square matrix, if it exists
+    pub fn inverse(&self) -> Option<BinaryMatrix> {
+        let size = self.num_rows();
+        assert_eq!(size, self.num_cols, "only square matrices can be inverted");
+        if size == 0 {
+            return Some(Self::zeros(0, 0));
+        }
+        let mut augmented = Self::zeros(size, 2 * size);
+        for i in 0..size {
+            augmented.rows[i][..size].copy_from_slice(&self.rows[i]);
+            augmented.rows[i][size + i] = true;
+        }
+        let pivots = augmented.row_reduce();
+        if pivots.len() < size || pivots[size - 1] >= size {
+            return None;
+        }
+        Some(Self::from_rows(
+            size,
+            augmented.rows.iter().map(|row| row[size..].to_vec()).collect(),
+        ))
+    }
+}
+
+impl fmt::Display for BinaryMatrix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in &self.rows {
+            let line: String = row.iter().map(|&bit| if bit { '1' } else { '0' }).collect();
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+/// A set of linearly independent vectors kept in echelon form, so that membership of the
+/// span can be tested and extended one vector at a time.
+#[derive(Clone, Debug, Default)]
+pub struct EchelonBasis {
+    rows: Vec<(usize, Vec<bool>)>,
+}
+
+impl EchelonBasis {
+    pub fn new() -> Self {
+        Self { rows: Vec::new() }
+    }
+
+    /// Number of independent vectors inserted so far
+    pub fn rank(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Reduces `vector` against the basis, leaving a zero vector exactly when it is in the span
+    fn reduce(&self, vector: &mut [bool]) {
+        for (pivot, row) in &self.rows {
+            if vector[*pivot] {
+                add_into(vector, row);
+            }
+        }
+    }
+
+    pub fn contains(&self, vector: &[bool]) -> bool {
+        let mut reduced = vector.to_vec();
+        self.reduce(&mut reduced);
+        reduced.iter().all(|&bit| !bit)
+    }
+
+    /// Adds `vector` to the basis and returns whether it was independent of it
+    pub fn insert(&mut self, vector: &[bool]) -> bool {
+        let mut reduced = vector.to_vec();
+        self.reduce(&mut reduced);
+        let Some(pivot) = reduced.iter().position(|&bit| bit) else {
+            return false;
+        };
+        for (_, row) in self.rows.iter_mut() {
+            if row[pivot] {
+                add_into(row, &reduced);
+            }
+        }
+        self.rows.push((pivot, reduced));
+        true
+    }
+}
+
+/// Inner product of two bit vectors over GF(2)
+pub fn dot(a: &[bool], b: &[bool]) -> bool {
+    a.iter().zip(b).fold(false, |acc, (&x, &y)| acc ^ (x && y))
+}
+
+/// Adds `source` into `target` over GF(2)
+pub fn add_into(target: &mut [bool], source: &[bool]) {
+    for (t, &s) in target.iter_mut().zip(source) {
+        *t ^= s;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hamming() -> BinaryMatrix {
+        BinaryMatrix::from_bits(&[
+            &[0, 0, 0, 1, 1, 1, 1],
+            &[0, 1, 1, 0, 0, 1, 1],
+            &[1, 0, 1, 0, 1, 0, 1],
+        ])
+    }
+
+    #[test]
+    fn rank_counts_independent_rows() {
+        assert_eq!(hamming().rank(), 3);
+        assert_eq!(BinaryMatrix::identity(5).rank(), 5);
+        assert_eq!(BinaryMatrix::zeros(3, 4).rank(), 0);
+
+        // The third row is the sum of the first two
+        let dependent = BinaryMatrix::from_bits(&[&[1, 1, 0, 1], &[0, 1, 1, 1], &[1, 0, 1, 0]]);
+        assert_eq!(dependent.rank(), 2);
+        assert_eq!(dependent.transpose().rank(), 2);
+    }
+
+    #[test]
+    fn row_reduce_gives_reduced_echelon_form_with_the_same_row_space() {
+        let original = BinaryMatrix::from_bits(&[&[0, 1, 1, 0, 1], &[1, 1, 0, 1, 0], &[1, 0, 1, 1, 1]]);
+        let mut reduced = original.clone();
+        let pivots = reduced.row_reduce();
+        assert_eq!(pivots, vec![0, 1]);
+
+        // Each pivot column is a unit vector with its 1 in the pivot's row
+        for (row, &pivot) in pivots.iter().enumerate() {
+            assert_eq!(reduced.column(pivot), (0..3).map(|r| r == row).collect::<Vec<_>>());
+        }
+        assert!(reduced.row(2).iter().all(|&bit| !bit));
+        for row in original.rows() {
+            assert!(reduced.row_space_contains(row));
+        }
+    }
+
+    #[test]
+    fn kernel_spans_the_null_space() {
+        for matrix in [
+            hamming(),
+            BinaryMatrix::from_bits(&[&[1, 1, 0, 0, 0], &[0, 1, 1, 0, 0], &[0, 0, 1, 1, 0], &[0, 0, 0, 1, 1]]),
+            BinaryMatrix::from_bits(&[&[1, 0, 1, 1], &[1, 0, 1, 1]]),
+        ] {
+            let kernel = matrix.kernel();
+            assert_eq!(kernel.num_rows(), matrix.num_cols() - matrix.rank());
+            assert_eq!(kernel.rank(), kernel.num_rows());
+            assert!(matrix.multiply(&kernel.transpose()).is_zero());
+        }
+    }
+
+    #[test]
+    fn solve_and_inverse_agree_with_multiplication() {
+        let matrix = hamming();
+        let rhs = vec![true, false, true];
+        let solution = matrix.solve(&rhs).expect("a full-rank system is consistent");
+        assert_eq!(matrix.mul_vec(&solution), rhs);
+        assert_eq!(BinaryMatrix::from_bits(&[&[1, 1], &[1, 1]]).solve(&[true, false]), None);
+
+        let invertible = BinaryMatrix::from_bits(&[&[1, 1, 0], &[0, 1, 1], &[0, 0, 1]]);
+        let inverse = invertible.inverse().expect("an upper triangular matrix is invertible");
+        assert_eq!(invertible.multiply(&inverse), BinaryMatrix::identity(3));
+        assert_eq!(BinaryMatrix::from_bits(&[&[1, 1], &[1, 1]]).inverse(), None);
+    }
+
+    #[test]
+    fn echelon_basis_tracks_the_span() {
+        let mut basis = EchelonBasis::new();
+        assert!(basis.insert(&[true, true, false]));
+        assert!(basis.insert(&[false, true, true]));
+        assert!(!basis.insert(&[true, false, true]));
+        assert_eq!(basis.rank(), 2);
+        assert!(basis.contains(&[true, false, true]));
+        assert!(!basis.contains(&[true, false, false]));
+    }
+}
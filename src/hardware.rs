@@ -0,0 +1,189 @@
+use crate::sampling::standard_normal;
+use crate::gates::CliffordGate;
+
+/// Which pairs of physical qubits on a device can interact directly.
+///
+/// Nodes are device qubits and edges are couplers. Two-qubit gates are only allowed between
+/// coupled qubits, so a circuit fits the device when every CNOT and CZ in it lies on an edge.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CouplingGraph {
+    adjacency: Vec<Vec<usize>>,
+}
+
+impl CouplingGraph {
+    pub fn new(num_qubits: usize) -> Self {
+        Self {
+            adjacency: vec![Vec::new(); num_qubits],
+        }
+    }
+
+    /// A graph with the given couplers, on enough qubits to hold them all
+    pub fn from_edges(edges: &[(usize, usize)]) -> Self {
+        let num_qubits = edges.iter().map(|&(a, b)| a.max(b) + 1).max().unwrap_or(0);
+        let mut graph = Self::new(num_qubits);
+        for &(a, b) in edges {
+            graph.add_edge(a, b);
+        }
+        graph
+    }
+
+    /// Couples two qubits; adding an existing coupler again has no effect
+    pub fn add_edge(&mut self, a: usize, b: usize) {
+        assert!(a != b, "a qubit cannot be coupled to itself");
+        if !self.are_coupled(a, b) {
+            self.adjacency[a].push(b);
+            self.adjacency[b].push(a);
+        }
+    }
+
+    pub fn num_qubits(&self) -> usize {
+        self.adjacency.len()
+    }
+
+    pub fn num_edges(&self) -> usize {
+        self.adjacency.iter().map(Vec::len).sum::<usize>() / 2
+    }
+
+    pub fn are_coupled(&self, a: usize, b: usize) -> bool {
+        self.adjacency[a].contains(&b)
+    }
+
+    pub fn neighbours(&self, qubit: usize) -> &[usize] {
+        &self.adjacency[qubit]
+    }
+
+    pub fn degree(&self, qubit: usize) -> usize {
+        self.adjacency[qubit].len()
+    }
+
+    /// Largest number of couplers on one qubit; heavy-hex devices have at most three
+    pub fn max_degree(&self) -> usize {
+        self.adjacency.iter().map(Vec::len).max().unwrap_or(0)
+    }
+
+    /// Every coupler once, as (lower, higher) qubit
+    pub fn edges(&self) -> Vec<(usize, usize)> {
+        let mut edges = Vec::with_capacity(self.num_edges());
+        for (a, neighbours) in self.adjacency.iter().enumerate() {
+            edges.extend(neighbours.iter().filter(|&&b| a < b).map(|&b| (a, b)));
+        }
+        edges
+    }
+
+    /// Qubits of the first two-qubit gate that acts across a missing coupler, if any
+    pub fn uncoupled_gate(&self, circuit: &[CliffordGate]) -> Option<(usize, usize)> {
+        circuit.iter().find_map(|gate| {
+            let (a, b) = match *gate {
+                CliffordGate::Cnot { control, target } => (control, target),
+                CliffordGate::Cz(a, b) => (a, b),
+                _ => return None,
+            };
+            let coupled = a < self.num_qubits() && b < self.num_qubits() && self.are_coupled(a, b);
+            (!coupled).then_some((a, b))
+        })
+    }
+}
+
+/// A point in the IQ plane, the demodulated in-phase and quadrature parts of a readout signal
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct IqPoint {
+    pub i: f64,
+    pub q: f64,
+}
+
+/// Dispersive readout as two Gaussian blobs in the IQ plane.
+///
+/// Measuring a qubit gives a point drawn around the centre of its outcome, with independent
+/// Gaussian noise of standard deviation `sigma` on each quadrature. The outcome is decided by
+/// the nearer centre, and how far the point lies from the midline between them says how much
+/// to trust that decision: the posterior probability that it is wrong, with both outcomes
+/// equally likely beforehand, is 1 / (1 + e^|L|) for the log-likelihood ratio
+/// L = (|x - c₀|² - |x - c₁|²) / 2σ².
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GaussianReadout {
+    ground: IqPoint,
+    excited: IqPoint,
+    sigma: f64,
+}
+
+/// Measurement outcomes read out through a noisy channel, each with the probability that it
+/// is wrong
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SoftSyndrome {
+    pub outcomes: Vec<bool>,
+    pub flip_probabilities: Vec<f64>,
+}
+
+impl GaussianReadout {
+    /// Readout with outcome 0 centred on `ground` and outcome 1 on `excited`
+    pub fn new(ground: IqPoint, excited: IqPoint, sigma: f64) -> Self {
+        assert!(sigma > 0.0, "the readout noise must be positive");
+        Self { ground, excited, sigma }
+    }
+
+    /// Centres on the I axis at ±`separation` / 2, with the given signal-to-noise ratio
+    /// `separation` / `sigma`
+    pub fn with_snr(snr: f64) -> Self {
+        Self::new(IqPoint { i: -snr / 2.0, q: 0.0 }, IqPoint { i: snr / 2.0, q: 0.0 }, 1.0)
+    }
+
+    pub fn sigma(&self) -> f64 {
+        self.sigma
+    }
+
+    /// Distance between the centres over the noise
+    pub fn snr(&self) -> f64 {
+        (self.excited.i - self.ground.i).hypot(self.excited.q - self.ground.q) / self.sigma
+    }
+
+    /// Probability that deciding by the nearer centre gets an outcome wrong, Φ(-snr / 2)
+    pub fn assignment_error(&self) -> f64 {
+        0.5 * erfc(self.snr() / (2.0 * std::f64::consts::SQRT_2))
+    }
+
+    /// A sample of the signal for `outcome`
+    pub fn signal(&self, outcome: bool) -> IqPoint {
+        let centre = if outcome { self.excited } else { self.ground };
+        IqPoint {
+            i: centre.i + self.sigma * standard_normal(),
+            q: centre.q + self.sigma * standard_normal(),
+        }
+    }
+
+    /// ln(P(point | 1) / P(point | 0))
+    pub fn log_likelihood_ratio(&self, point: IqPoint) -> f64 {
+        let squared_distance = |centre: IqPoint| (point.i - centre.i).powi(2) + (point.q - centre.q).powi(2);
+        (squared_distance(self.ground) - squared_distance(self.excited)) / (2.0 * self.sigma * self.sigma)
+    }
+
+    /// The likelier outcome for `point` and the probability that it is wrong
+    pub fn classify(&self, point: IqPoint) -> (bool, f64) {
+        let ratio = self.log_likelihood_ratio(point);
+        (ratio > 0.0, 1.0 / (1.0 + ratio.abs().exp()))
+    }
+
+    /// Reads out each of the true `outcomes` through the channel
+    pub fn read(&self, outcomes: &[bool]) -> SoftSyndrome {
+        let (outcomes, flip_probabilities) =
+            outcomes.iter().map(|&outcome| self.classify(self.signal(outcome))).unzip();
+        SoftSyndrome { outcomes, flip_probabilities }
+    }
+}
+
+/// The complementary error function, to within 1.2e-7 (Numerical Recipes' erfcc)
+fn erfc(x: f64) -> f64 {
+    let t = 1.0 / (1.0 + 0.5 * x.abs());
+    let polynomial = [
+        -1.26551223, 1.00002368, 0.37409196, 0.09678418, -0.18628806, 0.27886807, -1.13520398, 1.48851587,
+        -0.82215223, 0.17087277,
+    ]
+    .iter()
+    .rev()
+    .fold(0.0, |sum, &coefficient| sum * t + coefficient);
+    let value = t * (-x * x + polynomial).exp();
+    if x >= 0.0 {
+        value
+    } else {
+        2.0 - value
+    }
+}
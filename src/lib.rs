@@ -0,0 +1,16 @@
+pub mod qubit;
+pub mod gates;
+pub mod gf2;
+pub mod pauli;
+pub mod matching;
+pub mod error_models;
+mod sampling;
+pub mod circuits;
+pub mod hardware;
+pub mod bosonic;
+pub mod correction_codes;
+pub mod decoders;
+pub mod simulation;
+// The plotting code predates the library target and borrows colours plotters also takes by value
+#[allow(clippy::needless_borrows_for_generic_args)]
+pub mod visualization;
@@ -1,16 +1,12 @@
 // Imports for all code types
-use crate::qubit::Qubit;
-use crate::error_models::{BitFlipNoise, PhaseFlipNoise};
-use crate::correction_codes::{BitFlipCode, PhaseFlipCode};
-use crate::simulation::Simulation;
-use crate::visualization::{plot_success_rates, plot_error_vs_success};
-
-mod qubit;
-mod gates;
-mod error_models;
-mod correction_codes;
-mod simulation;
-mod visualization;
+use quantum_error_correction::error_models::{BitFlipNoise, PauliChannel, PhaseFlipNoise};
+use quantum_error_correction::correction_codes::{BitFlipCode, LogicalErrorPolynomial, PhaseFlipCode};
+use quantum_error_correction::simulation::Simulation;
+use quantum_error_correction::visualization::{
+    plot_error_vs_success, plot_error_vs_success_exact, plot_success_rates,
+};
+
+// Demonstrations of the other codes and decoders live in examples/
 
 fn main() {
     println!("Quantum Error Correction Simulator");
@@ -100,6 +96,31 @@ fn main() {
         Err(e) => println!("Failed to create chart: {}", e),
     }
 
+    // Exact success rates of the same decoders, to overlay on the sampled points
+    let bit_flip_polynomial = LogicalErrorPolynomial::new(&mut BitFlipCode::new());
+    let phase_flip_polynomial = LogicalErrorPolynomial::new(&mut PhaseFlipCode::new());
+    let curve_points: Vec<f64> = (0..=66).map(|i| i as f64 * 0.005).collect();
+    let bit_flip_exact: Vec<(f64, f64)> = curve_points
+        .iter()
+        .map(|&p| (p, 1.0 - bit_flip_polynomial.failure_probability(&PauliChannel::new(p, 0.0, 0.0))))
+        .collect();
+    let phase_flip_exact: Vec<(f64, f64)> = curve_points
+        .iter()
+        .map(|&p| (p, 1.0 - phase_flip_polynomial.failure_probability(&PauliChannel::new(0.0, 0.0, p))))
+        .collect();
+
+    match plot_error_vs_success_exact(
+        &error_rates,
+        &bit_flip_success_rates,
+        &phase_flip_success_rates,
+        &bit_flip_exact,
+        &phase_flip_exact,
+        "error_vs_success_exact.svg"
+    ) {
+        Ok(_) => println!("Sampled vs exact chart created successfully"),
+        Err(e) => println!("Failed to create chart: {}", e),
+    }
+
     println!("\nSimulations and visualizations complete!");
     println!("Results have been saved as 'success_rates.png' and 'error_vs_success.png'");
 }
\ No newline at end of file
@@ -0,0 +1,716 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+
+mod blossom;
+mod union_find;
+
+/// Largest defect count for which the pairing is found by dynamic programming; larger ones
+/// go to the blossom algorithm
+const EXACT_PAIRING_LIMIT: usize = 12;
+
+/// Number of nearest other defects each defect may be paired with when matching on a graph
+const MATCHING_NEIGHBOURS: usize = 30;
+
+/// Resolution of the integer weights handed to the blossom algorithm, relative to the
+/// heaviest weight
+const WEIGHT_RESOLUTION: f64 = 1e9;
+
+/// A weighted graph whose nodes are checks and whose edges are the faults between them.
+///
+/// Nodes marked as boundary absorb any number of defects, which is how the open edges of
+/// planar codes are handled. Matching a set of defects joins them up along shortest paths
+/// and reports the edges that end up flipped.
+///
+/// Edges added with `add_fault` also carry the probability of their fault and the logical
+/// observables it flips, as a bit mask. The weight is then the fault's log-likelihood, the
+/// matched edges predict which observables the error flipped, and the graph can sample its
+/// own errors.
+#[derive(Clone, Debug, Default)]
+pub struct DecodingGraph {
+    edges: Vec<(usize, usize, f64)>,
+    /// Fault probability of each edge, zero for edges added with `add_edge`
+    probabilities: Vec<f64>,
+    /// Observables each edge flips, bit i for observable i
+    observables: Vec<u64>,
+    adjacency: Vec<Vec<usize>>,
+    boundary: Vec<bool>,
+}
+
+impl DecodingGraph {
+    pub fn new(num_nodes: usize) -> Self {
+        Self {
+            edges: Vec::new(),
+            probabilities: Vec::new(),
+            observables: Vec::new(),
+            adjacency: vec![Vec::new(); num_nodes],
+            boundary: vec![false; num_nodes],
+        }
+    }
+
+    /// Adds an edge between two nodes and returns its index
+    pub fn add_edge(&mut self, a: usize, b: usize, weight: f64) -> usize {
+        assert!(weight >= 0.0, "edge weights must be non-negative");
+        let edge = self.edges.len();
+        self.edges.push((a, b, weight));
+        self.probabilities.push(0.0);
+        self.observables.push(0);
+        self.adjacency[a].push(edge);
+        self.adjacency[b].push(edge);
+        edge
+    }
+
+    /// Adds the edge of a fault that occurs with `probability`, trips nodes a and b and flips
+    /// the observables in the mask `observables`, weighted by its log-likelihood
+    pub fn add_fault(&mut self, a: usize, b: usize, probability: f64, observables: u64) -> usize {
+        let edge = self.add_edge(a, b, log_likelihood_weight(probability));
+        self.probabilities[edge] = probability;
+        self.observables[edge] = observables;
+        edge
+    }
+
+    /// Changes the weight of an edge, leaving its fault probability as it was
+    pub fn set_weight(&mut self, edge: usize, weight: f64) {
+        assert!(weight >= 0.0, "edge weights must be non-negative");
+        self.edges[edge].2 = weight;
+    }
+
+    pub fn set_boundary(&mut self, node: usize) {
+        self.boundary[node] = true;
+    }
+
+    pub fn is_boundary(&self, node: usize) -> bool {
+        self.boundary[node]
+    }
+
+    pub fn num_nodes(&self) -> usize {
+        self.adjacency.len()
+    }
+
+    pub fn num_edges(&self) -> usize {
+        self.edges.len()
+    }
+
+    /// Endpoints and weight of an edge
+    pub fn edge(&self, edge: usize) -> (usize, usize, f64) {
+        self.edges[edge]
+    }
+
+    pub fn edge_probability(&self, edge: usize) -> f64 {
+        self.probabilities[edge]
+    }
+
+    pub fn edge_observables(&self, edge: usize) -> u64 {
+        self.observables[edge]
+    }
+
+    /// The observables flipped by the faults of all these edges together
+    pub fn observables_of(&self, edges: &[usize]) -> u64 {
+        edges.iter().fold(0, |mask, &edge| mask ^ self.observables[edge])
+    }
+
+    /// The observables the error behind `defects` is predicted to have flipped
+    pub fn predict_observables(&self, defects: &[usize]) -> u64 {
+        self.observables_of(&self.match_defects(defects))
+    }
+
+    /// Fires every fault edge independently with its probability, returning the non-boundary
+    /// nodes left with odd parity and the observables flipped
+    pub fn sample(&self) -> (Vec<usize>, u64) {
+        let mut parity = vec![false; self.num_nodes()];
+        let mut observables = 0;
+        for (edge, &(a, b, _)) in self.edges.iter().enumerate() {
+            if self.probabilities[edge] > 0.0 && rand::random::<f64>() < self.probabilities[edge] {
+                parity[a] ^= true;
+                parity[b] ^= true;
+                observables ^= self.observables[edge];
+            }
+        }
+        let defects = (0..self.num_nodes()).filter(|&node| parity[node] && !self.boundary[node]).collect();
+        (defects, observables)
+    }
+
+    /// The space-time graph of `rounds` noisy measurement rounds followed by a perfect one,
+    /// for a phenomenological memory experiment.
+    ///
+    /// Each node becomes a detector per round, comparing it with the round before, so node v
+    /// of round t has index t · `num_nodes` + v. The faults of this graph happen between
+    /// rounds and connect detectors of the same round; a measurement error at round t, with
+    /// probability `measurement_error_probability`, connects v in rounds t and t + 1.
+    pub fn repeated(&self, rounds: usize, measurement_error_probability: f64) -> DecodingGraph {
+        let nodes = self.num_nodes();
+        let mut graph = DecodingGraph::new(nodes * (rounds + 1));
+        for round in 0..=rounds {
+            for node in (0..nodes).filter(|&node| self.boundary[node]) {
+                graph.set_boundary(round * nodes + node);
+            }
+        }
+        for round in 0..rounds {
+            let offset = round * nodes;
+            for (edge, &(a, b, weight)) in self.edges.iter().enumerate() {
+                let copy = graph.add_edge(offset + a, offset + b, weight);
+                graph.probabilities[copy] = self.probabilities[edge];
+                graph.observables[copy] = self.observables[edge];
+            }
+            for node in (0..nodes).filter(|&node| !self.boundary[node]) {
+                graph.add_fault(offset + node, offset + nodes + node, measurement_error_probability, 0);
+            }
+        }
+        graph
+    }
+
+    /// Dijkstra's algorithm from `source`
+    pub fn shortest_paths(&self, source: usize) -> ShortestPaths {
+        self.dijkstra(&[source])
+    }
+
+    /// Joins the defects in pairs or to the boundary so that the total path weight is
+    /// minimal, returning every edge crossed an odd number of times.
+    ///
+    /// Distances to the boundary come from one search out of every boundary node. Each
+    /// defect then searches its surroundings only as far as a partner could be worth more
+    /// than the boundary, and for at most a few dozen other defects. This keeps large
+    /// space-time graphs tractable, and only gives up optimality when a defect's best
+    /// partner is not among its nearest ones; the tests check it against an exact matching
+    /// on random space-time graphs with up to 41 defects.
+    ///
+    /// The pairing itself is an O(n³) blossom on two vertices per defect, and dominates at
+    /// scale. On the 25-round, d = 25 XZZX memory experiment at p = 2% (16 250 nodes and
+    /// about 1300 defects) a shot takes 0.6–0.9 s in a release build, against under 10 ms
+    /// for `union_find`, so this is meant for studies rather than real-time decoding there.
+    pub fn match_defects(&self, defects: &[usize]) -> Vec<usize> {
+        let boundary_nodes: Vec<usize> = (0..self.num_nodes()).filter(|&node| self.boundary[node]).collect();
+        let boundary_paths = (!boundary_nodes.is_empty()).then(|| self.dijkstra(&boundary_nodes));
+        let boundary_distances: Vec<f64> = defects
+            .iter()
+            .map(|&defect| boundary_paths.as_ref().map_or(f64::INFINITY, |paths| paths.distance(defect)))
+            .collect();
+        // A defect that can reach another can reach the boundary whenever that one can
+        let farthest_boundary =
+            boundary_distances.iter().copied().filter(|distance| distance.is_finite()).fold(0.0, f64::max);
+
+        let mut defect_index = vec![None; self.num_nodes()];
+        for (i, &defect) in defects.iter().enumerate() {
+            defect_index[defect] = Some(i);
+        }
+        let mut scratch = SearchScratch::new(self.num_nodes());
+        let searches: Vec<LocalSearch> = defects
+            .iter()
+            .zip(&boundary_distances)
+            .map(|(&defect, &to_boundary)| {
+                self.local_search(defect, &defect_index, to_boundary + farthest_boundary, &mut scratch)
+            })
+            .collect();
+
+        let mut distances = vec![vec![f64::INFINITY; defects.len()]; defects.len()];
+        for (i, search) in searches.iter().enumerate() {
+            for &(j, distance) in &search.defects {
+                distances[i][j] = distances[i][j].min(distance);
+                distances[j][i] = distances[j][i].min(distance);
+            }
+        }
+
+        let mut flipped = vec![false; self.num_edges()];
+        for pairing in min_weight_pairing(&distances, &boundary_distances) {
+            let path = match pairing {
+                Pairing::Pair(i, j) if searches[i].defects.iter().any(|&(found, _)| found == j) => {
+                    searches[i].path_to(self, defects[j])
+                }
+                Pairing::Pair(i, j) => searches[j].path_to(self, defects[i]),
+                Pairing::Boundary(i) => match &boundary_paths {
+                    Some(paths) => paths.path_to(self, defects[i]),
+                    None => continue,
+                },
+            };
+            for edge in path {
+                flipped[edge] ^= true;
+            }
+        }
+
+        (0..self.num_edges()).filter(|&edge| flipped[edge]).collect()
+    }
+
+    /// Dijkstra's algorithm from several sources at once; paths lead back to the nearest one
+    fn dijkstra(&self, sources: &[usize]) -> ShortestPaths {
+        let mut distance = vec![f64::INFINITY; self.num_nodes()];
+        let mut previous = vec![None; self.num_nodes()];
+        let mut queue = BinaryHeap::new();
+        for &source in sources {
+            distance[source] = 0.0;
+            queue.push(Candidate { distance: 0.0, node: source });
+        }
+
+        while let Some(Candidate { distance: reached, node }) = queue.pop() {
+            if reached > distance[node] {
+                continue;
+            }
+            for &edge in &self.adjacency[node] {
+                let (a, b, weight) = self.edges[edge];
+                let next = if a == node { b } else { a };
+                if reached + weight < distance[next] {
+                    distance[next] = reached + weight;
+                    previous[next] = Some(edge);
+                    queue.push(Candidate { distance: distance[next], node: next });
+                }
+            }
+        }
+
+        ShortestPaths { source: sources[0], distance, previous }
+    }
+
+    /// Dijkstra's algorithm from `source`, stopped at distance `radius` or once
+    /// `MATCHING_NEIGHBOURS` other defects are settled. Only the nodes it reaches are stored.
+    fn local_search(
+        &self,
+        source: usize,
+        defect_index: &[Option<usize>],
+        radius: f64,
+        scratch: &mut SearchScratch,
+    ) -> LocalSearch {
+        let mut queue = BinaryHeap::new();
+        let mut defects = Vec::new();
+        scratch.reach(source, 0.0, None);
+        queue.push(Candidate { distance: 0.0, node: source });
+
+        while let Some(Candidate { distance: reached, node }) = queue.pop() {
+            if reached > scratch.distance[node] {
+                continue;
+            }
+            if reached > radius {
+                break;
+            }
+            if let Some(index) = defect_index[node].filter(|_| node != source) {
+                defects.push((index, reached));
+                if defects.len() >= MATCHING_NEIGHBOURS {
+                    break;
+                }
+            }
+            for &edge in &self.adjacency[node] {
+                let (a, b, weight) = self.edges[edge];
+                let next = if a == node { b } else { a };
+                if reached + weight < scratch.distance[next] {
+                    scratch.reach(next, reached + weight, Some(edge));
+                    queue.push(Candidate { distance: reached + weight, node: next });
+                }
+            }
+        }
+
+        LocalSearch { defects, previous: scratch.take_previous() }
+    }
+}
+
+/// Distances and incoming edges of a `local_search`, kept between searches so that each one
+/// only pays for the nodes it reaches
+struct SearchScratch {
+    distance: Vec<f64>,
+    previous: Vec<Option<usize>>,
+    reached: Vec<usize>,
+}
+
+impl SearchScratch {
+    fn new(num_nodes: usize) -> Self {
+        Self {
+            distance: vec![f64::INFINITY; num_nodes],
+            previous: vec![None; num_nodes],
+            reached: Vec::new(),
+        }
+    }
+
+    fn reach(&mut self, node: usize, distance: f64, edge: Option<usize>) {
+        if self.distance[node].is_infinite() {
+            self.reached.push(node);
+        }
+        self.distance[node] = distance;
+        self.previous[node] = edge;
+    }
+
+    /// The edge each reached node was reached through, sorted by node, leaving the scratch
+    /// ready for the next search
+    fn take_previous(&mut self) -> Vec<(usize, usize)> {
+        let mut previous: Vec<(usize, usize)> = self
+            .reached
+            .iter()
+            .filter_map(|&node| self.previous[node].map(|edge| (node, edge)))
+            .collect();
+        previous.sort_unstable();
+        for node in self.reached.drain(..) {
+            self.distance[node] = f64::INFINITY;
+            self.previous[node] = None;
+        }
+        previous
+    }
+}
+
+/// ln((1 - p) / p), the matching weight of a fault with probability p; impossible faults get
+/// an infinite weight and faults likelier than not a weight of zero
+pub fn log_likelihood_weight(probability: f64) -> f64 {
+    if probability <= 0.0 {
+        return f64::INFINITY;
+    }
+    ((1.0 - probability) / probability).ln().max(0.0)
+}
+
+/// A truncated Dijkstra search from a defect: the other defects it settled, by index and
+/// with their distances, and the edge it reached each node through
+struct LocalSearch {
+    defects: Vec<(usize, f64)>,
+    /// Sorted by node
+    previous: Vec<(usize, usize)>,
+}
+
+impl LocalSearch {
+    fn path_to(&self, graph: &DecodingGraph, node: usize) -> Vec<usize> {
+        let mut path = Vec::new();
+        let mut current = node;
+        while let Ok(found) = self.previous.binary_search_by_key(&current, |&(node, _)| node) {
+            let edge = self.previous[found].1;
+            path.push(edge);
+            let (a, b, _) = graph.edge(edge);
+            current = if a == current { b } else { a };
+        }
+        path
+    }
+}
+
+/// Result of `DecodingGraph::shortest_paths`
+#[derive(Clone, Debug)]
+pub struct ShortestPaths {
+    source: usize,
+    distance: Vec<f64>,
+    previous: Vec<Option<usize>>,
+}
+
+impl ShortestPaths {
+    pub fn source(&self) -> usize {
+        self.source
+    }
+
+    /// Path weight from the source, infinite when `node` cannot be reached
+    pub fn distance(&self, node: usize) -> f64 {
+        self.distance[node]
+    }
+
+    /// Edges on a shortest path from the source to `node`, empty if it cannot be reached
+    pub fn path_to(&self, graph: &DecodingGraph, node: usize) -> Vec<usize> {
+        let mut path = Vec::new();
+        let mut current = node;
+        while let Some(edge) = self.previous[current] {
+            path.push(edge);
+            let (a, b, _) = graph.edge(edge);
+            current = if a == current { b } else { a };
+        }
+        path
+    }
+}
+
+/// Entry of Dijkstra's priority queue, ordered so that the closest node is popped first
+#[derive(Clone, Copy, Debug)]
+struct Candidate {
+    distance: f64,
+    node: usize,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.distance.total_cmp(&self.distance).then_with(|| other.node.cmp(&self.node))
+    }
+}
+
+/// How `min_weight_pairing` resolved a defect
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Pairing {
+    /// Two defects joined to each other
+    Pair(usize, usize),
+    /// A defect joined to the boundary
+    Boundary(usize),
+}
+
+/// Pairs up defects, or sends them to the boundary, minimising the summed distance.
+///
+/// `distances[i][j]` is the cost of joining defects i and j and `boundary_distances[i]`
+/// the cost of joining defect i to the boundary; use `f64::INFINITY` where a move is not
+/// allowed. Small instances are solved by dynamic programming over subsets, larger ones as a
+/// minimum-weight perfect matching with the blossom algorithm. Defects that cannot be
+/// resolved at all are left out.
+pub fn min_weight_pairing(distances: &[Vec<f64>], boundary_distances: &[f64]) -> Vec<Pairing> {
+    let count = distances.len();
+    if count <= EXACT_PAIRING_LIMIT {
+        // best[mask] is the cheapest resolution of the defects in mask, where the lowest
+        // defect of the mask is always resolved first; choice[mask] is its partner, or
+        // None for the boundary
+        let full = (1usize << count) - 1;
+        let mut best = vec![f64::INFINITY; full + 1];
+        let mut choice: Vec<Option<usize>> = vec![None; full + 1];
+        best[0] = 0.0;
+        for mask in 1..=full {
+            let i = mask.trailing_zeros() as usize;
+            best[mask] = best[mask & !(1 << i)] + boundary_distances[i];
+            for (j, &distance) in distances[i].iter().enumerate().skip(i + 1) {
+                if mask & (1 << j) == 0 {
+                    continue;
+                }
+                let cost = best[mask & !(1 << i) & !(1 << j)] + distance;
+                if cost < best[mask] {
+                    best[mask] = cost;
+                    choice[mask] = Some(j);
+                }
+            }
+        }
+
+        if best[full].is_finite() {
+            let mut pairings = Vec::new();
+            let mut mask = full;
+            while mask != 0 {
+                let i = mask.trailing_zeros() as usize;
+                match choice[mask] {
+                    Some(j) => {
+                        pairings.push(Pairing::Pair(i, j));
+                        mask &= !(1 << j);
+                    }
+                    None => pairings.push(Pairing::Boundary(i)),
+                }
+                mask &= !(1 << i);
+            }
+            return pairings;
+        }
+    }
+
+    blossom_pairing(distances, boundary_distances)
+}
+
+/// `min_weight_pairing` as perfect matchings, one per group of defects that could pair with
+/// each other. A pair costing more than sending both defects to the boundary is never
+/// needed, so such pairs do not join groups.
+fn blossom_pairing(distances: &[Vec<f64>], boundary_distances: &[f64]) -> Vec<Pairing> {
+    let count = distances.len();
+    let mut pairs = Vec::new();
+    for (i, row) in distances.iter().enumerate() {
+        for (j, &distance) in row.iter().enumerate().skip(i + 1) {
+            if distance.is_finite() && distance < boundary_distances[i] + boundary_distances[j] {
+                pairs.push((i, j, distance));
+            }
+        }
+    }
+
+    let mut root: Vec<usize> = (0..count).collect();
+    for &(i, j, _) in &pairs {
+        let (a, b) = (find_root(&mut root, i), find_root(&mut root, j));
+        root[a] = b;
+    }
+    let mut members = vec![Vec::new(); count];
+    let mut group_pairs = vec![Vec::new(); count];
+    for i in 0..count {
+        let group = find_root(&mut root, i);
+        members[group].push(i);
+    }
+    for &(i, j, distance) in &pairs {
+        group_pairs[find_root(&mut root, i)].push((i, j, distance));
+    }
+
+    let mut pairings = Vec::new();
+    for (group, pairs) in members.iter().zip(&group_pairs) {
+        match group[..] {
+            [] => {}
+            [i] if boundary_distances[i].is_finite() => pairings.push(Pairing::Boundary(i)),
+            [_] => {}
+            _ => pairings.extend(match_group(group, pairs, boundary_distances)),
+        }
+    }
+    pairings
+}
+
+/// The representative of i's set in a union-find forest, halving the path on the way
+fn find_root(root: &mut [usize], mut i: usize) -> usize {
+    while root[i] != i {
+        root[i] = root[root[i]];
+        i = root[i];
+    }
+    i
+}
+
+/// One group of `blossom_pairing` as a perfect matching. Every defect i gets a boundary copy
+/// i', joined to it by the boundary distance; copies pair up with each other at no cost
+/// wherever their defects could pair, so that defects sent to the boundary leave their
+/// copies an even set to match.
+fn match_group(group: &[usize], pairs: &[(usize, usize, f64)], boundary_distances: &[f64]) -> Vec<Pairing> {
+    let count = group.len();
+    let local: HashMap<usize, usize> = group.iter().enumerate().map(|(local, &i)| (i, local)).collect();
+    let with_boundary = group.iter().any(|&i| boundary_distances[i].is_finite());
+    let mut edges = Vec::new();
+    for (local, &i) in group.iter().enumerate() {
+        if boundary_distances[i].is_finite() {
+            edges.push((local, count + local, boundary_distances[i]));
+        }
+    }
+    for &(i, j, distance) in pairs {
+        edges.push((local[&i], local[&j], distance));
+        if with_boundary {
+            edges.push((count + local[&i], count + local[&j], 0.0));
+        }
+    }
+
+    // The heaviest perfect matching under W − w is the lightest one under w
+    let heaviest = edges.iter().map(|&(_, _, weight)| weight).fold(0.0, f64::max);
+    let scale = if heaviest > 0.0 { WEIGHT_RESOLUTION / heaviest } else { 1.0 };
+    let ceiling = (heaviest * scale).round() as i64 + 1;
+    let integer_edges: Vec<(usize, usize, i64)> = edges
+        .iter()
+        .map(|&(i, j, weight)| (i, j, ceiling - (weight * scale).round() as i64))
+        .collect();
+    let num_vertices = if with_boundary { 2 * count } else { count };
+    let mates = blossom::max_weight_matching(num_vertices, &integer_edges);
+
+    let mut pairings = Vec::new();
+    for (i, &mate) in mates.iter().enumerate().take(count) {
+        match mate {
+            Some(j) if j < count && i < j => pairings.push(Pairing::Pair(group[i], group[j])),
+            Some(j) if j >= count => pairings.push(Pairing::Boundary(group[i])),
+            _ => {}
+        }
+    }
+    pairings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+    use rand::{Rng, SeedableRng};
+
+    /// Pairing cost by dynamic programming over subsets, for any number of defects
+    fn exact_pairing_cost(distances: &[Vec<f64>], boundary_distances: &[f64]) -> f64 {
+        let count = distances.len();
+        let full = (1usize << count) - 1;
+        let mut best = vec![f64::INFINITY; full + 1];
+        best[0] = 0.0;
+        for mask in 1..=full {
+            let i = mask.trailing_zeros() as usize;
+            let rest = mask & !(1 << i);
+            best[mask] = best[rest] + boundary_distances[i];
+            for j in (i + 1..count).filter(|&j| rest & (1 << j) != 0) {
+                best[mask] = best[mask].min(best[rest & !(1 << j)] + distances[i][j]);
+            }
+        }
+        best[full]
+    }
+
+    fn pairing_cost(pairings: &[Pairing], distances: &[Vec<f64>], boundary_distances: &[f64]) -> f64 {
+        let mut resolved = vec![0; distances.len()];
+        let mut cost = 0.0;
+        for &pairing in pairings {
+            match pairing {
+                Pairing::Pair(i, j) => {
+                    resolved[i] += 1;
+                    resolved[j] += 1;
+                    cost += distances[i][j];
+                }
+                Pairing::Boundary(i) => {
+                    resolved[i] += 1;
+                    cost += boundary_distances[i];
+                }
+            }
+        }
+        assert!(resolved.iter().all(|&times| times == 1), "every defect is resolved once: {:?}", pairings);
+        cost
+    }
+
+    /// A d×d grid with random fault probabilities whose left and right columns are boundary
+    /// nodes, repeated over `rounds` rounds
+    fn random_space_time_graph(rng: &mut StdRng, d: usize, rounds: usize) -> DecodingGraph {
+        let mut graph = DecodingGraph::new(d * d);
+        for r in 0..d {
+            graph.set_boundary(r * d);
+            graph.set_boundary(r * d + d - 1);
+            for c in 0..d {
+                if c + 1 < d {
+                    graph.add_fault(r * d + c, r * d + c + 1, rng.gen_range(0.01..0.2), 1);
+                }
+                if r + 1 < d {
+                    graph.add_fault(r * d + c, (r + 1) * d + c, rng.gen_range(0.01..0.2), 0);
+                }
+            }
+        }
+        graph.repeated(rounds, rng.gen_range(0.01..0.2))
+    }
+
+    #[test]
+    fn min_weight_pairing_matches_subset_dynamic_programming() {
+        let mut rng = StdRng::seed_from_u64(43);
+        for trial in 0..100 {
+            let count = 13 + trial % 5;
+            let mut distances = vec![vec![f64::INFINITY; count]; count];
+            for (i, j) in (0..count).flat_map(|i| (i + 1..count).map(move |j| (i, j))) {
+                if rng.gen_bool(0.8) {
+                    let distance = rng.gen_range(0.1..10.0);
+                    distances[i][j] = distance;
+                    distances[j][i] = distance;
+                }
+            }
+            let boundary_distances: Vec<f64> = (0..count)
+                .map(|_| if rng.gen_bool(0.7) { rng.gen_range(0.1..10.0) } else { f64::INFINITY })
+                .collect();
+
+            let expected = exact_pairing_cost(&distances, &boundary_distances);
+            let pairings = min_weight_pairing(&distances, &boundary_distances);
+            if expected.is_finite() {
+                let cost = pairing_cost(&pairings, &distances, &boundary_distances);
+                assert!((cost - expected).abs() < 1e-6, "trial {}: {} against {}", trial, cost, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn match_defects_is_optimal_on_random_space_time_graphs() {
+        let mut rng = StdRng::seed_from_u64(43);
+        for trial in 0..40 {
+            let graph = random_space_time_graph(&mut rng, 7, 6);
+            let mut nodes: Vec<usize> = (0..graph.num_nodes()).filter(|&node| !graph.is_boundary(node)).collect();
+            nodes.shuffle(&mut rng);
+            let defects = &nodes[..2 + trial];
+
+            // Exact pairing on the complete graph of shortest-path distances
+            let paths: Vec<ShortestPaths> = defects.iter().map(|&defect| graph.shortest_paths(defect)).collect();
+            let distances: Vec<Vec<f64>> =
+                paths.iter().map(|from| defects.iter().map(|&to| from.distance(to)).collect()).collect();
+            let boundary_distances: Vec<f64> = paths
+                .iter()
+                .map(|from| {
+                    (0..graph.num_nodes())
+                        .filter(|&node| graph.is_boundary(node))
+                        .map(|node| from.distance(node))
+                        .fold(f64::INFINITY, f64::min)
+                })
+                .collect();
+            let pairings = min_weight_pairing(&distances, &boundary_distances);
+            let expected = pairing_cost(&pairings, &distances, &boundary_distances);
+
+            let matched = graph.match_defects(defects);
+            let mut parity = vec![false; graph.num_nodes()];
+            for &edge in &matched {
+                let (a, b, _) = graph.edge(edge);
+                parity[a] ^= true;
+                parity[b] ^= true;
+            }
+            let lit: Vec<usize> = (0..graph.num_nodes()).filter(|&node| parity[node] && !graph.is_boundary(node)).collect();
+            let mut sorted = defects.to_vec();
+            sorted.sort_unstable();
+            assert_eq!(lit, sorted, "trial {}: the matching explains the defects", trial);
+            let weight: f64 = matched.iter().map(|&edge| graph.edge(edge).2).sum();
+            assert!((weight - expected).abs() < 1e-6, "trial {}: {} against {}", trial, weight, expected);
+        }
+    }
+}
@@ -0,0 +1,552 @@
+//! Edmonds' blossom algorithm for maximum-weight matching in general graphs, in the
+//! O(n³) primal-dual form of Galil, "Efficient algorithms for finding maximum matching in
+//! graphs" (1986), following the structure of Joris van Rantwijk's reference implementation.
+//!
+//! Edges are referred to by index k and their endpoints by 2k and 2k + 1, so that p ^ 1 is
+//! the other end of the edge that endpoint p belongs to. Indices below n are vertices, and
+//! n..2n are slots for non-trivial blossoms. Labels are 1 for S (outer), 2 for T (inner)
+//! and 0 for free.
+
+/// Marks a missing vertex, endpoint, edge or blossom
+const NONE: usize = usize::MAX;
+
+/// A maximum-cardinality matching of greatest total weight among the maximum-cardinality
+/// ones. Integer weights keep the dual variables exact. Returns each vertex's partner.
+pub(super) fn max_weight_matching(num_vertices: usize, edges: &[(usize, usize, i64)]) -> Vec<Option<usize>> {
+    if edges.is_empty() {
+        return vec![None; num_vertices];
+    }
+    let mut search = BlossomSearch::new(num_vertices, edges);
+    search.solve();
+    search
+        .mate
+        .iter()
+        .map(|&p| if p == NONE { None } else { Some(search.endpoint[p]) })
+        .collect()
+}
+
+struct BlossomSearch<'a> {
+    n: usize,
+    edges: &'a [(usize, usize, i64)],
+    /// Vertex at each endpoint
+    endpoint: Vec<usize>,
+    /// Remote endpoints of the edges at each vertex
+    neighbour_ends: Vec<Vec<usize>>,
+    /// Remote endpoint of each vertex's matched edge
+    mate: Vec<usize>,
+    label: Vec<u8>,
+    /// The endpoint through which each labelled vertex or blossom got its label
+    label_end: Vec<usize>,
+    /// Top-level blossom containing each vertex
+    in_blossom: Vec<usize>,
+    blossom_parent: Vec<usize>,
+    /// Sub-blossoms of each blossom in cyclic order, starting from the base
+    blossom_children: Vec<Vec<usize>>,
+    blossom_base: Vec<usize>,
+    /// Endpoints of the edges joining consecutive sub-blossoms
+    blossom_endpoints: Vec<Vec<usize>>,
+    /// Least-slack edge from each free vertex or S-blossom towards an S-blossom
+    best_edge: Vec<usize>,
+    /// Least-slack edges from an S-blossom to each neighbouring S-blossom
+    blossom_best_edges: Vec<Option<Vec<usize>>>,
+    unused_blossoms: Vec<usize>,
+    dual: Vec<i64>,
+    /// Edges known to have zero slack
+    allowed: Vec<bool>,
+    /// S-vertices whose edges still have to be scanned
+    queue: Vec<usize>,
+}
+
+impl<'a> BlossomSearch<'a> {
+    fn new(n: usize, edges: &'a [(usize, usize, i64)]) -> Self {
+        let endpoint = (0..2 * edges.len())
+            .map(|p| if p % 2 == 0 { edges[p / 2].0 } else { edges[p / 2].1 })
+            .collect();
+        let mut neighbour_ends = vec![Vec::new(); n];
+        for (k, &(i, j, _)) in edges.iter().enumerate() {
+            neighbour_ends[i].push(2 * k + 1);
+            neighbour_ends[j].push(2 * k);
+        }
+        let max_weight = edges.iter().map(|&(_, _, weight)| weight).max().unwrap_or(0).max(0);
+        let mut dual = vec![max_weight; n];
+        dual.resize(2 * n, 0);
+
+        Self {
+            n,
+            edges,
+            endpoint,
+            neighbour_ends,
+            mate: vec![NONE; n],
+            label: vec![0; 2 * n],
+            label_end: vec![NONE; 2 * n],
+            in_blossom: (0..n).collect(),
+            blossom_parent: vec![NONE; 2 * n],
+            blossom_children: vec![Vec::new(); 2 * n],
+            blossom_base: (0..n).chain(std::iter::repeat_n(NONE, n)).collect(),
+            blossom_endpoints: vec![Vec::new(); 2 * n],
+            best_edge: vec![NONE; 2 * n],
+            blossom_best_edges: vec![None; 2 * n],
+            unused_blossoms: (n..2 * n).collect(),
+            dual,
+            allowed: vec![false; edges.len()],
+            queue: Vec::new(),
+        }
+    }
+
+    fn slack(&self, k: usize) -> i64 {
+        let (i, j, weight) = self.edges[k];
+        self.dual[i] + self.dual[j] - 2 * weight
+    }
+
+    fn leaves(&self, b: usize) -> Vec<usize> {
+        let mut leaves = Vec::new();
+        let mut pending = vec![b];
+        while let Some(b) = pending.pop() {
+            if b < self.n {
+                leaves.push(b);
+            } else {
+                pending.extend(self.blossom_children[b].iter().rev());
+            }
+        }
+        leaves
+    }
+
+    /// Labels the top-level blossom of `w` with `t`, reached through endpoint `p`; a T-blossom
+    /// passes an S label on to its mate
+    fn assign_label(&mut self, w: usize, t: u8, p: usize) {
+        let b = self.in_blossom[w];
+        self.label[w] = t;
+        self.label[b] = t;
+        self.label_end[w] = p;
+        self.label_end[b] = p;
+        self.best_edge[w] = NONE;
+        self.best_edge[b] = NONE;
+        if t == 1 {
+            let leaves = self.leaves(b);
+            self.queue.extend(leaves);
+        } else {
+            let base_mate = self.mate[self.blossom_base[b]];
+            self.assign_label(self.endpoint[base_mate], 1, base_mate ^ 1);
+        }
+    }
+
+    /// Traces back from two S-vertices joined by an edge. Returns the base of the blossom
+    /// they close, or NONE when their trees differ and an augmenting path was found.
+    fn scan_blossom(&mut self, mut v: usize, mut w: usize) -> usize {
+        let mut path = Vec::new();
+        let mut base = NONE;
+        while v != NONE || w != NONE {
+            let b = self.in_blossom[v];
+            if self.label[b] & 4 != 0 {
+                base = self.blossom_base[b];
+                break;
+            }
+            path.push(b);
+            self.label[b] = 5;
+            if self.label_end[b] == NONE {
+                v = NONE;
+            } else {
+                let t = self.in_blossom[self.endpoint[self.label_end[b]]];
+                v = self.endpoint[self.label_end[t]];
+            }
+            if w != NONE {
+                std::mem::swap(&mut v, &mut w);
+            }
+        }
+        for b in path {
+            self.label[b] = 1;
+        }
+        base
+    }
+
+    /// Shrinks the odd cycle closed by edge k, with the given base, into a new S-blossom
+    fn add_blossom(&mut self, base: usize, k: usize) {
+        let (v, w, _) = self.edges[k];
+        let bb = self.in_blossom[base];
+        let mut bv = self.in_blossom[v];
+        let mut bw = self.in_blossom[w];
+        let b = self.unused_blossoms.pop().expect("there are at most n blossoms");
+        self.blossom_base[b] = base;
+        self.blossom_parent[b] = NONE;
+        self.blossom_parent[bb] = b;
+
+        let mut path = Vec::new();
+        let mut endpoints = Vec::new();
+        while bv != bb {
+            self.blossom_parent[bv] = b;
+            path.push(bv);
+            endpoints.push(self.label_end[bv]);
+            bv = self.in_blossom[self.endpoint[self.label_end[bv]]];
+        }
+        path.push(bb);
+        path.reverse();
+        endpoints.reverse();
+        endpoints.push(2 * k);
+        while bw != bb {
+            self.blossom_parent[bw] = b;
+            path.push(bw);
+            endpoints.push(self.label_end[bw] ^ 1);
+            bw = self.in_blossom[self.endpoint[self.label_end[bw]]];
+        }
+        self.blossom_children[b] = path.clone();
+        self.blossom_endpoints[b] = endpoints;
+        self.label[b] = 1;
+        self.label_end[b] = self.label_end[bb];
+        self.dual[b] = 0;
+
+        for v in self.leaves(b) {
+            if self.label[self.in_blossom[v]] == 2 {
+                // Former T-vertices become S-vertices and have to be scanned
+                self.queue.push(v);
+            }
+            self.in_blossom[v] = b;
+        }
+
+        let mut best_edge_to = vec![NONE; 2 * self.n];
+        for &child in &path {
+            let edge_lists: Vec<Vec<usize>> = match self.blossom_best_edges[child].take() {
+                Some(list) => vec![list],
+                None => self
+                    .leaves(child)
+                    .iter()
+                    .map(|&v| self.neighbour_ends[v].iter().map(|p| p / 2).collect())
+                    .collect(),
+            };
+            for k in edge_lists.into_iter().flatten() {
+                let (i, j, _) = self.edges[k];
+                let far = if self.in_blossom[j] == b { i } else { j };
+                let bj = self.in_blossom[far];
+                if bj != b
+                    && self.label[bj] == 1
+                    && (best_edge_to[bj] == NONE || self.slack(k) < self.slack(best_edge_to[bj]))
+                {
+                    best_edge_to[bj] = k;
+                }
+            }
+            self.best_edge[child] = NONE;
+        }
+        let best_edges: Vec<usize> = best_edge_to.into_iter().filter(|&k| k != NONE).collect();
+        self.best_edge[b] = NONE;
+        for &k in &best_edges {
+            if self.best_edge[b] == NONE || self.slack(k) < self.slack(self.best_edge[b]) {
+                self.best_edge[b] = k;
+            }
+        }
+        self.blossom_best_edges[b] = Some(best_edges);
+    }
+
+    /// Undoes blossom b, relabelling its sub-blossoms if it was a T-blossom mid-stage
+    fn expand_blossom(&mut self, b: usize, end_of_stage: bool) {
+        let children = self.blossom_children[b].clone();
+        for &child in &children {
+            self.blossom_parent[child] = NONE;
+            if child < self.n {
+                self.in_blossom[child] = child;
+            } else if end_of_stage && self.dual[child] == 0 {
+                self.expand_blossom(child, end_of_stage);
+            } else {
+                for v in self.leaves(child) {
+                    self.in_blossom[v] = child;
+                }
+            }
+        }
+
+        if !end_of_stage && self.label[b] == 2 {
+            // Relabel the even-length path from the entry child to the base as alternating
+            // T and S sub-blossoms
+            let endpoints = self.blossom_endpoints[b].clone();
+            let entry_child = self.in_blossom[self.endpoint[self.label_end[b] ^ 1]];
+            let (mut j, step, trick) = cycle_walk(&children, entry_child);
+            let at = |j: isize| wrap(j, children.len());
+            let mut p = self.label_end[b];
+            while j != 0 {
+                self.label[self.endpoint[p ^ 1]] = 0;
+                self.label[self.endpoint[endpoints[at(j - trick)] ^ trick as usize ^ 1]] = 0;
+                self.assign_label(self.endpoint[p ^ 1], 2, p);
+                self.allowed[endpoints[at(j - trick)] / 2] = true;
+                j += step;
+                p = endpoints[at(j - trick)] ^ trick as usize;
+                self.allowed[p / 2] = true;
+                j += step;
+            }
+            let bv = children[at(j)];
+            self.label[self.endpoint[p ^ 1]] = 2;
+            self.label[bv] = 2;
+            self.label_end[self.endpoint[p ^ 1]] = p;
+            self.label_end[bv] = p;
+            self.best_edge[bv] = NONE;
+            j += step;
+
+            // The rest of the cycle is free, except for vertices reached from outside
+            while children[at(j)] != entry_child {
+                let bv = children[at(j)];
+                j += step;
+                if self.label[bv] == 1 {
+                    continue;
+                }
+                if let Some(v) = self.leaves(bv).into_iter().find(|&v| self.label[v] != 0) {
+                    self.label[v] = 0;
+                    self.label[self.endpoint[self.mate[self.blossom_base[bv]]]] = 0;
+                    self.assign_label(v, 2, self.label_end[v]);
+                }
+            }
+        }
+
+        self.label[b] = 0;
+        self.label_end[b] = NONE;
+        self.blossom_children[b].clear();
+        self.blossom_endpoints[b].clear();
+        self.blossom_base[b] = NONE;
+        self.blossom_best_edges[b] = None;
+        self.best_edge[b] = NONE;
+        self.unused_blossoms.push(b);
+    }
+
+    /// Flips the matched edges along the even path from vertex v to the base of blossom b,
+    /// making v the new base
+    fn augment_blossom(&mut self, b: usize, v: usize) {
+        let mut t = v;
+        while self.blossom_parent[t] != b {
+            t = self.blossom_parent[t];
+        }
+        if t >= self.n {
+            self.augment_blossom(t, v);
+        }
+
+        let children = self.blossom_children[b].clone();
+        let endpoints = self.blossom_endpoints[b].clone();
+        let i = children.iter().position(|&child| child == t).expect("t is a child of b");
+        let (mut j, step, trick) = cycle_walk(&children, t);
+        let at = |j: isize| wrap(j, children.len());
+        while j != 0 {
+            j += step;
+            let t = children[at(j)];
+            let p = endpoints[at(j - trick)] ^ trick as usize;
+            if t >= self.n {
+                self.augment_blossom(t, self.endpoint[p]);
+            }
+            j += step;
+            let t = children[at(j)];
+            if t >= self.n {
+                self.augment_blossom(t, self.endpoint[p ^ 1]);
+            }
+            self.mate[self.endpoint[p]] = p ^ 1;
+            self.mate[self.endpoint[p ^ 1]] = p;
+        }
+
+        self.blossom_children[b].rotate_left(i);
+        self.blossom_endpoints[b].rotate_left(i);
+        self.blossom_base[b] = self.blossom_base[self.blossom_children[b][0]];
+    }
+
+    /// Flips the matched edges along the augmenting path through edge k
+    fn augment_matching(&mut self, k: usize) {
+        let (v, w, _) = self.edges[k];
+        for (mut s, mut p) in [(v, 2 * k + 1), (w, 2 * k)] {
+            loop {
+                let bs = self.in_blossom[s];
+                if bs >= self.n {
+                    self.augment_blossom(bs, s);
+                }
+                self.mate[s] = p;
+                if self.label_end[bs] == NONE {
+                    // Reached a free root
+                    break;
+                }
+                let t = self.endpoint[self.label_end[bs]];
+                let bt = self.in_blossom[t];
+                s = self.endpoint[self.label_end[bt]];
+                let j = self.endpoint[self.label_end[bt] ^ 1];
+                if bt >= self.n {
+                    self.augment_blossom(bt, j);
+                }
+                self.mate[j] = self.label_end[bt];
+                p = self.label_end[bt] ^ 1;
+            }
+        }
+    }
+
+    /// Grows alternating trees from every free vertex, adjusting the duals whenever no tight
+    /// edge is left, until a stage ends without an augmenting path
+    fn solve(&mut self) {
+        let n = self.n;
+        for _ in 0..n {
+            self.label.fill(0);
+            self.best_edge.fill(NONE);
+            for list in &mut self.blossom_best_edges[n..] {
+                *list = None;
+            }
+            self.allowed.fill(false);
+            self.queue.clear();
+            for v in 0..n {
+                if self.mate[v] == NONE && self.label[self.in_blossom[v]] == 0 {
+                    self.assign_label(v, 1, NONE);
+                }
+            }
+
+            let mut augmented = false;
+            loop {
+                while !augmented {
+                    let Some(v) = self.queue.pop() else { break };
+                    augmented = self.scan_vertex(v);
+                }
+                if augmented {
+                    break;
+                }
+                if !self.adjust_duals() {
+                    break;
+                }
+            }
+            if !augmented {
+                break;
+            }
+
+            for b in n..2 * n {
+                if self.blossom_parent[b] == NONE
+                    && self.blossom_base[b] != NONE
+                    && self.label[b] == 1
+                    && self.dual[b] == 0
+                {
+                    self.expand_blossom(b, true);
+                }
+            }
+        }
+    }
+
+    /// Follows the tight edges out of S-vertex v. Returns whether the matching was augmented.
+    fn scan_vertex(&mut self, v: usize) -> bool {
+        for p in self.neighbour_ends[v].clone() {
+            let k = p / 2;
+            let w = self.endpoint[p];
+            if self.in_blossom[v] == self.in_blossom[w] {
+                continue;
+            }
+            let mut slack = 0;
+            if !self.allowed[k] {
+                slack = self.slack(k);
+                if slack <= 0 {
+                    self.allowed[k] = true;
+                }
+            }
+            if self.allowed[k] {
+                if self.label[self.in_blossom[w]] == 0 {
+                    self.assign_label(w, 2, p ^ 1);
+                } else if self.label[self.in_blossom[w]] == 1 {
+                    let base = self.scan_blossom(v, w);
+                    if base != NONE {
+                        self.add_blossom(base, k);
+                    } else {
+                        self.augment_matching(k);
+                        return true;
+                    }
+                } else if self.label[w] == 0 {
+                    // w is in a T-blossom but not yet reached itself
+                    self.label[w] = 2;
+                    self.label_end[w] = p ^ 1;
+                }
+            } else if self.label[self.in_blossom[w]] == 1 {
+                let b = self.in_blossom[v];
+                if self.best_edge[b] == NONE || slack < self.slack(self.best_edge[b]) {
+                    self.best_edge[b] = k;
+                }
+            } else if self.label[w] == 0
+                && (self.best_edge[w] == NONE || slack < self.slack(self.best_edge[w]))
+            {
+                self.best_edge[w] = k;
+            }
+        }
+        false
+    }
+
+    /// Changes the duals by the largest step that keeps every slack non-negative, and acts
+    /// on whatever became tight. Returns false when the matching cannot grow any further.
+    fn adjust_duals(&mut self) -> bool {
+        let n = self.n;
+        // 2: an edge from a free vertex to an S-vertex becomes tight. 3: an edge between two
+        // S-blossoms does. 4: a T-blossom's dual reaches zero. 1: none of these, stop.
+        let mut delta_type = 0;
+        let mut delta = 0;
+        let mut delta_edge = NONE;
+        let mut delta_blossom = NONE;
+        for v in 0..n {
+            if self.label[self.in_blossom[v]] == 0 && self.best_edge[v] != NONE {
+                let d = self.slack(self.best_edge[v]);
+                if delta_type == 0 || d < delta {
+                    (delta, delta_type, delta_edge) = (d, 2, self.best_edge[v]);
+                }
+            }
+        }
+        for b in 0..2 * n {
+            if self.blossom_parent[b] == NONE && self.label[b] == 1 && self.best_edge[b] != NONE {
+                let d = self.slack(self.best_edge[b]) / 2;
+                if delta_type == 0 || d < delta {
+                    (delta, delta_type, delta_edge) = (d, 3, self.best_edge[b]);
+                }
+            }
+        }
+        for b in n..2 * n {
+            if self.blossom_base[b] != NONE
+                && self.blossom_parent[b] == NONE
+                && self.label[b] == 2
+                && (delta_type == 0 || self.dual[b] < delta)
+            {
+                (delta, delta_type, delta_blossom) = (self.dual[b], 4, b);
+            }
+        }
+        if delta_type == 0 {
+            delta_type = 1;
+            delta = self.dual[..n].iter().copied().min().unwrap_or(0).max(0);
+        }
+
+        for v in 0..n {
+            match self.label[self.in_blossom[v]] {
+                1 => self.dual[v] -= delta,
+                2 => self.dual[v] += delta,
+                _ => {}
+            }
+        }
+        for b in n..2 * n {
+            if self.blossom_base[b] != NONE && self.blossom_parent[b] == NONE {
+                match self.label[b] {
+                    1 => self.dual[b] += delta,
+                    2 => self.dual[b] -= delta,
+                    _ => {}
+                }
+            }
+        }
+
+        match delta_type {
+            1 => return false,
+            2 => {
+                self.allowed[delta_edge] = true;
+                let (i, j, _) = self.edges[delta_edge];
+                let s_vertex = if self.label[self.in_blossom[i]] == 0 { j } else { i };
+                self.queue.push(s_vertex);
+            }
+            3 => {
+                self.allowed[delta_edge] = true;
+                self.queue.push(self.edges[delta_edge].0);
+            }
+            _ => self.expand_blossom(delta_blossom, false),
+        }
+        true
+    }
+}
+
+/// Where a walk around a blossom's cycle from `start` to the base begins, in which direction
+/// it goes and the endpoint offset that direction needs. Odd positions are walked forwards,
+/// with negative indices counting from the end, so that the path to the base is even.
+fn cycle_walk(children: &[usize], start: usize) -> (isize, isize, isize) {
+    let position = children.iter().position(|&child| child == start).expect("start is a child");
+    let position = position as isize;
+    if position & 1 == 1 {
+        (position - children.len() as isize, 1, 0)
+    } else {
+        (position, -1, 1)
+    }
+}
+
+/// A possibly negative cycle index as an index into a slice of length `len`
+fn wrap(index: isize, len: usize) -> usize {
+    index.rem_euclid(len as isize) as usize
+}
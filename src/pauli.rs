@@ -0,0 +1,207 @@
+use crate::gates::{Gate, PauliX, PauliZ};
+use crate::qubit::Qubit;
+use std::fmt;
+use std::str::FromStr;
+
+/// A single-qubit Pauli operator, ignoring global phase.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Pauli {
+    I,
+    X,
+    Y,
+    Z,
+}
+
+impl Pauli {
+    /// Builds a Pauli from its symplectic (x, z) representation
+    pub fn from_bits(x: bool, z: bool) -> Self {
+        match (x, z) {
+            (false, false) => Pauli::I,
+            (true, false) => Pauli::X,
+            (true, true) => Pauli::Y,
+            (false, true) => Pauli::Z,
+        }
+    }
+
+    /// Whether the operator has an X component (X or Y)
+    pub fn x_bit(self) -> bool {
+        matches!(self, Pauli::X | Pauli::Y)
+    }
+
+    /// Whether the operator has a Z component (Z or Y)
+    pub fn z_bit(self) -> bool {
+        matches!(self, Pauli::Z | Pauli::Y)
+    }
+
+    /// Product of two Paulis, ignoring phase
+    pub fn multiply(self, other: Pauli) -> Pauli {
+        Pauli::from_bits(self.x_bit() ^ other.x_bit(), self.z_bit() ^ other.z_bit())
+    }
+
+    pub fn commutes_with(self, other: Pauli) -> bool {
+        !((self.x_bit() && other.z_bit()) ^ (self.z_bit() && other.x_bit()))
+    }
+
+    /// Applies the operator to a qubit using the existing gates
+    pub fn apply(self, qubit: &mut Qubit) {
+        if self.z_bit() {
+            PauliZ.apply(qubit);
+        }
+        if self.x_bit() {
+            PauliX.apply(qubit);
+        }
+    }
+
+    pub fn from_char(c: char) -> Option<Self> {
+        match c {
+            'I' | '_' => Some(Pauli::I),
+            'X' => Some(Pauli::X),
+            'Y' => Some(Pauli::Y),
+            'Z' => Some(Pauli::Z),
+            _ => None,
+        }
+    }
+
+    pub fn to_char(self) -> char {
+        match self {
+            Pauli::I => 'I',
+            Pauli::X => 'X',
+            Pauli::Y => 'Y',
+            Pauli::Z => 'Z',
+        }
+    }
+}
+
+/// An n-qubit Pauli operator stored in symplectic form, ignoring global phase.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PauliString {
+    x: Vec<bool>,
+    z: Vec<bool>,
+}
+
+impl PauliString {
+    /// The identity on `num_qubits` qubits
+    pub fn identity(num_qubits: usize) -> Self {
+        Self {
+            x: vec![false; num_qubits],
+            z: vec![false; num_qubits],
+        }
+    }
+
+    /// Builds an operator from its X and Z bit vectors
+    pub fn from_bits(x: Vec<bool>, z: Vec<bool>) -> Self {
+        assert_eq!(x.len(), z.len(), "X and Z parts must act on the same number of qubits");
+        Self { x, z }
+    }
+
+    /// Places `pauli` on every qubit in `support` and the identity elsewhere
+    pub fn from_support(num_qubits: usize, support: &[usize], pauli: Pauli) -> Self {
+        let mut operator = Self::identity(num_qubits);
+        for &qubit in support {
+            operator.set(qubit, pauli);
+        }
+        operator
+    }
+
+    /// Reads the Pauli frame carried by each qubit, see `Qubit::pauli_frame`
+    pub fn from_frame(qubits: &[Qubit]) -> Self {
+        let mut operator = Self::identity(qubits.len());
+        for (i, qubit) in qubits.iter().enumerate() {
+            operator.set(i, qubit.pauli_frame());
+        }
+        operator
+    }
+
+    /// Applies the operator qubit by qubit
+    pub fn apply_to(&self, qubits: &mut [Qubit]) {
+        for (i, qubit) in qubits.iter_mut().enumerate().take(self.len()) {
+            self.get(i).apply(qubit);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.x.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.x.is_empty()
+    }
+
+    pub fn get(&self, qubit: usize) -> Pauli {
+        Pauli::from_bits(self.x[qubit], self.z[qubit])
+    }
+
+    pub fn set(&mut self, qubit: usize, pauli: Pauli) {
+        self.x[qubit] = pauli.x_bit();
+        self.z[qubit] = pauli.z_bit();
+    }
+
+    pub fn x_bits(&self) -> &[bool] {
+        &self.x
+    }
+
+    pub fn z_bits(&self) -> &[bool] {
+        &self.z
+    }
+
+    /// Number of qubits on which the operator acts non-trivially
+    pub fn weight(&self) -> usize {
+        self.x.iter().zip(&self.z).filter(|(&x, &z)| x || z).count()
+    }
+
+    pub fn is_identity(&self) -> bool {
+        self.weight() == 0
+    }
+
+    /// Indices of the qubits on which the operator acts non-trivially
+    pub fn support(&self) -> Vec<usize> {
+        (0..self.len()).filter(|&i| self.x[i] || self.z[i]).collect()
+    }
+
+    /// Symplectic inner product: true when the two operators commute
+    pub fn commutes_with(&self, other: &PauliString) -> bool {
+        let mut anticommuting = false;
+        for i in 0..self.len() {
+            anticommuting ^= (self.x[i] && other.z[i]) ^ (self.z[i] && other.x[i]);
+        }
+        !anticommuting
+    }
+
+    /// Multiplies `other` into this operator in place, ignoring phase
+    pub fn multiply(&mut self, other: &PauliString) {
+        for i in 0..self.len() {
+            self.x[i] ^= other.x[i];
+            self.z[i] ^= other.z[i];
+        }
+    }
+
+    /// Returns the product of the two operators, ignoring phase
+    pub fn product(&self, other: &PauliString) -> PauliString {
+        let mut result = self.clone();
+        result.multiply(other);
+        result
+    }
+}
+
+impl FromStr for PauliString {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut operator = Self::identity(s.chars().count());
+        for (i, c) in s.chars().enumerate() {
+            let pauli = Pauli::from_char(c)
+                .ok_or_else(|| format!("invalid Pauli character '{}' in \"{}\"", c, s))?;
+            operator.set(i, pauli);
+        }
+        Ok(operator)
+    }
+}
+
+impl fmt::Display for PauliString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for i in 0..self.len() {
+            write!(f, "{}", self.get(i).to_char())?;
+        }
+        Ok(())
+    }
+}
@@ -1,4 +1,6 @@
+use crate::pauli::Pauli;
 use num_complex::Complex64;
+use std::f64::consts::FRAC_PI_8;
 
 #[derive(Clone)]
 pub struct Qubit {
@@ -15,6 +17,22 @@ impl Qubit {
         }
     }
 
+    /// A reference state cos(π/8)|0⟩ + sin(π/8)|1⟩ on which X, Y and Z all act visibly.
+    /// Codes that track errors as a Pauli frame start every physical qubit here.
+    pub fn frame_reference() -> Self {
+        Self {
+            state: [Complex64::new(FRAC_PI_8.cos(), 0.0), Complex64::new(FRAC_PI_8.sin(), 0.0)],
+        }
+    }
+
+    /// Reads back which Pauli has been applied since `frame_reference` (or `new`).
+    /// On |0⟩ a Z is invisible, so only the X component can be recovered there.
+    pub fn pauli_frame(&self) -> Pauli {
+        let x = self.state[0].norm_sqr() < self.state[1].norm_sqr();
+        let z = (self.state[0] * self.state[1].conj()).re < 0.0;
+        Pauli::from_bits(x, z)
+    }
+
     pub fn measure(&self) -> bool {
         let zero_prob = self.state[0].norm_sqr();
         let random = rand::random::<f64>();
@@ -39,6 +57,12 @@ impl Qubit {
     }
 }
 
+impl Default for Qubit {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl PartialEq for Qubit {
     fn eq(&self, other: &Self) -> bool {
         self.state[0] == other.state[0] && self.state[1] == other.state[1]
@@ -0,0 +1,20 @@
+use std::f64::consts::PI;
+
+/// An index drawn with probability proportional to its weight
+pub(crate) fn sample_index(weights: &[f64]) -> usize {
+    let total: f64 = weights.iter().sum();
+    let mut remaining = rand::random::<f64>() * total;
+    for (index, &weight) in weights.iter().enumerate() {
+        if remaining < weight {
+            return index;
+        }
+        remaining -= weight;
+    }
+    weights.iter().rposition(|&weight| weight > 0.0).unwrap_or(0)
+}
+
+/// A standard normal sample, by the Box–Muller transform
+pub(crate) fn standard_normal() -> f64 {
+    let radius = (-2.0 * (1.0 - rand::random::<f64>()).ln()).sqrt();
+    radius * (2.0 * PI * rand::random::<f64>()).cos()
+}
@@ -1,17 +1,56 @@
 use crate::qubit::Qubit;
 use crate::error_models::ErrorModel;
 use crate::correction_codes::CorrectionCode;
+use crate::decoders::{DecodedCode, Decoder};
+use crate::hardware::{GaussianReadout, SoftSyndrome};
+use crate::pauli::Pauli;
 
 pub struct Simulation {
     error_model: Box<dyn ErrorModel>,
     correction_code: Box<dyn CorrectionCode>,
-    num_runs: usize
+    num_runs: usize,
+    readout: Readout,
+    readout_rounds: usize,
+}
+
+/// How the syndrome reaches the decoder
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Readout {
+    /// The syndrome exactly as measured
+    Perfect,
+    /// Outcomes decided from noisy signals, passed on as bare bits that the decoder trusts
+    /// as if they were perfect
+    Hard(GaussianReadout),
+    /// Outcomes decided from noisy signals, all trusted equally: the decoder is told the
+    /// channel's assignment error for each
+    Calibrated(GaussianReadout),
+    /// Outcomes decided from noisy signals, each with the probability that it is wrong given
+    /// its own signal
+    Soft(GaussianReadout),
+}
+
+impl Readout {
+    /// `outcomes` as they reach the decoder, each with the probability it is told that the
+    /// outcome is wrong
+    fn read(&self, outcomes: &[bool]) -> SoftSyndrome {
+        let (outcomes, flip_probabilities) = match self {
+            Readout::Perfect => (outcomes.to_vec(), vec![0.0; outcomes.len()]),
+            Readout::Hard(readout) => (readout.read(outcomes).outcomes, vec![0.0; outcomes.len()]),
+            Readout::Calibrated(readout) => {
+                (readout.read(outcomes).outcomes, vec![readout.assignment_error(); outcomes.len()])
+            }
+            Readout::Soft(readout) => return readout.read(outcomes),
+        };
+        SoftSyndrome { outcomes, flip_probabilities }
+    }
 }
 
 pub struct SimulationResult {
     pub success_rate: f64,
     pub error_rate: f64,
     pub average_correction_time: f64,
+    /// Failure rate of each logical qubit, empty for codes that do not report logical errors
+    pub logical_error_rates: Vec<f64>,
 }
 
 impl Simulation {
@@ -23,35 +62,104 @@ impl Simulation {
         Self {
             error_model,
             correction_code,
-            num_runs
+            num_runs,
+            readout: Readout::Perfect,
+            readout_rounds: 1,
         }
     }
 
+    /// The simulation with `rounds` rounds of syndrome readout through `readout`.
+    ///
+    /// Before each round the error model acts on every physical qubit again, so errors build
+    /// up over the rounds. A final syndrome is then measured perfectly and the code corrects
+    /// from all of them through `correct_soft`, which returns the block to the code space
+    /// before its logical errors are read. A single round read out perfectly is the plain
+    /// simulation.
+    pub fn with_readout(mut self, readout: Readout, rounds: usize) -> Self {
+        assert!(rounds > 0, "readout needs at least one round");
+        self.readout = readout;
+        self.readout_rounds = rounds;
+        self
+    }
+
+    /// A simulation of `correction_code` with its own decoder replaced by `decoder`.
+    ///
+    /// The code is wrapped in a `DecodedCode`, so the syndrome comes from its stabilizer
+    /// generators rather than its own `syndrome_measurement`: a subsystem code hands over
+    /// stabilizer values instead of gauge outcomes, and a `Concatenated` code its lifted
+    /// outer checks as well as the inner ones. The correction times reported are the
+    /// decoder's.
+    pub fn with_decoder(
+        error_model: Box<dyn ErrorModel>,
+        correction_code: Box<dyn CorrectionCode>,
+        decoder: Box<dyn Decoder>,
+        num_runs: usize,
+    ) -> Self {
+        Self::new(error_model, Box::new(DecodedCode::new(correction_code, decoder)), num_runs)
+    }
+
+    /// Runs `num_runs` rounds of encode, noise, syndrome measurement, correction and decode.
+    ///
+    /// The error model acts on every physical qubit of the encoded block, after encoding.
     pub fn run(&mut self) -> SimulationResult {
         // Run the simulation multiple times
         let mut success_count = 0;
+        let mut logical_failure_counts: Vec<usize> = Vec::new();
 
         for _ in 0..self.num_runs {
             // Create a qubit
-            let mut qubit = Qubit::new();
-
-            // Apply the error model
-            self.error_model.apply_error(&mut qubit);
+            let qubit = Qubit::new();
 
             // Encode the qubit using the correction code
             let mut encoded_qubits = self.correction_code.encode(&qubit);
 
-            // Measure the syndromes
-            let syndromes = self.correction_code.syndrome_measurement(&mut encoded_qubits);
+            if self.readout == Readout::Perfect && self.readout_rounds == 1 {
+                // Apply the error model to every physical qubit of the code block
+                for physical_qubit in encoded_qubits.iter_mut() {
+                    self.error_model.apply_error(physical_qubit);
+                }
+
+                // Measure the syndromes and correct the errors
+                let syndromes = self.correction_code.syndrome_measurement(&mut encoded_qubits);
+                self.correction_code.correct(&mut encoded_qubits, syndromes);
+            } else {
+                // Rounds of noise and syndrome measurement, each read out through the channel
+                let mut rounds = Vec::with_capacity(self.readout_rounds);
+                for _ in 0..self.readout_rounds {
+                    for physical_qubit in encoded_qubits.iter_mut() {
+                        self.error_model.apply_error(physical_qubit);
+                    }
+                    let syndromes = self.correction_code.syndrome_measurement(&mut encoded_qubits);
+                    rounds.push(self.readout.read(&syndromes));
+                }
 
-            // Correct the errors
-            self.correction_code.correct(&mut encoded_qubits, syndromes);
+                // A perfect final round, then correct from all of them
+                let final_syndromes = self.correction_code.syndrome_measurement(&mut encoded_qubits);
+                self.correction_code.correct_soft(&mut encoded_qubits, &rounds, final_syndromes);
+            }
+
+            // Record which logical qubits, if any, were flipped
+            let logical_errors = self.correction_code.logical_errors(&encoded_qubits);
+            if logical_failure_counts.len() < logical_errors.len() {
+                logical_failure_counts.resize(logical_errors.len(), 0);
+            }
+            for (count, error) in logical_failure_counts.iter_mut().zip(&logical_errors) {
+                if *error != Pauli::I {
+                    *count += 1;
+                }
+            }
 
             // Decode the qubit
             let decoded_qubit = self.correction_code.decode(&mut encoded_qubits);
 
-            // Check if the decoded qubit is in the correct state
-            if decoded_qubit == qubit {
+            // Codes reporting logical errors succeed when none occurred, the others
+            // when the decoded qubit is in the correct state
+            let success = if logical_errors.is_empty() {
+                decoded_qubit == qubit
+            } else {
+                logical_errors.iter().all(|&error| error == Pauli::I)
+            };
+            if success {
                 success_count += 1;
             }
         }
@@ -60,11 +168,341 @@ impl Simulation {
         let success_rate = success_count as f64 / self.num_runs as f64;
         let error_rate = 1.0 - success_rate;
         let average_correction_time = self.correction_code.get_average_correction_time();
+        let logical_error_rates = logical_failure_counts
+            .iter()
+            .map(|&count| count as f64 / self.num_runs as f64)
+            .collect();
 
         SimulationResult {
             success_rate,
             error_rate,
             average_correction_time,
+            logical_error_rates,
+        }
+    }
+}
+
+/// Logical error rates of a family of codes of growing distance, swept over physical
+/// error rates to locate the threshold
+pub struct ThresholdSweep {
+    pub distances: Vec<usize>,
+    pub physical_error_rates: Vec<f64>,
+    /// Shots behind each logical error rate
+    pub num_runs: usize,
+    /// `logical_error_rates[i][j]` is the failure rate at `distances[i]` and
+    /// `physical_error_rates[j]`
+    pub logical_error_rates: Vec<Vec<f64>>,
+}
+
+/// Threshold fitted to a `ThresholdSweep`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ThresholdEstimate {
+    /// Physical error rate at which the curves cross
+    pub threshold: f64,
+    /// One-sigma statistical uncertainty of `threshold`
+    pub standard_error: f64,
+    /// Finite-size scaling exponent ν
+    pub exponent: f64,
+    /// Weighted sum of squared residuals per degree of freedom; well above 1 means the
+    /// quadratic ansatz does not hold over the sampled rates
+    pub reduced_chi_squared: f64,
+}
+
+/// Scaling exponents tried by `ThresholdSweep::threshold`
+const EXPONENTS: std::ops::RangeInclusive<usize> = 25..=300;
+/// Candidate thresholds tried by `ThresholdSweep::threshold`, across the sampled rates
+const THRESHOLD_STEPS: usize = 400;
+
+impl ThresholdSweep {
+    /// Simulates every pair of distance and physical error rate. `make_code` builds the code
+    /// for a distance and error rate, so that decoders can be tuned to the noise, and
+    /// `make_noise` the error model for an error rate.
+    pub fn run<C, N>(
+        distances: &[usize],
+        physical_error_rates: &[f64],
+        num_runs: usize,
+        make_code: C,
+        make_noise: N,
+    ) -> Self
+    where
+        C: Fn(usize, f64) -> Box<dyn CorrectionCode>,
+        N: Fn(f64) -> Box<dyn ErrorModel>,
+    {
+        let logical_error_rates = distances
+            .iter()
+            .map(|&distance| {
+                physical_error_rates
+                    .iter()
+                    .map(|&rate| {
+                        let mut simulation =
+                            Simulation::new(make_noise(rate), make_code(distance, rate), num_runs);
+                        simulation.run().error_rate
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Self {
+            distances: distances.to_vec(),
+            physical_error_rates: physical_error_rates.to_vec(),
+            num_runs,
+            logical_error_rates,
+        }
+    }
+
+    /// Fits every curve at once to the finite-size scaling ansatz
+    /// `P_L = A + B x + C x²` with `x = (p - p_th) d^(1/ν)`, weighting each point by its
+    /// binomial variance.
+    ///
+    /// The threshold is profiled over a grid spanning the sampled rates, minimising the
+    /// weighted squared residuals over ν in [0.25, 3] and over A, B and C (by linear least
+    /// squares) at each step. The standard error is half the width of the interval where
+    /// the profile stays within 1 of its minimum, the Δχ² = 1 rule, widened by the square
+    /// root of the reduced χ² when the ansatz fits worse than the shot noise allows. It
+    /// only counts shot noise: the ansatz is a Taylor expansion about the threshold, so
+    /// rates far from the crossing bias the estimate and should be left out of the sweep.
+    ///
+    /// `None` without at least three distances, which the fit needs to separate ν from
+    /// p_th, or when the best threshold sits at the edge of the sampled rates, meaning the
+    /// curves do not cross inside them.
+    pub fn threshold(&self) -> Option<ThresholdEstimate> {
+        let points = self.fit_points();
+        if self.distances.len() < 3 || points.len() <= 5 {
+            return None;
+        }
+        let low = self.physical_error_rates.iter().cloned().fold(f64::INFINITY, f64::min);
+        let high = self.physical_error_rates.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        let candidates: Vec<f64> = (0..=THRESHOLD_STEPS)
+            .map(|k| low + (high - low) * k as f64 / THRESHOLD_STEPS as f64)
+            .collect();
+
+        // Best exponent and χ² for every candidate threshold
+        let profile: Vec<(f64, f64)> = candidates
+            .iter()
+            .map(|&threshold| {
+                EXPONENTS
+                    .map(|step| step as f64 / 100.0)
+                    .map(|exponent| (exponent, scaling_chi_squared(&points, threshold, exponent)))
+                    .fold((f64::NAN, f64::INFINITY), |best, fit| if fit.1 < best.1 { fit } else { best })
+            })
+            .collect();
+        let best = (0..profile.len()).min_by(|&a, &b| profile[a].1.total_cmp(&profile[b].1))?;
+        if best == 0 || best == candidates.len() - 1 {
+            return None;
+        }
+
+        let (exponent, chi_squared) = profile[best];
+        let reduced_chi_squared = chi_squared / (points.len() - 5) as f64;
+        let tolerance = reduced_chi_squared.max(1.0);
+        let inside: Vec<f64> = candidates
+            .iter()
+            .zip(&profile)
+            .filter(|(_, &(_, chi))| chi <= chi_squared + tolerance)
+            .map(|(&threshold, _)| threshold)
+            .collect();
+        let (first, last) = (inside[0], inside[inside.len() - 1]);
+
+        Some(ThresholdEstimate {
+            threshold: candidates[best],
+            standard_error: ((last - first) / 2.0).max((high - low) / THRESHOLD_STEPS as f64),
+            exponent,
+            reduced_chi_squared,
+        })
+    }
+
+    /// For each pair of consecutive distances, the physical error rate at which the larger
+    /// first fails more often than the smaller, interpolated linearly between the sampled
+    /// rates. Shot noise moves these around, so they only bracket the threshold, to choose
+    /// the rates of a finer sweep for `threshold`.
+    pub fn crossings(&self) -> Vec<f64> {
+        self.logical_error_rates
+            .windows(2)
+            .filter_map(|pair| {
+                let gaps: Vec<f64> = pair[1].iter().zip(&pair[0]).map(|(l, s)| l - s).collect();
+                (1..gaps.len()).find(|&j| gaps[j - 1] < 0.0 && gaps[j] > 0.0).map(|j| {
+                    let (low, high) = (self.physical_error_rates[j - 1], self.physical_error_rates[j]);
+                    low + (high - low) * gaps[j - 1] / (gaps[j - 1] - gaps[j])
+                })
+            })
+            .collect()
+    }
+
+    /// Distance, physical rate, logical rate and weight of every sampled point
+    fn fit_points(&self) -> Vec<(f64, f64, f64, f64)> {
+        let shots = self.num_runs.max(1) as f64;
+        let mut points = Vec::new();
+        for (distance, rates) in self.distances.iter().zip(&self.logical_error_rates) {
+            for (&p, &logical) in self.physical_error_rates.iter().zip(rates) {
+                // A rate of 0 or 1 has no sampling spread of its own; bound it by one shot
+                let variance = (logical * (1.0 - logical)).max(1.0 / shots) / shots;
+                points.push((*distance as f64, p, logical, 1.0 / variance));
+            }
         }
+        points
     }
-}
\ No newline at end of file
+}
+
+/// Weighted squared residuals of the best quadratic in `x = (p - threshold) d^(1/exponent)`
+fn scaling_chi_squared(points: &[(f64, f64, f64, f64)], threshold: f64, exponent: f64) -> f64 {
+    let scaled = |d: f64, p: f64| (p - threshold) * d.powf(1.0 / exponent);
+    let mut normal = [[0.0; 4]; 3];
+    for &(d, p, logical, weight) in points {
+        let x = scaled(d, p);
+        let basis = [1.0, x, x * x];
+        for row in 0..3 {
+            for col in 0..3 {
+                normal[row][col] += weight * basis[row] * basis[col];
+            }
+            normal[row][3] += weight * basis[row] * logical;
+        }
+    }
+    let Some(coefficients) = solve_3x3(normal) else {
+        return f64::INFINITY;
+    };
+    points
+        .iter()
+        .map(|&(d, p, logical, weight)| {
+            let x = scaled(d, p);
+            let fitted = coefficients[0] + coefficients[1] * x + coefficients[2] * x * x;
+            weight * (logical - fitted).powi(2)
+        })
+        .sum()
+}
+
+/// Solves a 3×3 system given as an augmented matrix, by Gaussian elimination with partial
+/// pivoting; `None` if it is singular
+fn solve_3x3(mut m: [[f64; 4]; 3]) -> Option<[f64; 3]> {
+    for col in 0..3 {
+        let pivot = (col..3).max_by(|&a, &b| m[a][col].abs().total_cmp(&m[b][col].abs()))?;
+        if m[pivot][col].abs() < 1e-300 {
+            return None;
+        }
+        m.swap(col, pivot);
+        let pivot_row = m[col];
+        for (row, entries) in m.iter_mut().enumerate() {
+            if row != col {
+                let factor = entries[col] / pivot_row[col];
+                for (entry, pivot_entry) in entries.iter_mut().zip(pivot_row).skip(col) {
+                    *entry -= factor * pivot_entry;
+                }
+            }
+        }
+    }
+    Some([m[0][3] / m[0][0], m[1][3] / m[1][1], m[2][3] / m[2][2]])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::correction_codes::XzzxCode;
+    use crate::decoders::MatchingDecoder;
+    use crate::error_models::PauliChannel;
+    use std::cell::Cell;
+
+    /// Depolarizing noise from a fixed xorshift sequence, so that two simulations see the
+    /// same errors
+    struct ScriptedNoise {
+        state: Cell<u64>,
+        probability: f64,
+    }
+
+    impl ErrorModel for ScriptedNoise {
+        fn apply_error(&self, qubit: &mut Qubit) {
+            let mut state = self.state.get();
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            self.state.set(state);
+            let random = (state >> 11) as f64 / (1u64 << 53) as f64;
+            let pauli = match (random / self.probability * 3.0) as usize {
+                0 => Pauli::X,
+                1 => Pauli::Y,
+                2 => Pauli::Z,
+                _ => return,
+            };
+            pauli.apply(qubit);
+        }
+    }
+
+    /// The d = 3 XZZX code under matching, with the syndrome read out as given
+    fn scripted_run(readout: Option<(Readout, usize)>) -> SimulationResult {
+        let code = XzzxCode::new(3);
+        let (stabilizers, logicals) = (code.stabilizer_generators(), code.logical_operators());
+        let channel = PauliChannel::new(0.01, 0.01, 0.01);
+        let decoder = MatchingDecoder::new(&stabilizers, &logicals, &channel);
+        let noise = ScriptedNoise { state: Cell::new(0x9e37_79b9_7f4a_7c15), probability: 0.03 };
+        let simulation = Simulation::with_decoder(Box::new(noise), Box::new(code), Box::new(decoder), 2000);
+        let mut simulation = match readout {
+            Some((readout, rounds)) => simulation.with_readout(readout, rounds),
+            None => simulation,
+        };
+        simulation.run()
+    }
+
+    #[test]
+    fn noiseless_readout_reproduces_perfect_readout() {
+        // At this signal-to-noise ratio every outcome is read right and trusted completely
+        let readout = GaussianReadout::with_snr(1e3);
+        let plain = scripted_run(None);
+        assert!(plain.error_rate > 0.0 && plain.error_rate < 0.1, "{}", plain.error_rate);
+        let noisy = [Readout::Hard(readout), Readout::Calibrated(readout), Readout::Soft(readout)];
+        for mode in [Readout::Perfect].into_iter().chain(noisy) {
+            let rates = scripted_run(Some((mode, 1))).logical_error_rates;
+            assert_eq!(rates, plain.logical_error_rates, "{:?}", mode);
+        }
+
+        let perfect = scripted_run(Some((Readout::Perfect, 4)));
+        assert!(perfect.error_rate > plain.error_rate, "{} {}", perfect.error_rate, plain.error_rate);
+        for mode in noisy {
+            let rates = scripted_run(Some((mode, 4))).logical_error_rates;
+            assert_eq!(rates, perfect.logical_error_rates, "{:?}", mode);
+        }
+    }
+
+    /// A sweep whose curves follow the scaling ansatz exactly
+    fn ideal_sweep(threshold: f64, exponent: f64) -> ThresholdSweep {
+        let distances = vec![3, 5, 7, 9];
+        let physical_error_rates: Vec<f64> = (0..=10).map(|i| 0.08 + 0.005 * i as f64).collect();
+        let logical_error_rates = distances
+            .iter()
+            .map(|&d| {
+                physical_error_rates
+                    .iter()
+                    .map(|&p| {
+                        let x = (p - threshold) * (d as f64).powf(1.0 / exponent);
+                        0.2 + 0.8 * x + 2.0 * x * x
+                    })
+                    .collect()
+            })
+            .collect();
+        ThresholdSweep { distances, physical_error_rates, num_runs: 10_000, logical_error_rates }
+    }
+
+    #[test]
+    fn threshold_recovers_the_crossing_of_ideal_curves() {
+        let estimate = ideal_sweep(0.103, 1.5).threshold().unwrap();
+        assert!((estimate.threshold - 0.103).abs() < 1e-3, "{:?}", estimate);
+        assert!((estimate.exponent - 1.5).abs() < 0.05, "{:?}", estimate);
+        assert!(estimate.reduced_chi_squared < 1e-6, "{:?}", estimate);
+    }
+
+    #[test]
+    fn threshold_needs_a_crossing_inside_the_sampled_rates() {
+        assert_eq!(ideal_sweep(0.2, 1.5).threshold(), None);
+    }
+
+    #[test]
+    fn crossings_bracket_the_threshold() {
+        for crossing in ideal_sweep(0.103, 1.5).crossings() {
+            assert!((crossing - 0.103).abs() < 0.005, "{}", crossing);
+        }
+    }
+
+    #[test]
+    fn threshold_needs_three_distances() {
+        let mut sweep = ideal_sweep(0.103, 1.5);
+        sweep.distances.truncate(2);
+        sweep.logical_error_rates.truncate(2);
+        assert_eq!(sweep.threshold(), None);
+    }
+}
@@ -116,4 +116,81 @@ pub fn plot_error_vs_success(
     println!("Chart has been saved to {}", output_file);
 
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Generates a chart of sampled success rates as points over the exact success-rate curves
+pub fn plot_error_vs_success_exact(
+    error_rates: &[f64],
+    bit_flip_success_rates: &[f64],
+    phase_flip_success_rates: &[f64],
+    bit_flip_exact: &[(f64, f64)],
+    phase_flip_exact: &[(f64, f64)],
+    output_file: &str,
+) -> Result<(), Box<dyn Error>> {
+    // Create a drawing area
+    let root = SVGBackend::new(output_file, (800, 600)).into_drawing_area();
+    root.fill(&WHITE)?;
+
+    let max_error_rate = error_rates
+        .iter()
+        .chain(bit_flip_exact.iter().map(|(x, _)| x))
+        .fold(0.0, |max, &val| if val > max { val } else { max });
+
+    let mut chart = ChartBuilder::on(&root)
+        .caption("Sampled vs. Exact Success Rate", ("sans-serif", 30))
+        .margin(5)
+        .x_label_area_size(40)
+        .y_label_area_size(60)
+        .build_cartesian_2d(0.0..(max_error_rate * 1.1), 0.0..110.0)?;
+
+    chart
+        .configure_mesh()
+        .x_desc("Error Rate")
+        .y_desc("Success Rate (%)")
+        .draw()?;
+
+    // Exact curves as lines
+    chart.draw_series(LineSeries::new(
+        bit_flip_exact.iter().map(|&(x, y)| (x, y * 100.0)),
+        &RED,
+    ))?
+        .label("Bit Flip Code (exact)")
+        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], RED));
+
+    chart.draw_series(LineSeries::new(
+        phase_flip_exact.iter().map(|&(x, y)| (x, y * 100.0)),
+        &BLUE,
+    ))?
+        .label("Phase Flip Code (exact)")
+        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], BLUE));
+
+    // Sampled rates as points
+    chart.draw_series(
+        error_rates
+            .iter()
+            .zip(bit_flip_success_rates.iter())
+            .map(|(&x, &y)| Circle::new((x, y * 100.0), 4, RED.filled())),
+    )?
+        .label("Bit Flip Code (sampled)")
+        .legend(|(x, y)| Circle::new((x + 10, y), 4, RED.filled()));
+
+    chart.draw_series(
+        error_rates
+            .iter()
+            .zip(phase_flip_success_rates.iter())
+            .map(|(&x, &y)| Circle::new((x, y * 100.0), 4, BLUE.filled())),
+    )?
+        .label("Phase Flip Code (sampled)")
+        .legend(|(x, y)| Circle::new((x + 10, y), 4, BLUE.filled()));
+
+    chart
+        .configure_series_labels()
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .draw()?;
+
+    root.present()?;
+    println!("Chart has been saved to {}", output_file);
+
+    Ok(())
+}